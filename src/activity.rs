@@ -0,0 +1,136 @@
+//! Per-vault activity log.
+//!
+//! Every vault keeps a short on-chain record of what happened to it: the action, the address
+//! whose authorization performed it (if any) and when. Support and dispute handling can read it
+//! with `get_vault_history` even when an indexer missed the events.
+//!
+//! Entries are only ever appended. The log is bounded at `MAX_ACTIVITY_ENTRIES`: past that the
+//! oldest entry is dropped, and the running `seq` numbers show how many came before. An
+//! archived vault's log is removed with its other side entries in tombstone mode.
+
+use soroban_sdk::{contracttype, Address, Env, Vec};
+
+/// Most entries kept per vault.
+pub const MAX_ACTIVITY_ENTRIES: u32 = 32;
+
+/// Storage key of vault activity logs.
+#[contracttype]
+#[derive(Clone)]
+pub enum ActivityKey {
+    /// Vault id -> its latest [`ActivityEntry`]s, oldest first.
+    History(u32),
+}
+
+/// What a logged action did.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ActivityAction {
+    Created = 0,
+    Validated = 1,
+    Released = 2,
+    Redirected = 3,
+    Cancelled = 4,
+    Snoozed = 5,
+    Amended = 6,
+    DisputeOpened = 7,
+    Paused = 8,
+    Resumed = 9,
+    Frozen = 10,
+    Unfrozen = 11,
+}
+
+/// One logged action.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ActivityEntry {
+    /// Position in the vault's full log, from zero.
+    pub seq: u32,
+    pub action: ActivityAction,
+    /// Address whose authorization performed the action; `None` if permissionless.
+    pub actor: Option<Address>,
+    pub at: u64,
+}
+
+/// Latest logged actions of `vault_id`, oldest first.
+pub fn history(env: &Env, vault_id: u32) -> Vec<ActivityEntry> {
+    env.storage()
+        .persistent()
+        .get(&ActivityKey::History(vault_id))
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+/// Append `action` by `actor` to the log of `vault_id`.
+pub fn record(env: &Env, vault_id: u32, action: ActivityAction, actor: Option<Address>) {
+    let mut entries = history(env, vault_id);
+    let seq = entries.last().map_or(0, |last| last.seq.saturating_add(1));
+    if entries.len() >= MAX_ACTIVITY_ENTRIES {
+        entries.pop_front();
+    }
+    entries.push_back(ActivityEntry {
+        seq,
+        action,
+        actor,
+        at: env.ledger().timestamp(),
+    });
+    env.storage()
+        .persistent()
+        .set(&ActivityKey::History(vault_id), &entries);
+}
+
+/// Drop the log of an archived vault.
+pub fn remove(env: &Env, vault_id: u32) {
+    env.storage()
+        .persistent()
+        .remove(&ActivityKey::History(vault_id));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use soroban_sdk::testutils::Ledger;
+
+    #[test]
+    fn test_history_records_actions_and_actors() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let vault_id = fixture.create_vault();
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        client.validate_milestone(&vault_id, &None);
+        client.release_funds(&vault_id, &fixture.token, &None);
+
+        let history = client.get_vault_history(&vault_id);
+        let actions: Vec<(u32, ActivityAction, Option<Address>)> =
+            history.iter().fold(Vec::new(&env), |mut actions, entry| {
+                actions.push_back((entry.seq, entry.action, entry.actor));
+                actions
+            });
+        assert_eq!(
+            actions,
+            soroban_sdk::vec![
+                &env,
+                (0, ActivityAction::Created, Some(fixture.creator.clone())),
+                (1, ActivityAction::Validated, Some(fixture.verifier.clone())),
+                (2, ActivityAction::Released, None),
+            ]
+        );
+        assert_eq!(history.get(1).unwrap().at, fixture.start_timestamp);
+    }
+
+    #[test]
+    fn test_history_keeps_the_latest_entries() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let vault_id = fixture.create_vault();
+        env.as_contract(&fixture.contract_id, || {
+            for _ in 0..MAX_ACTIVITY_ENTRIES {
+                record(&env, vault_id, ActivityAction::Snoozed, None);
+            }
+            let entries = history(&env, vault_id);
+            assert_eq!(entries.len(), MAX_ACTIVITY_ENTRIES);
+            assert_eq!(entries.first().unwrap().seq, 1);
+            assert_eq!(entries.last().unwrap().seq, MAX_ACTIVITY_ENTRIES);
+        });
+    }
+}
@@ -0,0 +1,244 @@
+//! Aggregated BLS12-381 attestations from verifier committees.
+//!
+//! Instead of M separate verifier transactions, committee members each sign the vault's
+//! attestation message off-chain and anyone submits one aggregated signature. Uses the
+//! "minimal-pubkey-size" variant: public keys in G1, signatures in G2.
+//!
+//! Aggregating public keys is only safe against rogue-key attacks if every member proved
+//! possession of their secret key; creators must check this off-chain before registering a
+//! committee.
+
+use soroban_sdk::{
+    contracttype,
+    crypto::bls12_381::{G1Affine, G2Affine},
+    vec,
+    xdr::ToXdr,
+    Address, Bytes, BytesN, Env, Vec,
+};
+
+/// Domain tag prefixed to every attestation message.
+pub const ATTESTATION_DOMAIN: &[u8] = b"disciplr:attest:v1";
+
+/// Hash-to-curve domain separation tag for attestation signatures.
+pub const ATTESTATION_DST: &[u8] = b"DISCIPLR-V01-CS01-with-BLS12381G2_XMD:SHA-256_SSWU_RO_";
+
+/// Uncompressed BLS12-381 G1 generator (`x || y`, big-endian).
+const G1_GENERATOR: [u8; 96] = [
+    0x17, 0xf1, 0xd3, 0xa7, 0x31, 0x97, 0xd7, 0x94, 0x26, 0x95, 0x63, 0x8c, 0x4f, 0xa9, 0xac, 0x0f,
+    0xc3, 0x68, 0x8c, 0x4f, 0x97, 0x74, 0xb9, 0x05, 0xa1, 0x4e, 0x3a, 0x3f, 0x17, 0x1b, 0xac, 0x58,
+    0x6c, 0x55, 0xe8, 0x3f, 0xf9, 0x7a, 0x1a, 0xef, 0xfb, 0x3a, 0xf0, 0x0a, 0xdb, 0x22, 0xc6, 0xbb,
+    0x08, 0xb3, 0xf4, 0x81, 0xe3, 0xaa, 0xa0, 0xf1, 0xa0, 0x9e, 0x30, 0xed, 0x74, 0x1d, 0x8a, 0xe4,
+    0xfc, 0xf5, 0xe0, 0x95, 0xd5, 0xd0, 0x0a, 0xf6, 0x00, 0xdb, 0x18, 0xcb, 0x2c, 0x04, 0xb3, 0xed,
+    0xd0, 0x3c, 0xc7, 0x44, 0xa2, 0x88, 0x8a, 0xe4, 0x0c, 0xaa, 0x23, 0x29, 0x46, 0xc5, 0xe7, 0xe1,
+];
+
+/// Verifier committee: `threshold` of `public_keys` must sign.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BlsCommittee {
+    pub public_keys: Vec<BytesN<96>>,
+    pub threshold: u32,
+}
+
+/// The standard G1 generator as an SDK point.
+pub fn g1_generator(env: &Env) -> G1Affine {
+    G1Affine::from_bytes(BytesN::from_array(env, &G1_GENERATOR))
+}
+
+/// Message committee members sign to validate `vault_id`:
+/// `ATTESTATION_DOMAIN || contract_xdr || u32_be(vault_id) || milestone_hash`.
+pub fn attestation_message(
+    env: &Env,
+    contract: &Address,
+    vault_id: u32,
+    milestone_hash: &BytesN<32>,
+) -> Bytes {
+    let mut message = Bytes::from_slice(env, ATTESTATION_DOMAIN);
+    message.append(&contract.clone().to_xdr(env));
+    message.extend_from_array(&vault_id.to_be_bytes());
+    message.append(&Bytes::from(milestone_hash.clone()));
+    message
+}
+
+/// Verify `signature` over `message` by the members at `signers` (strictly increasing indices
+/// into `committee.public_keys`, at least `threshold` of them).
+pub fn verify_aggregate(
+    env: &Env,
+    committee: &BlsCommittee,
+    signers: &Vec<u32>,
+    message: &Bytes,
+    signature: &BytesN<192>,
+) -> bool {
+    if signers.len() < committee.threshold || signers.is_empty() {
+        return false;
+    }
+    let bls = env.crypto().bls12_381();
+    let mut aggregate_pk: Option<G1Affine> = None;
+    let mut previous: Option<u32> = None;
+    for index in signers.iter() {
+        if previous.is_some_and(|p| index <= p) || index >= committee.public_keys.len() {
+            return false;
+        }
+        previous = Some(index);
+        let pk = G1Affine::from_bytes(committee.public_keys.get_unchecked(index));
+        aggregate_pk = Some(match aggregate_pk {
+            Some(sum) => bls.g1_add(&sum, &pk),
+            None => pk,
+        });
+    }
+    let Some(aggregate_pk) = aggregate_pk else {
+        return false;
+    };
+
+    let hashed = bls.hash_to_g2(message, &Bytes::from_slice(env, ATTESTATION_DST));
+    // e(g1, sig) · e(-pk, H(m)) == 1
+    bls.pairing_check(
+        vec![env, g1_generator(env), -aggregate_pk],
+        vec![env, G2Affine::from_bytes(signature.clone()), hashed],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use crate::{ActivityAction, Error};
+    use soroban_sdk::{crypto::bls12_381::Fr, testutils::Ledger, U256};
+
+    extern crate std;
+
+    struct Member {
+        sk: Fr,
+        pk: BytesN<96>,
+    }
+
+    fn members(env: &Env, n: u32) -> std::vec::Vec<Member> {
+        (0..n)
+            .map(|i| {
+                let sk = Fr::from_u256(U256::from_u32(env, 1_000 + 7 * i));
+                let pk = (g1_generator(env) * sk.clone()).to_bytes();
+                Member { sk, pk }
+            })
+            .collect()
+    }
+
+    fn aggregate_sign(env: &Env, signers: &[&Member], message: &Bytes) -> BytesN<192> {
+        let bls = env.crypto().bls12_381();
+        let hashed = bls.hash_to_g2(message, &Bytes::from_slice(env, ATTESTATION_DST));
+        let mut sum: Option<G2Affine> = None;
+        for member in signers {
+            let sig = hashed.clone() * member.sk.clone();
+            sum = Some(match sum {
+                Some(s) => bls.g2_add(&s, &sig),
+                None => sig,
+            });
+        }
+        sum.unwrap().to_bytes()
+    }
+
+    #[test]
+    fn test_committee_threshold_attestation_validates_milestone() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        env.cost_estimate().budget().reset_unlimited();
+        let m = members(&env, 3);
+        let committee = BlsCommittee {
+            public_keys: vec![&env, m[0].pk.clone(), m[1].pk.clone(), m[2].pk.clone()],
+            threshold: 2,
+        };
+        let params = fixture.params();
+        crate::testutils::mint(&env, &fixture.token, &params.creator, params.amount);
+        let vault_id = client.create_vault_with_committee(&params, &committee);
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        let message = client.attestation_message(&vault_id);
+
+        assert_eq!(
+            client.try_validate_milestone(&vault_id, &None),
+            Err(Ok(Error::NotAuthorized))
+        );
+
+        // One signer is below threshold.
+        let single = aggregate_sign(&env, &[&m[0]], &message);
+        assert_eq!(
+            client.try_validate_with_attestation(&vault_id, &vec![&env, 0], &single),
+            Err(Ok(Error::InvalidAttestation))
+        );
+        // Signature by 0 and 2 claimed as 0 and 1.
+        let sig = aggregate_sign(&env, &[&m[0], &m[2]], &message);
+        assert_eq!(
+            client.try_validate_with_attestation(&vault_id, &vec![&env, 0, 1], &sig),
+            Err(Ok(Error::InvalidAttestation))
+        );
+        // Duplicate signer indices are rejected.
+        assert_eq!(
+            client.try_validate_with_attestation(&vault_id, &vec![&env, 0, 0], &sig),
+            Err(Ok(Error::InvalidAttestation))
+        );
+
+        assert!(client.validate_with_attestation(&vault_id, &vec![&env, 0, 2], &sig));
+        assert!(
+            client
+                .get_vault_state(&vault_id)
+                .unwrap()
+                .milestone_validated
+        );
+        let last = client.get_vault_history(&vault_id).last().unwrap();
+        assert_eq!((last.action, last.actor), (ActivityAction::Validated, None));
+    }
+
+    #[test]
+    fn test_attestation_cannot_be_replayed_once_validated() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        env.cost_estimate().budget().reset_unlimited();
+        let m = members(&env, 1);
+        let committee = BlsCommittee {
+            public_keys: vec![&env, m[0].pk.clone()],
+            threshold: 1,
+        };
+        let params = fixture.params();
+        crate::testutils::mint(&env, &fixture.token, &params.creator, params.amount);
+        let vault_id = client.create_vault_with_committee(&params, &committee);
+        crate::testutils::mint(&env, &fixture.token, &params.creator, 100);
+        client.fund_bonus(&vault_id, &params.creator, &100, &10);
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        let sig = aggregate_sign(&env, &[&m[0]], &client.attestation_message(&vault_id));
+
+        assert!(client.validate_with_attestation(&vault_id, &vec![&env, 0], &sig));
+        let validated_at = client.get_bonus(&vault_id).unwrap().validated_at;
+        let history = client.get_vault_history(&vault_id).len();
+        env.ledger().set_timestamp(fixture.start_timestamp + 10);
+        assert_eq!(
+            client.try_validate_with_attestation(&vault_id, &vec![&env, 0], &sig),
+            Err(Ok(Error::VaultNotActive))
+        );
+        assert_eq!(client.get_vault_history(&vault_id).len(), history);
+        assert_eq!(
+            client.get_bonus(&vault_id).unwrap().validated_at,
+            validated_at
+        );
+    }
+
+    #[test]
+    fn test_committee_shape_is_validated_at_creation() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let m = members(&env, 2);
+        let params = fixture.params();
+        crate::testutils::mint(&env, &fixture.token, &params.creator, params.amount);
+
+        for threshold in [0, 3] {
+            let committee = BlsCommittee {
+                public_keys: vec![&env, m[0].pk.clone(), m[1].pk.clone()],
+                threshold,
+            };
+            assert_eq!(
+                client.try_create_vault_with_committee(&params, &committee),
+                Err(Ok(Error::InvalidCommittee))
+            );
+        }
+    }
+}
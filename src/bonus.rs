@@ -0,0 +1,182 @@
+//! Early-completion bonuses funded by a sponsor or the protocol.
+//!
+//! Anyone may escrow a bonus pool on an `Active` vault before it is validated. If the milestone
+//! is validated before the deadline, the vault earns `bps_per_day` of its stake for every full
+//! day early (capped at the pool) and the bonus is paid to the success destination when the
+//! vault is released. Whatever is not earned, including the whole pool of a vault that fails or
+//! is cancelled, is clawed back to the funder when the vault settles.
+//!
+//! "Early" is measured against the deadline at funding time, so later snoozes or pauses cannot
+//! inflate the bonus.
+
+use soroban_sdk::{contracttype, Address};
+
+use crate::{
+    math::{self, Rounding},
+    BPS_DENOMINATOR,
+};
+
+/// Length of one bonus day.
+pub const BONUS_DAY_SECS: u64 = 24 * 60 * 60;
+
+/// Bonus pool escrowed for one vault.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VaultBonus {
+    pub funder: Address,
+    /// Escrowed pool; the most the vault can earn.
+    pub amount: i128,
+    /// Bonus per full day early, in bps of the vault's stake.
+    pub bps_per_day: u32,
+    /// The vault's `end_timestamp` when the pool was funded.
+    pub deadline: u64,
+    /// When the milestone was validated, once it has been.
+    pub validated_at: Option<u64>,
+}
+
+/// `true` if `bps_per_day` is a usable schedule rate.
+pub fn is_valid_rate(bps_per_day: u32) -> bool {
+    (1..=BPS_DENOMINATOR).contains(&bps_per_day)
+}
+
+/// Bonus earned by a stake of `stake` validated at `validated_at`: `bps_per_day` per full day
+/// before `bonus.deadline`, rounded down and capped at the pool.
+pub fn earned(bonus: &VaultBonus, stake: i128, validated_at: u64) -> Option<i128> {
+    let days_early = bonus.deadline.saturating_sub(validated_at) / BONUS_DAY_SECS;
+    let bps = (bonus.bps_per_day as u64).saturating_mul(days_early);
+    let earned = math::mul_div(
+        stake,
+        bps.min(i128::MAX as u64) as i128,
+        BPS_DENOMINATOR as i128,
+        Rounding::Down,
+    )?;
+    Some(earned.min(bonus.amount))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use crate::Error;
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger},
+        token::TokenClient,
+        Env,
+    };
+
+    fn bonus(env: &Env, amount: i128, bps_per_day: u32, deadline: u64) -> VaultBonus {
+        VaultBonus {
+            funder: Address::generate(env),
+            amount,
+            bps_per_day,
+            deadline,
+            validated_at: None,
+        }
+    }
+
+    #[test]
+    fn test_earned_counts_full_days_and_caps_at_pool() {
+        let env = Env::default();
+        let pool = bonus(&env, 500, 100, 10 * BONUS_DAY_SECS);
+        // 3.5 days early counts as 3.
+        assert_eq!(earned(&pool, 1_000, 6 * BONUS_DAY_SECS + 1), Some(30));
+        assert_eq!(earned(&pool, 1_000, 10 * BONUS_DAY_SECS - 1), Some(0));
+        assert_eq!(earned(&pool, 1_000, 20 * BONUS_DAY_SECS), Some(0));
+        assert_eq!(earned(&pool, 100_000, 0), Some(500));
+        assert!(!is_valid_rate(0));
+        assert!(!is_valid_rate(BPS_DENOMINATOR + 1));
+    }
+
+    #[test]
+    fn test_early_release_pays_bonus_and_claws_back_rest() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let token = TokenClient::new(&env, &fixture.token);
+        let funder = Address::generate(&env);
+        crate::testutils::mint(&env, &fixture.token, &funder, 100_000);
+
+        let params = crate::VaultParams {
+            end_timestamp: fixture.start_timestamp + 10 * BONUS_DAY_SECS,
+            ..fixture.params()
+        };
+        let vault_id = fixture.create_vault_with(&params);
+        client.fund_bonus(&vault_id, &funder, &100_000, &50);
+        assert_eq!(
+            client.try_fund_bonus(&vault_id, &funder, &1, &50),
+            Err(Ok(Error::BonusAlreadyFunded))
+        );
+        assert_eq!(
+            client.get_escrow_breakdown(&fixture.token).bonus_pools,
+            100_000
+        );
+
+        // Validated four full days early: 4 * 0.5% of the stake.
+        env.ledger()
+            .set_timestamp(params.end_timestamp - 4 * BONUS_DAY_SECS - 10);
+        client.validate_milestone(&vault_id, &None);
+        let expected = fixture.amount * 200 / 10_000;
+        assert_eq!(client.preview_bonus(&vault_id), expected);
+
+        client.release_funds(&vault_id, &fixture.token, &None);
+        assert_eq!(
+            token.balance(&fixture.success_destination),
+            fixture.amount + expected
+        );
+        assert_eq!(token.balance(&funder), 100_000 - expected);
+        assert_eq!(client.get_bonus(&vault_id), None);
+        assert_eq!(client.get_escrow_breakdown(&fixture.token).bonus_pools, 0);
+    }
+
+    #[test]
+    fn test_failed_vault_returns_whole_pool() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let token = TokenClient::new(&env, &fixture.token);
+        let funder = Address::generate(&env);
+        crate::testutils::mint(&env, &fixture.token, &funder, 300);
+        let vault_id = fixture.create_vault();
+        assert_eq!(
+            client.try_fund_bonus(&vault_id, &funder, &300, &0),
+            Err(Ok(Error::InvalidSchedule))
+        );
+        client.fund_bonus(&vault_id, &funder, &300, &100);
+
+        env.ledger().set_timestamp(fixture.end_timestamp);
+        assert_eq!(
+            client.try_fund_bonus(&vault_id, &funder, &300, &100),
+            Err(Ok(Error::BonusAlreadyFunded))
+        );
+        client.redirect_funds(&vault_id, &fixture.token, &None);
+        assert_eq!(token.balance(&funder), 300);
+        assert_eq!(token.balance(&fixture.failure_destination), fixture.amount);
+    }
+    #[test]
+    fn test_repeated_validation_keeps_the_first_stamp() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let funder = Address::generate(&env);
+        crate::testutils::mint(&env, &fixture.token, &funder, 100_000);
+        let params = crate::VaultParams {
+            end_timestamp: fixture.start_timestamp + 10 * BONUS_DAY_SECS,
+            ..fixture.params()
+        };
+        let vault_id = fixture.create_vault_with(&params);
+        client.fund_bonus(&vault_id, &funder, &100_000, &50);
+
+        let first = fixture.start_timestamp + BONUS_DAY_SECS;
+        env.ledger().set_timestamp(first);
+        client.validate_milestone(&vault_id, &None);
+        let earned = client.preview_bonus(&vault_id);
+
+        env.ledger().set_timestamp(first + 5 * BONUS_DAY_SECS);
+        client.validate_milestone(&vault_id, &None);
+        assert_eq!(
+            client.get_bonus(&vault_id).unwrap().validated_at,
+            Some(first)
+        );
+        assert_eq!(client.preview_bonus(&vault_id), earned);
+    }
+}
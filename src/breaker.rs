@@ -0,0 +1,192 @@
+//! Circuit breaker on outflow from escrow.
+//!
+//! The admin can cap how much of a token leaves the contract per [`OUTFLOW_WINDOW_SECS`]
+//! window with `set_outflow_cap`. Every payout, burn and claim counts against the window. A
+//! payout that would exceed the cap is not sent: it is credited to the recipient's claimable
+//! balance (`payout_deferred` with reason `rate_limited`), so the settlement still completes and
+//! the vault still closes. Claims pay out at most what is left of the window and fail with
+//! `TimelockPending` once it is spent.
+//!
+//! The window opens with the first outflow after the previous one ended, and resets
+//! `OUTFLOW_WINDOW_SECS` later. After reviewing what is held, the admin can also reset it
+//! early with `reset_outflow_window`. This bounds what an undiscovered settlement bug could
+//! drain in one day to the cap, with no effect on tokens that have no cap.
+
+use soroban_sdk::{contracttype, Address, Env};
+
+use crate::math;
+
+/// Length of one outflow window.
+pub const OUTFLOW_WINDOW_SECS: u64 = 86_400;
+
+/// Storage keys of the outflow caps and windows.
+#[contracttype]
+#[derive(Clone)]
+pub enum BreakerKey {
+    /// Token -> most that may leave escrow per window.
+    OutflowCap(Address),
+    /// Token -> its current [`OutflowWindow`].
+    OutflowWindow(Address),
+}
+
+/// Outflow of one token in the current window.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct OutflowWindow {
+    pub started_at: u64,
+    /// Amount that left escrow since `started_at`.
+    pub outflow: i128,
+}
+
+/// Cap on `token`'s outflow per window, if any.
+pub fn cap(env: &Env, token: &Address) -> Option<i128> {
+    env.storage()
+        .persistent()
+        .get(&BreakerKey::OutflowCap(token.clone()))
+}
+
+/// `token`'s window as of now; a fresh one if the stored window ended.
+pub fn window(env: &Env, token: &Address) -> OutflowWindow {
+    let now = env.ledger().timestamp();
+    let stored: Option<OutflowWindow> = env
+        .storage()
+        .persistent()
+        .get(&BreakerKey::OutflowWindow(token.clone()));
+    match stored {
+        Some(window) if now < window.started_at.saturating_add(OUTFLOW_WINDOW_SECS) => window,
+        _ => OutflowWindow {
+            started_at: now,
+            outflow: 0,
+        },
+    }
+}
+
+/// What may still leave escrow in `token` this window; `None` without a cap.
+pub fn remaining(env: &Env, token: &Address) -> Option<i128> {
+    let cap = cap(env, token)?;
+    Some(cap.saturating_sub(window(env, token).outflow).max(0))
+}
+
+/// Whether `amount` of `token` may leave escrow now.
+pub fn allows(env: &Env, token: &Address, amount: i128) -> bool {
+    remaining(env, token).is_none_or(|left| amount <= left)
+}
+
+/// Count `amount` of `token` against the window. Untracked without a cap.
+pub fn record(env: &Env, token: &Address, amount: i128) {
+    if cap(env, token).is_none() {
+        return;
+    }
+    let mut window = window(env, token);
+    window.outflow = math::add(window.outflow, amount).unwrap_or(i128::MAX);
+    env.storage()
+        .persistent()
+        .set(&BreakerKey::OutflowWindow(token.clone()), &window);
+}
+
+/// End `token`'s window now, so the next outflow opens a fresh one.
+pub fn reset(env: &Env, token: &Address) {
+    env.storage()
+        .persistent()
+        .remove(&BreakerKey::OutflowWindow(token.clone()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use crate::Error;
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger},
+        token::TokenClient,
+    };
+
+    #[test]
+    fn test_outflow_beyond_cap_is_held_until_window_resets() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let token = TokenClient::new(&env, &fixture.token);
+        client.initialize(&Address::generate(&env));
+        let cap = fixture.amount * 3 / 2;
+        client.set_outflow_cap(&fixture.token, &Some(cap));
+        assert_eq!(client.get_outflow_cap(&fixture.token), Some(cap));
+
+        let first = fixture.create_vault();
+        let second = fixture.create_vault();
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        client.validate_milestone(&first, &None);
+        client.validate_milestone(&second, &None);
+        client.release_funds(&first, &fixture.token, &None);
+        client.release_funds(&second, &fixture.token, &None);
+
+        // The second release fits no longer: it settles, but is held as claimable.
+        let success = &fixture.success_destination;
+        assert_eq!(token.balance(success), fixture.amount);
+        assert_eq!(
+            client.get_claimable(success, &fixture.token),
+            fixture.amount
+        );
+        assert_eq!(
+            client.get_outflow_window(&fixture.token).outflow,
+            fixture.amount
+        );
+
+        // Claims pay what is left of the window, then wait for the next one.
+        assert_eq!(client.claim(success, &fixture.token), fixture.amount / 2);
+        assert_eq!(
+            client.try_claim(success, &fixture.token),
+            Err(Ok(Error::TimelockPending))
+        );
+        env.ledger()
+            .set_timestamp(env.ledger().timestamp() + OUTFLOW_WINDOW_SECS);
+        assert_eq!(client.claim(success, &fixture.token), fixture.amount / 2);
+        assert_eq!(token.balance(success), 2 * fixture.amount);
+        assert_eq!(
+            client.get_escrow_breakdown(&fixture.token).pending_claims,
+            0
+        );
+    }
+
+    #[test]
+    fn test_admin_resets_window_and_clears_cap() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        assert_eq!(
+            client.try_set_outflow_cap(&fixture.token, &Some(0)),
+            Err(Ok(Error::NotInitialized))
+        );
+        client.initialize(&Address::generate(&env));
+        assert_eq!(
+            client.try_set_outflow_cap(&fixture.token, &Some(-1)),
+            Err(Ok(Error::InvalidAmount))
+        );
+
+        // A zero cap halts every outflow of the token.
+        client.set_outflow_cap(&fixture.token, &Some(0));
+        let vault_id = fixture.create_vault();
+        let later = fixture.create_vault();
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        client.validate_milestone(&vault_id, &None);
+        client.release_funds(&vault_id, &fixture.token, &None);
+        let success = &fixture.success_destination;
+        assert_eq!(
+            client.get_claimable(success, &fixture.token),
+            fixture.amount
+        );
+
+        client.set_outflow_cap(&fixture.token, &Some(fixture.amount));
+        client.reset_outflow_window(&fixture.token);
+        assert_eq!(client.claim(success, &fixture.token), fixture.amount);
+
+        client.set_outflow_cap(&fixture.token, &None);
+        assert_eq!(client.get_outflow_cap(&fixture.token), None);
+        client.validate_milestone(&later, &None);
+        client.release_funds(&later, &fixture.token, &None);
+        assert_eq!(
+            TokenClient::new(&env, &fixture.token).balance(success),
+            2 * fixture.amount
+        );
+    }
+}
@@ -0,0 +1,70 @@
+//! Burning forfeits instead of paying them to anyone.
+//!
+//! Some creators don't want anyone, not even a charity, to profit from their failure. They name
+//! [`address`] (`get_burn_address`) as the failure destination. Payouts to it are burned with the
+//! token's own `burn`, and tokens that do not support burning are transferred to the address
+//! instead. That address is the one this contract would deploy a contract to with
+//! [`BURN_SALT`], which it never does, so nothing can ever move a balance out of it.
+//!
+//! Because the sink is an ordinary address, it works wherever a failure destination does:
+//! redirects, cancellation penalties, arbiter rulings, team forfeits and hidden destinations.
+
+use soroban_sdk::{token, Address, BytesN, Env};
+
+/// Deployment salt of the burn address. Cohort salts are `sha256` digests, so they never
+/// collide with it.
+pub const BURN_SALT: [u8; 32] = [0; 32];
+
+/// The burn sink of this instance.
+pub fn address(env: &Env) -> Address {
+    env.deployer()
+        .with_current_contract(BytesN::from_array(env, &BURN_SALT))
+        .deployed_address()
+}
+
+/// Burn `amount` of `token` held by this contract, or lock it at the burn address if the token
+/// cannot burn. Returns whether it was burned, or `None` if it could neither be burned nor
+/// moved.
+pub fn burn_or_lock(env: &Env, token: &Address, amount: i128) -> Option<bool> {
+    let client = token::Client::new(env, token);
+    let this = env.current_contract_address();
+    if client.try_burn(&this, &amount).is_ok() {
+        return Some(true);
+    }
+    match client.try_transfer(&this, &address(env), &amount) {
+        Ok(Ok(())) => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use crate::VaultParams;
+    use soroban_sdk::testutils::Ledger;
+    use soroban_sdk::token::TokenClient;
+
+    #[test]
+    fn test_redirect_to_burn_address_burns_the_stake() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let token = TokenClient::new(&env, &fixture.token);
+        let burn = client.get_burn_address();
+        let vault_id = fixture.create_vault_with(&VaultParams {
+            failure_destination: burn.clone(),
+            ..fixture.params()
+        });
+        let supply_before = token.balance(&fixture.contract_id);
+
+        env.ledger().set_timestamp(fixture.end_timestamp);
+        client.redirect_funds(&vault_id, &fixture.token, &None);
+        assert_eq!(
+            token.balance(&fixture.contract_id),
+            supply_before - fixture.amount
+        );
+        assert_eq!(token.balance(&burn), 0);
+        assert_eq!(client.get_escrow_breakdown(&fixture.token).active_escrow, 0);
+    }
+}
@@ -0,0 +1,174 @@
+//! Milestone categories for analytics.
+//!
+//! A creator can tag a vault with a coarse [`Category`] at creation
+//! (`create_vault_with_category`). The tag is announced with `("vault_categorized", vault_id)`
+//! right after `vault_created`, and every categorized vault is counted in its category's
+//! [`CategoryStats`] when it is created and again when it settles. Success rates per category
+//! ("fitness vaults complete 71% of the time") come straight from `get_category_stats`, with no
+//! off-chain classification of milestone hashes.
+//!
+//! The tag is informational: no rule depends on it. Untagged vaults are not counted.
+
+use soroban_sdk::{contracttype, Env};
+
+use crate::VaultStatus;
+
+/// Coarse kind of milestone a vault commits to.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Category {
+    Fitness = 0,
+    Learning = 1,
+    Work = 2,
+    Finance = 3,
+    Health = 4,
+    Other = 5,
+}
+
+/// Storage keys of vault categories.
+#[contracttype]
+#[derive(Clone)]
+pub enum CategoryKey {
+    /// Vault id -> its category.
+    VaultCategory(u32),
+    /// Category -> its [`CategoryStats`].
+    Stats(Category),
+}
+
+/// Lifetime counts of the vaults tagged with one category.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CategoryStats {
+    pub created: u32,
+    pub completed: u32,
+    pub failed: u32,
+    pub cancelled: u32,
+    /// Sum of the stakes of every vault created in the category.
+    pub staked: i128,
+    /// USD value of those stakes at their creation snapshots (see [`crate::valuation`]).
+    pub staked_usd: i128,
+}
+
+impl CategoryStats {
+    /// Completed share of the settled vaults that were completed or failed, in bps; zero
+    /// before any has.
+    pub fn success_rate_bps(&self) -> u32 {
+        let decided = u64::from(self.completed) + u64::from(self.failed);
+        if decided == 0 {
+            return 0;
+        }
+        (u64::from(self.completed) * u64::from(crate::BPS_DENOMINATOR) / decided) as u32
+    }
+}
+
+/// Category of `vault_id`, if it was tagged.
+pub fn get(env: &Env, vault_id: u32) -> Option<Category> {
+    env.storage()
+        .persistent()
+        .get(&CategoryKey::VaultCategory(vault_id))
+}
+
+/// Stats of `category`.
+pub fn stats(env: &Env, category: Category) -> CategoryStats {
+    env.storage()
+        .persistent()
+        .get(&CategoryKey::Stats(category))
+        .unwrap_or_default()
+}
+
+fn update(env: &Env, category: Category, apply: impl FnOnce(&mut CategoryStats)) {
+    let mut stats = stats(env, category);
+    apply(&mut stats);
+    env.storage()
+        .persistent()
+        .set(&CategoryKey::Stats(category), &stats);
+}
+
+/// Tag the new vault `vault_id` (stake `amount`, worth `usd_value`) with `category`.
+pub fn tag(env: &Env, vault_id: u32, category: Category, amount: i128, usd_value: i128) {
+    env.storage()
+        .persistent()
+        .set(&CategoryKey::VaultCategory(vault_id), &category);
+    update(env, category, |stats| {
+        stats.created = stats.created.saturating_add(1);
+        stats.staked = stats.staked.saturating_add(amount);
+        stats.staked_usd = stats.staked_usd.saturating_add(usd_value);
+    });
+}
+
+/// Count the settlement of `vault_id` as `status` in its category, if it has one.
+pub fn record_outcome(env: &Env, vault_id: u32, status: VaultStatus) {
+    let Some(category) = get(env, vault_id) else {
+        return;
+    };
+    update(env, category, |stats| match status {
+        VaultStatus::Completed => stats.completed = stats.completed.saturating_add(1),
+        VaultStatus::Failed => stats.failed = stats.failed.saturating_add(1),
+        VaultStatus::Cancelled => stats.cancelled = stats.cancelled.saturating_add(1),
+        VaultStatus::Active | VaultStatus::Disputed => {}
+    });
+}
+
+/// Drop the tag of an archived vault; its category's stats are kept.
+pub fn remove(env: &Env, vault_id: u32) {
+    env.storage()
+        .persistent()
+        .remove(&CategoryKey::VaultCategory(vault_id));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::{mint, VaultFixture};
+    use soroban_sdk::{
+        testutils::{Events, Ledger},
+        Symbol, TryFromVal, TryIntoVal,
+    };
+
+    #[test]
+    fn test_category_stats_track_outcomes() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let mut ids = [0u32; 3];
+        for id in ids.iter_mut() {
+            mint(&env, &fixture.token, &fixture.creator, fixture.amount);
+            *id = client.create_vault_with_category(&fixture.params(), &Category::Fitness);
+        }
+        let (_, topics, data) = env.events().all().last().unwrap();
+        assert_eq!(
+            Symbol::try_from_val(&env, &topics.get(0).unwrap()),
+            Ok(Symbol::new(&env, "vault_categorized"))
+        );
+        let (_, category): (u32, Category) = data.try_into_val(&env).unwrap();
+        assert_eq!(category, Category::Fitness);
+        fixture.create_vault();
+
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        for id in &ids[..2] {
+            client.validate_milestone(id, &None);
+            client.release_funds(id, &fixture.token, &None);
+        }
+        env.ledger().set_timestamp(fixture.end_timestamp);
+        client.redirect_funds(&ids[2], &fixture.token, &None);
+
+        let stats = client.get_category_stats(&Category::Fitness);
+        assert_eq!(
+            stats,
+            CategoryStats {
+                created: 3,
+                completed: 2,
+                failed: 1,
+                cancelled: 0,
+                staked: 3 * fixture.amount,
+                staked_usd: 0,
+            }
+        );
+        assert_eq!(stats.success_rate_bps(), 6_666);
+        assert_eq!(
+            client.get_category_stats(&Category::Other),
+            CategoryStats::default()
+        );
+        assert_eq!(client.get_vault_category(&ids[0]), Some(Category::Fitness));
+    }
+}
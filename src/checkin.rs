@@ -0,0 +1,227 @@
+//! Location check-ins attested by a designated attestor ("gym 3 times a week").
+//!
+//! A check-in vault splits its life, from `start_timestamp`, into periods of `period_secs`
+//! (the last period may be shorter) and requires `per_period` check-ins in every one. The
+//! creator fixes the schedule and the ed25519 public key of a location attestor at creation
+//! with `create_vault_with_check_ins`.
+//!
+//! The attestor checks the creator's location off chain and signs [`check_in_message`] over
+//! the `sha256` of its signed location attestation, the period it belongs to and a sequence
+//! number. Anyone submits it with `check_in` during that period; raw location data never goes
+//! on chain. Strictly increasing sequence numbers keep an attestation from counting twice,
+//! and the signed period keeps it from counting in another period.
+//!
+//! The check-in that satisfies the last unmet period validates the milestone and releases the
+//! stake in the same call. A vault that misses a period can still be redirected once it
+//! expires.
+
+use soroban_sdk::{contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env, Vec};
+
+use crate::Error;
+
+/// Domain tag prefixed to every check-in message.
+pub const CHECK_IN_DOMAIN: &[u8] = b"disciplr:checkin:v1";
+
+/// Storage key of check-in vaults.
+#[contracttype]
+#[derive(Clone)]
+pub enum CheckInKey {
+    /// Vault id -> its [`CheckInSchedule`].
+    CheckIns(u32),
+}
+
+/// Check-in requirement of a vault and the check-ins counted so far.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CheckInSchedule {
+    /// Ed25519 public key of the location attestor.
+    pub attestor: BytesN<32>,
+    pub period_secs: u64,
+    pub per_period: u32,
+    /// Check-ins counted in each period, from the one starting at `start_timestamp`.
+    pub counts: Vec<u32>,
+    /// Lowest sequence number the next check-in may carry.
+    pub next_seq: u32,
+}
+
+/// A schedule for a vault running from `start` to `end`, with no check-ins yet. Fails with
+/// `InvalidSchedule` for a zero period or requirement, or more than `max_periods` periods.
+pub fn schedule(
+    env: &Env,
+    attestor: BytesN<32>,
+    start: u64,
+    end: u64,
+    period_secs: u64,
+    per_period: u32,
+    max_periods: u32,
+) -> Result<CheckInSchedule, Error> {
+    if period_secs == 0 || per_period == 0 {
+        return Err(Error::InvalidSchedule);
+    }
+    let periods = end.saturating_sub(start).div_ceil(period_secs);
+    if periods > u64::from(max_periods) {
+        return Err(Error::InvalidSchedule);
+    }
+    let mut counts = Vec::new(env);
+    for _ in 0..periods {
+        counts.push_back(0);
+    }
+    Ok(CheckInSchedule {
+        attestor,
+        period_secs,
+        per_period,
+        counts,
+        next_seq: 0,
+    })
+}
+
+impl CheckInSchedule {
+    /// Whether every period has its required check-ins.
+    pub fn is_met(&self) -> bool {
+        self.counts.iter().all(|count| count >= self.per_period)
+    }
+}
+
+/// Check-in schedule of `vault_id`, if it is a check-in vault.
+pub fn get(env: &Env, vault_id: u32) -> Option<CheckInSchedule> {
+    env.storage()
+        .persistent()
+        .get(&CheckInKey::CheckIns(vault_id))
+}
+
+/// Store the check-in schedule of `vault_id`.
+pub fn save(env: &Env, vault_id: u32, schedule: &CheckInSchedule) {
+    env.storage()
+        .persistent()
+        .set(&CheckInKey::CheckIns(vault_id), schedule);
+}
+
+/// Drop the schedule of an archived vault.
+pub fn remove(env: &Env, vault_id: u32) {
+    env.storage()
+        .persistent()
+        .remove(&CheckInKey::CheckIns(vault_id));
+}
+
+/// Message the attestor signs for a check-in of `vault_id`:
+/// `CHECK_IN_DOMAIN || contract_xdr || u32_be(vault_id) || u32_be(period) || u32_be(seq) ||
+/// attestation_hash`.
+pub fn check_in_message(
+    env: &Env,
+    contract: &Address,
+    vault_id: u32,
+    period: u32,
+    seq: u32,
+    attestation_hash: &BytesN<32>,
+) -> Bytes {
+    let mut message = Bytes::from_slice(env, CHECK_IN_DOMAIN);
+    message.append(&contract.clone().to_xdr(env));
+    message.extend_from_array(&vault_id.to_be_bytes());
+    message.extend_from_array(&period.to_be_bytes());
+    message.extend_from_array(&seq.to_be_bytes());
+    message.append(&Bytes::from(attestation_hash.clone()));
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cosign::CosignPolicy;
+    use crate::testutils::{mint, VaultFixture};
+    use crate::{VaultStatus, VerificationMode};
+    use ed25519_dalek::{Signer, SigningKey};
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    const PERIOD: u64 = 450;
+
+    fn sign(env: &Env, key: &SigningKey, message: &Bytes) -> BytesN<64> {
+        let mut buffer = [0u8; 256];
+        let len = message.len() as usize;
+        message.copy_into_slice(&mut buffer[..len]);
+        BytesN::from_array(env, &key.sign(&buffer[..len]).to_bytes())
+    }
+
+    #[test]
+    fn test_check_ins_in_every_period_complete_vault() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let attestor = SigningKey::from_bytes(&[6; 32]);
+        let attestor_key = BytesN::from_array(&env, &attestor.verifying_key().to_bytes());
+        mint(&env, &fixture.token, &fixture.creator, fixture.amount);
+        // 900 seconds in periods of 450: two periods, one check-in each.
+        let vault_id =
+            client.create_vault_with_check_ins(&fixture.params(), &attestor_key, &PERIOD, &1);
+        assert_eq!(
+            client.get_verification_mode(&vault_id),
+            VerificationMode::CheckIn
+        );
+        let submit = |period: u32, seq: u32, byte: u8| {
+            let attestation = BytesN::from_array(&env, &[byte; 32]);
+            let message = client.check_in_message(&vault_id, &period, &seq, &attestation);
+            let signature = sign(&env, &attestor, &message);
+            client.try_check_in(&vault_id, &period, &seq, &attestation, &signature)
+        };
+
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        assert_eq!(submit(0, 0, 1), Ok(Ok(1)));
+        assert_eq!(submit(0, 0, 1), Err(Ok(Error::InvalidAttestation)));
+        // Signed for the next period, submitted too early.
+        assert_eq!(submit(1, 1, 2), Err(Ok(Error::InvalidTimestamp)));
+
+        env.ledger().set_timestamp(fixture.start_timestamp + PERIOD);
+        assert_eq!(submit(1, 1, 2), Ok(Ok(1)));
+        let vault = client.get_vault_state(&vault_id).unwrap();
+        assert_eq!(vault.status, VaultStatus::Completed);
+    }
+
+    #[test]
+    fn test_final_check_in_only_validates_while_cosign_is_pending() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        client.initialize(&Address::generate(&env));
+        client.set_cosign_policy(&Some(CosignPolicy {
+            threshold: fixture.amount,
+            cosigner: Address::generate(&env),
+            delay_secs: 600,
+        }));
+        let attestor = SigningKey::from_bytes(&[6; 32]);
+        let attestor_key = BytesN::from_array(&env, &attestor.verifying_key().to_bytes());
+        mint(&env, &fixture.token, &fixture.creator, fixture.amount);
+        let duration = fixture.end_timestamp - fixture.start_timestamp;
+        let vault_id =
+            client.create_vault_with_check_ins(&fixture.params(), &attestor_key, &duration, &1);
+
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        let attestation = BytesN::from_array(&env, &[1; 32]);
+        let message = client.check_in_message(&vault_id, &0, &0, &attestation);
+        let signature = sign(&env, &attestor, &message);
+        assert_eq!(
+            client.check_in(&vault_id, &0, &0, &attestation, &signature),
+            1
+        );
+        let vault = client.get_vault_state(&vault_id).unwrap();
+        assert!(vault.milestone_validated);
+        assert_eq!(vault.status, VaultStatus::Active);
+
+        client.cosign_settlement(&vault_id);
+        client.release_funds(&vault_id, &fixture.token, &None);
+        assert_eq!(
+            client.get_vault_state(&vault_id).unwrap().status,
+            VaultStatus::Completed
+        );
+    }
+
+    #[test]
+    fn test_schedule_bounds_periods() {
+        let env = Env::default();
+        let key = BytesN::from_array(&env, &[0; 32]);
+        assert!(schedule(&env, key.clone(), 0, 100, 0, 1, 10).is_err());
+        assert!(schedule(&env, key.clone(), 0, 100, 10, 0, 10).is_err());
+        assert!(schedule(&env, key.clone(), 0, 101, 10, 1, 10).is_err());
+        let plan = schedule(&env, key, 0, 95, 10, 2, 10).unwrap();
+        assert_eq!(plan.counts.len(), 10);
+        assert!(!plan.is_met());
+    }
+}
@@ -0,0 +1,294 @@
+//! Contract-wide settings read on hot paths, held in a single instance-storage entry.
+//!
+//! Every option that entry points consult on most calls (admin, treasury, default arbiter, freeze
+//! cap, tombstone mode) lives in `Config` under `DataKey::Config`, so an invocation pays for one
+//! read however many options exist. Entry points that need several settings load it once and
+//! read fields from the local copy.
+//!
+//! Pagination and batch limits are part of `Config` too (`Limits`): every list and batch entry
+//! point reads its cap from there, and the admin can only tighten them below the compile-time
+//! ceilings (`MAX_PAGE_LIMIT`, `MAX_BATCH_SIZE`, `MAX_EXPIRY_QUERY_BUCKETS`,
+//! `MAX_SCAN_LIMIT`).
+//!
+//! Per-address settings and registries (arbiter registry, destination blocklist) stay in their
+//! own persistent entries and are only read when the address in question comes up.
+//!
+//! Every admin mutation, of `Config` or of a registry, emits `("config_changed", field)` with
+//! data `(key, old, new)` via [`publish_change`], so governance actions can be audited from one
+//! event stream. `key` is `()` for `Config` fields and the registry key otherwise.
+
+use soroban_sdk::{contracttype, Address, Env, IntoVal, Symbol, Val};
+
+use crate::{
+    events, DataKey, Error, DEFAULT_MAX_FREEZE_SECS, MAX_BATCH_SIZE, MAX_EXPIRY_QUERY_BUCKETS,
+    MAX_PAGE_LIMIT, MAX_SCAN_LIMIT,
+};
+
+/// Per-call caps applied by list and batch entry points.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Limits {
+    /// Maximum entries returned by one paginated query.
+    pub page_limit: u32,
+    /// Maximum ids or items accepted by one batch call (also caps committee, team and
+    /// verifying-key sizes at creation).
+    pub batch_size: u32,
+    /// Maximum expiry buckets (days) one `get_vaults_expiring_between` call may scan.
+    pub expiry_query_buckets: u64,
+    /// Maximum index entries one filtering query examines before returning a resume cursor.
+    pub scan_limit: u32,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            page_limit: MAX_PAGE_LIMIT,
+            batch_size: MAX_BATCH_SIZE,
+            expiry_query_buckets: MAX_EXPIRY_QUERY_BUCKETS,
+            scan_limit: MAX_SCAN_LIMIT,
+        }
+    }
+}
+
+impl Limits {
+    /// `true` if every limit is at least 1 and at most its compile-time ceiling.
+    pub fn is_valid(&self) -> bool {
+        (1..=MAX_PAGE_LIMIT).contains(&self.page_limit)
+            && (1..=MAX_BATCH_SIZE).contains(&self.batch_size)
+            && (1..=MAX_EXPIRY_QUERY_BUCKETS).contains(&self.expiry_query_buckets)
+            && (1..=MAX_SCAN_LIMIT).contains(&self.scan_limit)
+    }
+}
+
+/// Hot contract configuration.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Config {
+    /// Contract administrator, set once by `initialize`.
+    pub admin: Option<Address>,
+    /// Fallback recipient for forfeits whose destination was never revealed.
+    pub treasury: Option<Address>,
+    /// Arbiter assigned to vaults whose creator does not pick one.
+    pub default_arbiter: Option<Address>,
+    /// Maximum compliance freeze duration before renewal is required.
+    pub max_freeze_secs: u64,
+    /// When `true`, settled vaults are archived to events and replaced by a tombstone.
+    pub tombstone_mode: bool,
+    /// Pagination and batch caps.
+    pub limits: Limits,
+    /// Contract notified after each dispute resolution (see `hooks`).
+    pub settle_hook: Option<Address>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            admin: None,
+            treasury: None,
+            default_arbiter: None,
+            max_freeze_secs: DEFAULT_MAX_FREEZE_SECS,
+            tombstone_mode: false,
+            limits: Limits::default(),
+            settle_hook: None,
+        }
+    }
+}
+
+impl Config {
+    /// The admin, after requiring its authorization.
+    pub fn require_admin(&self) -> Result<Address, Error> {
+        let admin = self.admin.clone().ok_or(Error::NotInitialized)?;
+        admin.require_auth();
+        Ok(admin)
+    }
+}
+
+/// Effective pagination and batch caps.
+pub fn limits(env: &Env) -> Limits {
+    load(env).limits
+}
+
+/// Load the configuration (defaults if nothing has been set yet).
+pub fn load(env: &Env) -> Config {
+    env.storage()
+        .instance()
+        .get(&DataKey::Config)
+        .unwrap_or_default()
+}
+
+/// Store `config`.
+pub fn save(env: &Env, config: &Config) {
+    env.storage().instance().set(&DataKey::Config, config);
+}
+
+/// Emit `("config_changed", field)` with data `(key, old, new)` for an admin mutation.
+pub fn publish_change<K, T>(env: &Env, field: &str, key: K, old: T, new: T)
+where
+    K: IntoVal<Env, Val>,
+    T: IntoVal<Env, Val>,
+{
+    let key: Val = key.into_val(env);
+    let old: Val = old.into_val(env);
+    let new: Val = new.into_val(env);
+    events::publish(
+        env,
+        "config_changed",
+        Symbol::new(env, field),
+        (key, old, new),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use soroban_sdk::{
+        testutils::{Address as _, Events},
+        TryFromVal, TryIntoVal,
+    };
+
+    /// The `config_changed` event of the last invocation as `(field, key, old, new)`.
+    fn last_change(env: &Env) -> (Symbol, Val, Val, Val) {
+        let (_, topics, data) = env.events().all().last().unwrap();
+        let name: Symbol = topics.get(0).unwrap().try_into_val(env).unwrap();
+        assert_eq!(name, Symbol::new(env, "config_changed"));
+        let field: Symbol = topics.get(2).unwrap().try_into_val(env).unwrap();
+        let (_, (key, old, new)): (u32, (Val, Val, Val)) = data.try_into_val(env).unwrap();
+        (field, key, old, new)
+    }
+
+    #[test]
+    fn test_setters_share_one_entry() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let admin = Address::generate(&env);
+        let treasury = Address::generate(&env);
+        client.initialize(&admin);
+        client.set_treasury(&treasury);
+        client.set_max_freeze_secs(&60);
+        client.set_tombstone_mode(&true);
+
+        let stored = env.as_contract(&fixture.contract_id, || load(&env));
+        assert_eq!(
+            stored,
+            Config {
+                admin: Some(admin),
+                treasury: Some(treasury),
+                default_arbiter: None,
+                max_freeze_secs: 60,
+                tombstone_mode: true,
+                limits: Limits::default(),
+                settle_hook: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_defaults_before_initialize() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        assert_eq!(client.get_admin(), None);
+        assert_eq!(client.get_max_freeze_secs(), DEFAULT_MAX_FREEZE_SECS);
+        assert!(!client.get_tombstone_mode());
+    }
+
+    #[test]
+    fn test_limits_are_bounded_by_ceilings() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        client.initialize(&Address::generate(&env));
+        assert_eq!(client.get_limits(), Limits::default());
+
+        for limits in [
+            Limits {
+                page_limit: 0,
+                ..Limits::default()
+            },
+            Limits {
+                batch_size: MAX_BATCH_SIZE + 1,
+                ..Limits::default()
+            },
+            Limits {
+                expiry_query_buckets: MAX_EXPIRY_QUERY_BUCKETS + 1,
+                ..Limits::default()
+            },
+            Limits {
+                scan_limit: 0,
+                ..Limits::default()
+            },
+        ] {
+            assert_eq!(
+                client.try_set_limits(&limits),
+                Err(Ok(Error::InvalidLimits))
+            );
+        }
+
+        let tight = Limits {
+            page_limit: 2,
+            batch_size: 1,
+            expiry_query_buckets: 1,
+            scan_limit: 1,
+        };
+        client.set_limits(&tight);
+        assert_eq!(client.get_limits(), tight);
+
+        for _ in 0..3 {
+            let params = crate::VaultParams {
+                is_public: true,
+                ..fixture.params()
+            };
+            fixture.create_vault_with(&params);
+        }
+        assert_eq!(client.list_public_vaults(&0, &10).len(), 2);
+        assert_eq!(
+            client.try_get_vault_states(&soroban_sdk::vec![&env, 0, 1]),
+            Err(Ok(Error::BatchTooLarge))
+        );
+        assert_eq!(
+            client.try_get_vaults_expiring_between(&0, &crate::EXPIRY_BUCKET_SECS, &0, &10),
+            Err(Ok(Error::BatchTooLarge))
+        );
+    }
+
+    #[test]
+    fn test_admin_mutations_emit_old_and_new_values() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let admin = Address::generate(&env);
+        client.initialize(&admin);
+        let (field, _, old, new) = last_change(&env);
+        assert_eq!(field, Symbol::new(&env, "admin"));
+        assert_eq!(Option::<Address>::try_from_val(&env, &old).unwrap(), None);
+        assert_eq!(
+            Option::<Address>::try_from_val(&env, &new).unwrap(),
+            Some(admin.clone())
+        );
+
+        client.set_max_freeze_secs(&60);
+        let (field, _, old, new) = last_change(&env);
+        assert_eq!(field, Symbol::new(&env, "max_freeze_secs"));
+        assert_eq!(
+            u64::try_from_val(&env, &old).unwrap(),
+            DEFAULT_MAX_FREEZE_SECS
+        );
+        assert_eq!(u64::try_from_val(&env, &new).unwrap(), 60);
+
+        let address = Address::generate(&env);
+        client.block_address(&address);
+        let blocked = env.events().all();
+        let (_, topics, data) = blocked.get(blocked.len() - 2).unwrap();
+        let field: Symbol = topics.get(2).unwrap().try_into_val(&env).unwrap();
+        assert_eq!(field, Symbol::new(&env, "blocklist"));
+        let (_, (key, old, new)): (u32, (Address, bool, bool)) = data.try_into_val(&env).unwrap();
+        assert_eq!((key, old, new), (address, false, true));
+
+        let config = client.get_config();
+        assert_eq!(config.admin, Some(admin));
+        assert_eq!(config.max_freeze_secs, 60);
+        assert_eq!(config, env.as_contract(&fixture.contract_id, || load(&env)));
+    }
+}
@@ -0,0 +1,173 @@
+//! Second approval for large-value settlements.
+//!
+//! A single compromised verifier key could otherwise validate and release a whale-sized escrow
+//! at once. The admin sets a [`CosignPolicy`]: `release_funds`, `redirect_funds` and a final
+//! `reject_milestone` on vaults staking at least `threshold` then need the operations
+//! co-signer's approval (`cosign_settlement`), or must wait until `delay_secs` past the vault's
+//! deadline, which gives the co-signer time to freeze a suspicious vault. A rejection lands
+//! before the deadline, so only the co-signature clears it. Keeper batches skip such vaults
+//! until one of the two holds.
+//!
+//! Dispute rulings are not affected: they already need the arbiter.
+
+use soroban_sdk::{contracttype, Address, Env};
+
+use crate::ProductivityVault;
+
+/// Storage keys of the co-sign policy and approvals.
+#[contracttype]
+#[derive(Clone)]
+pub enum CosignKey {
+    /// The policy (instance storage), absent while settlements need no second approval.
+    CosignPolicy,
+    /// Vault id -> the co-signer that approved its settlement.
+    Cosigned(u32),
+}
+
+/// When a settlement needs a second approval.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CosignPolicy {
+    /// Stakes of at least this amount need a co-signature or the delay.
+    pub threshold: i128,
+    pub cosigner: Address,
+    /// Time after `end_timestamp` from which no co-signature is needed.
+    pub delay_secs: u64,
+}
+
+/// The stored policy, if any.
+pub fn policy(env: &Env) -> Option<CosignPolicy> {
+    env.storage().instance().get(&CosignKey::CosignPolicy)
+}
+
+/// Whether `vault` may be released or redirected now without further approval.
+pub fn is_cleared(env: &Env, vault_id: u32, vault: &ProductivityVault) -> bool {
+    let Some(policy) = policy(env) else {
+        return true;
+    };
+    vault.amount < policy.threshold
+        || env.ledger().timestamp() >= vault.end_timestamp.saturating_add(policy.delay_secs)
+        || env
+            .storage()
+            .persistent()
+            .has(&CosignKey::Cosigned(vault_id))
+}
+
+/// Record the co-signer's approval of `vault_id`'s settlement.
+pub fn approve(env: &Env, vault_id: u32, cosigner: &Address) {
+    env.storage()
+        .persistent()
+        .set(&CosignKey::Cosigned(vault_id), cosigner);
+}
+
+/// Drop the approval of a settled vault.
+pub fn clear(env: &Env, vault_id: u32) {
+    env.storage()
+        .persistent()
+        .remove(&CosignKey::Cosigned(vault_id));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use crate::{Error, SettlementOutcome, VaultStatus};
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    fn setup(env: &Env) -> (VaultFixture, Address) {
+        let fixture = VaultFixture::new(env);
+        let client = fixture.client();
+        client.initialize(&Address::generate(env));
+        let cosigner = Address::generate(env);
+        client.set_cosign_policy(&Some(CosignPolicy {
+            threshold: fixture.amount,
+            cosigner: cosigner.clone(),
+            delay_secs: 600,
+        }));
+        (fixture, cosigner)
+    }
+
+    #[test]
+    fn test_large_release_needs_cosigner() {
+        let env = Env::default();
+        let (fixture, cosigner) = setup(&env);
+        let client = fixture.client();
+        let vault_id = fixture.create_vault();
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        client.validate_milestone(&vault_id, &None);
+        assert_eq!(
+            client.try_release_funds(&vault_id, &fixture.token, &None),
+            Err(Ok(Error::TimelockPending))
+        );
+        assert_eq!(
+            client.try_preview_settlement(&vault_id, &SettlementOutcome::Release),
+            Err(Ok(Error::TimelockPending))
+        );
+
+        client.cosign_settlement(&vault_id);
+        assert_eq!(client.get_settlement_cosigner(&vault_id), Some(cosigner));
+        client.release_funds(&vault_id, &fixture.token, &None);
+        assert_eq!(
+            client.get_vault_state(&vault_id).unwrap().status,
+            VaultStatus::Completed
+        );
+        assert_eq!(client.get_settlement_cosigner(&vault_id), None);
+    }
+
+    #[test]
+    fn test_large_redirect_waits_out_delay_and_small_vaults_pass() {
+        let env = Env::default();
+        let (fixture, _) = setup(&env);
+        let client = fixture.client();
+        let large = fixture.create_vault();
+        let small = fixture.create_vault_with(&crate::VaultParams {
+            amount: fixture.amount - 1,
+            ..fixture.params()
+        });
+
+        env.ledger().set_timestamp(fixture.end_timestamp);
+        client.redirect_funds(&small, &fixture.token, &None);
+        assert_eq!(
+            client.try_redirect_funds(&large, &fixture.token, &None),
+            Err(Ok(Error::TimelockPending))
+        );
+        env.ledger().set_timestamp(fixture.end_timestamp + 600);
+        client.redirect_funds(&large, &fixture.token, &None);
+
+        assert_eq!(
+            client.try_set_cosign_policy(&Some(CosignPolicy {
+                threshold: 0,
+                cosigner: Address::generate(&env),
+                delay_secs: 0,
+            })),
+            Err(Ok(Error::InvalidAmount))
+        );
+        client.set_cosign_policy(&None);
+        assert_eq!(client.get_cosign_policy(), None);
+    }
+
+    #[test]
+    fn test_large_final_rejection_needs_cosigner() {
+        let env = Env::default();
+        let (fixture, _) = setup(&env);
+        let client = fixture.client();
+        let vault_id = fixture.create_vault();
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        assert_eq!(
+            client.try_reject_milestone(&vault_id, &fixture.token, &None),
+            Err(Ok(Error::TimelockPending))
+        );
+        assert_eq!(
+            client.get_vault_state(&vault_id).unwrap().status,
+            VaultStatus::Active
+        );
+
+        client.cosign_settlement(&vault_id);
+        assert!(client.reject_milestone(&vault_id, &fixture.token, &None));
+        assert_eq!(
+            client.get_vault_state(&vault_id).unwrap().status,
+            VaultStatus::Failed
+        );
+        assert_eq!(client.get_settlement_cosigner(&vault_id), None);
+    }
+}
@@ -0,0 +1,110 @@
+//! Verifier delegation to automation.
+//!
+//! A verifier can name one delegate (typically their automation bot) with
+//! `set_verifier_delegate`, and revoke it at any time by setting `None`. The delegate calls
+//! `validate_as_delegate` to validate any vault the verifier verifies, with the same checks as
+//! `validate_milestone`; revocation takes effect on the next call.
+//!
+//! Accountability stays with the verifier: the delegation is per verifier rather than per
+//! vault, and every delegated validation emits `milestone_validated` as usual plus
+//! `("delegated_validation", vault_id)` with data `(verifier, delegate)`, so indexers attribute
+//! it to both.
+
+use soroban_sdk::{contracttype, Address, Env};
+
+/// Storage key of verifier delegations.
+#[contracttype]
+#[derive(Clone)]
+pub enum DelegationKey {
+    /// Verifier -> its delegate.
+    Delegate(Address),
+}
+
+/// Delegate of `verifier`, if any.
+pub fn delegate(env: &Env, verifier: &Address) -> Option<Address> {
+    env.storage()
+        .persistent()
+        .get(&DelegationKey::Delegate(verifier.clone()))
+}
+
+/// Set or clear the delegate of `verifier`.
+pub fn set_delegate(env: &Env, verifier: &Address, delegate: Option<&Address>) {
+    let key = DelegationKey::Delegate(verifier.clone());
+    match delegate {
+        Some(delegate) => env.storage().persistent().set(&key, delegate),
+        None => env.storage().persistent().remove(&key),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use crate::{Error, VaultParams};
+    use soroban_sdk::{
+        testutils::{Address as _, Events, Ledger},
+        Symbol, TryFromVal, TryIntoVal,
+    };
+
+    #[test]
+    fn test_delegate_validates_on_behalf_of_verifier() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let bot = Address::generate(&env);
+        let vault_id = fixture.create_vault();
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        assert_eq!(
+            client.try_validate_as_delegate(&vault_id, &bot, &None),
+            Err(Ok(Error::NotAuthorized))
+        );
+
+        client.set_verifier_delegate(&fixture.verifier, &Some(bot.clone()));
+        assert_eq!(
+            client.get_verifier_delegate(&fixture.verifier),
+            Some(bot.clone())
+        );
+        client.validate_as_delegate(&vault_id, &bot, &None);
+        let (_, topics, data) = env.events().all().last().unwrap();
+        assert_eq!(
+            Symbol::try_from_val(&env, &topics.get(0).unwrap()),
+            Ok(Symbol::new(&env, "delegated_validation"))
+        );
+        let (_, attribution): (u32, (Address, Address)) = data.try_into_val(&env).unwrap();
+        assert_eq!(attribution, (fixture.verifier.clone(), bot));
+        assert!(
+            client
+                .get_vault_state(&vault_id)
+                .unwrap()
+                .milestone_validated
+        );
+    }
+
+    #[test]
+    fn test_revoked_or_foreign_delegate_cannot_validate() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let bot = Address::generate(&env);
+        client.set_verifier_delegate(&fixture.verifier, &Some(bot.clone()));
+        // A vault verified by someone else is out of the delegate's reach.
+        let other = fixture.create_vault_with(&VaultParams {
+            verifier: Some(Address::generate(&env)),
+            ..fixture.params()
+        });
+        let vault_id = fixture.create_vault();
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        assert_eq!(
+            client.try_validate_as_delegate(&other, &bot, &None),
+            Err(Ok(Error::NotAuthorized))
+        );
+
+        client.set_verifier_delegate(&fixture.verifier, &None);
+        assert_eq!(client.get_verifier_delegate(&fixture.verifier), None);
+        assert_eq!(
+            client.try_validate_as_delegate(&vault_id, &bot, &None),
+            Err(Ok(Error::NotAuthorized))
+        );
+        client.validate_milestone(&vault_id, &None);
+    }
+}
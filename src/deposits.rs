@@ -0,0 +1,137 @@
+//! Refundable verifier deposits that reward timely review.
+//!
+//! A creator can ask the verifier to put something at stake too. A vault created with
+//! `create_vault_with_review_deposit` names a deposit amount; the verifier posts it in the
+//! vault's token when accepting the job with `accept_verification`, and it is held as
+//! `verifier_bonds` in the escrow breakdown. When the vault settles:
+//!
+//! - if the verifier acted before the deadline (validated, or rejected the milestone), or the
+//!   creator cancelled, the deposit goes back to the verifier;
+//! - otherwise the verifier ghosted, and the deposit is forfeited to the creator.
+//!
+//! A verifier who never accepts posts nothing; the creator can see that with
+//! `get_verifier_deposit` and cancel in time.
+
+use soroban_sdk::{contracttype, Address, Env};
+
+/// Storage key of verifier deposits.
+#[contracttype]
+#[derive(Clone)]
+pub enum DepositKey {
+    /// Vault id -> its [`VerifierDeposit`].
+    VerifierDeposit(u32),
+}
+
+/// Deposit a vault asks of its verifier, and how far it got.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VerifierDeposit {
+    pub amount: i128,
+    /// Verifier who posted it with `accept_verification`; `None` until then.
+    pub posted_by: Option<Address>,
+    /// `true` once the verifier has rejected the milestone; validation is read off the vault.
+    pub acted: bool,
+}
+
+/// Deposit terms of `vault_id`, if it asks for one.
+pub fn get(env: &Env, vault_id: u32) -> Option<VerifierDeposit> {
+    env.storage()
+        .persistent()
+        .get(&DepositKey::VerifierDeposit(vault_id))
+}
+
+/// Store the deposit terms of `vault_id`.
+pub fn save(env: &Env, vault_id: u32, deposit: &VerifierDeposit) {
+    env.storage()
+        .persistent()
+        .set(&DepositKey::VerifierDeposit(vault_id), deposit);
+}
+
+/// Drop the deposit of a settled vault.
+pub fn remove(env: &Env, vault_id: u32) {
+    env.storage()
+        .persistent()
+        .remove(&DepositKey::VerifierDeposit(vault_id));
+}
+
+/// Record that the verifier of `vault_id` acted on it.
+pub fn mark_acted(env: &Env, vault_id: u32) {
+    if let Some(mut deposit) = get(env, vault_id) {
+        deposit.acted = true;
+        save(env, vault_id, &deposit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::{mint, VaultFixture};
+    use crate::Error;
+    use soroban_sdk::{testutils::Ledger, token::TokenClient};
+
+    const DEPOSIT: i128 = 5_000;
+
+    fn deposit_vault(fixture: &VaultFixture) -> u32 {
+        mint(
+            &fixture.env,
+            &fixture.token,
+            &fixture.creator,
+            fixture.amount,
+        );
+        mint(&fixture.env, &fixture.token, &fixture.verifier, DEPOSIT);
+        let client = fixture.client();
+        let vault_id = client.create_vault_with_review_deposit(&fixture.params(), &DEPOSIT);
+        client.accept_verification(&vault_id);
+        vault_id
+    }
+
+    #[test]
+    fn test_deposit_is_refunded_on_timely_validation() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let token = TokenClient::new(&env, &fixture.token);
+        let vault_id = deposit_vault(&fixture);
+        assert_eq!(token.balance(&fixture.verifier), 0);
+        assert_eq!(
+            client.get_escrow_breakdown(&fixture.token).verifier_bonds,
+            DEPOSIT
+        );
+        assert_eq!(
+            client.try_accept_verification(&vault_id),
+            Err(Ok(Error::InvalidStatus))
+        );
+
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        client.validate_milestone(&vault_id, &None);
+        client.release_funds(&vault_id, &fixture.token, &None);
+        assert_eq!(token.balance(&fixture.verifier), DEPOSIT);
+        assert_eq!(
+            client.get_escrow_breakdown(&fixture.token).verifier_bonds,
+            0
+        );
+        assert_eq!(client.get_verifier_deposit(&vault_id), None);
+    }
+
+    #[test]
+    fn test_ghosting_verifier_forfeits_deposit_to_creator() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let token = TokenClient::new(&env, &fixture.token);
+        let vault_id = deposit_vault(&fixture);
+        assert_eq!(
+            client.get_verifier_deposit(&vault_id).unwrap().posted_by,
+            Some(fixture.verifier.clone())
+        );
+
+        env.ledger().set_timestamp(fixture.end_timestamp);
+        client.redirect_funds(&vault_id, &fixture.token, &None);
+        assert_eq!(token.balance(&fixture.creator), DEPOSIT);
+        assert_eq!(token.balance(&fixture.verifier), 0);
+        assert_eq!(
+            client.get_escrow_breakdown(&fixture.token).verifier_bonds,
+            0
+        );
+    }
+}
@@ -0,0 +1,117 @@
+//! Approved-destination mode for managed programs.
+//!
+//! Employer-run deployments often must only pay stakes to addresses their policy allows (payroll
+//! accounts, approved charities). With the mode on, every success and failure destination must
+//! be on an admin-approved list: checked when a vault or team vault is created and when a hidden
+//! failure destination is revealed, the only ways a destination is set. Treasury fallbacks are
+//! admin-chosen and not checked.
+//!
+//! The list is independent of the blocklist, which applies in either mode. Revoking an approval
+//! or turning the mode on does not touch existing vaults; their destinations were checked when
+//! they were set.
+
+use soroban_sdk::{contracttype, Address, Env};
+
+/// Storage keys of the approved-destination list.
+#[contracttype]
+#[derive(Clone)]
+pub enum DestinationKey {
+    /// `true` while destinations must be approved (instance storage).
+    ApprovedOnly,
+    /// Approved destination -> `true`.
+    Approved(Address),
+}
+
+/// Whether the instance requires approved destinations.
+pub fn approved_only(env: &Env) -> bool {
+    env.storage()
+        .instance()
+        .get(&DestinationKey::ApprovedOnly)
+        .unwrap_or(false)
+}
+
+/// Whether `destination` is on the approved list.
+pub fn is_approved(env: &Env, destination: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&DestinationKey::Approved(destination.clone()))
+}
+
+/// Whether `destination` may be set on a vault under the current mode.
+pub fn is_allowed(env: &Env, destination: &Address) -> bool {
+    !approved_only(env) || is_approved(env, destination)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use crate::{Error, VaultParams};
+    use soroban_sdk::testutils::Address as _;
+
+    #[test]
+    fn test_approved_only_mode_gates_creation() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        client.initialize(&Address::generate(&env));
+        // Off by default: any destination.
+        fixture.create_vault();
+
+        client.set_approved_only(&true);
+        assert!(client.get_approved_only());
+        crate::testutils::mint(&env, &fixture.token, &fixture.creator, fixture.amount);
+        assert_eq!(
+            client.try_create_vault_with_params(&fixture.params()),
+            Err(Ok(Error::DestinationBlocked))
+        );
+        client.approve_destination(&fixture.success_destination);
+        assert_eq!(
+            client.try_create_vault_with_params(&fixture.params()),
+            Err(Ok(Error::DestinationBlocked))
+        );
+        client.approve_destination(&fixture.failure_destination);
+        assert!(client.is_destination_approved(&fixture.failure_destination));
+        let vault_id = client.create_vault_with_params(&fixture.params());
+
+        // Revoking does not strand existing vaults.
+        client.revoke_destination(&fixture.success_destination);
+        assert!(!client.is_destination_approved(&fixture.success_destination));
+        client.cancel_vault(&vault_id, &fixture.token, &None);
+        let params = VaultParams {
+            success_destination: fixture.failure_destination.clone(),
+            ..fixture.params()
+        };
+        fixture.create_vault_with(&params);
+    }
+
+    #[test]
+    fn test_approved_only_mode_checks_revealed_destination() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        client.initialize(&Address::generate(&env));
+        client.set_approved_only(&true);
+        client.approve_destination(&fixture.success_destination);
+        client.approve_destination(&fixture.failure_destination);
+
+        let hidden = Address::generate(&env);
+        let salt = soroban_sdk::BytesN::from_array(&env, &[7; 32]);
+        let commitment = client.compute_failure_commitment(&hidden, &salt);
+        crate::testutils::mint(&env, &fixture.token, &fixture.creator, fixture.amount);
+        let vault_id = client.create_vault_with_hidden_failure(&fixture.params(), &commitment);
+        assert_eq!(
+            client.try_reveal_failure_destination(&vault_id, &hidden, &salt),
+            Err(Ok(Error::DestinationBlocked))
+        );
+        client.approve_destination(&hidden);
+        client.reveal_failure_destination(&vault_id, &hidden, &salt);
+        assert_eq!(
+            client
+                .get_vault_state(&vault_id)
+                .unwrap()
+                .failure_destination,
+            hidden
+        );
+    }
+}
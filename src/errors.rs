@@ -0,0 +1,219 @@
+//! Contract errors and their numeric codes.
+//!
+//! Every failure is reported as a [`Error`] code: the number clients receive, e.g. as
+//! `Error(Contract, #19)` in a failed transaction. Frontends map these numbers to localized
+//! messages, so they are part of the contract's interface:
+//!
+//! - Each variant has an explicit discriminant, and a code never changes meaning across
+//!   versions. Variants are never renumbered, reordered or removed; a retired code stays
+//!   reserved.
+//! - New failures that fit an existing code reuse it, and its doc comment lists every cause
+//!   (the contract spec caps the enum at 50 variants).
+//! - `test_codes_are_stable` below pins every code; changing one fails the build's tests.
+//!
+//! The table in `vesting.md` ("Error codes") lists the same codes for client developers.
+
+use soroban_sdk::contracterror;
+
+/// Contract-specific errors used in revert paths. Follows Soroban error conventions: entry
+/// points return `Result<T, Error>` instead of panicking where appropriate.
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum Error {
+    /// Vault with the given id does not exist.
+    VaultNotFound = 1,
+    /// Caller is not authorized for this operation (e.g. not verifier/creator, or release before deadline without validation).
+    /// Also: a creator denied by the screener, an address that is not the verifier's delegate,
+    /// `accept_admin` without an offer, or a vault naming a withdrawn open verifier.
+    NotAuthorized = 2,
+    /// Vault is not in Active status (e.g. already Completed, Failed, or Cancelled).
+    VaultNotActive = 3,
+    /// Timestamp constraint violated (e.g. redirect before end_timestamp, or invalid time window).
+    InvalidTimestamp = 4,
+    /// Validation is no longer allowed because current time is at or past end_timestamp.
+    MilestoneExpired = 5,
+    /// Vault is in an invalid status for the requested operation. Also: a yield strategy that
+    /// still holds principal is revoked, or fails when recalled.
+    InvalidStatus = 6,
+    /// Amount must be positive (e.g. create_vault amount <= 0), or a yield strategy took a
+    /// different amount than deployed.
+    InvalidAmount = 7,
+    /// start_timestamp must be strictly less than end_timestamp.
+    InvalidTimestamps = 8,
+    /// Team vault definition is invalid (no members, duplicate members, or members/stakes
+    /// length mismatch).
+    InvalidTeam = 9,
+    /// Address is not a member of the team vault.
+    NotTeamMember = 10,
+    /// Pair vaults must be created by two distinct creators, or a stable settlement pair is
+    /// not allowlisted or its router under-delivered.
+    InvalidPair = 11,
+    /// `initialize` has already been called.
+    AlreadyInitialized = 12,
+    /// Contract has no admin yet; call `initialize` first.
+    NotInitialized = 13,
+    /// Arbiter is not in the admin-curated registry.
+    ArbiterNotRegistered = 14,
+    /// Vault has no arbiter, so it cannot be disputed.
+    NoArbiter = 15,
+    /// Fee in basis points exceeds `BPS_DENOMINATOR`, or the cap of its kind (keeper bounty,
+    /// open verifier fee).
+    InvalidFee = 16,
+    /// Vault is under an active compliance freeze; settlement is blocked.
+    VaultFrozen = 17,
+    /// Vault is not frozen.
+    VaultNotFrozen = 18,
+    /// Destination address is on the admin blocklist, is not approved while the instance
+    /// only accepts approved destinations, or is denied by the screener.
+    DestinationBlocked = 19,
+    /// No claimable balance for this address and token, or no principal deployed to the
+    /// strategy being recalled.
+    NothingToClaim = 20,
+    /// Amount arithmetic overflowed or went negative.
+    ArithmeticOverflow = 21,
+    /// Batch request contains more items than the per-call cap.
+    BatchTooLarge = 22,
+    /// Token passed to a settlement call is not the token the vault was funded with.
+    InvalidToken = 23,
+    /// A vault with the requested deterministic id, or a cohort with the requested name,
+    /// already exists; use a different salt or name. Also returned by the duplication guard
+    /// for a vault identical to an active one.
+    VaultIdTaken = 24,
+    /// Operation memo is longer than `MAX_MEMO_LEN` bytes.
+    InvalidMemo = 25,
+    /// `check_invariants` found stored state that violates a contract invariant.
+    InvariantViolated = 26,
+    /// Zero-knowledge proof (or verifying key) is malformed or does not verify.
+    InvalidProof = 27,
+    /// Revealed failure destination and salt do not match the stored commitment.
+    InvalidReveal = 28,
+    /// Failure destination is still hidden and the reveal grace period has not elapsed.
+    RevealPending = 29,
+    /// Committee has no keys, more keys than the batch limit, or a threshold outside `1..=len`.
+    InvalidCommittee = 30,
+    /// Aggregated attestation does not verify, names invalid signers or is below threshold, or
+    /// a goal reading or check-in reuses a sequence number.
+    InvalidAttestation = 31,
+    /// Rescue amount exceeds the token balance held above tracked escrow.
+    ExceedsSurplus = 32,
+    /// Timelocked action cannot be executed yet (including a cancel within the minimum
+    /// holding period, a large settlement still awaiting its co-signature, or a claim once the
+    /// outflow window is spent).
+    TimelockPending = 33,
+    /// No rescue is scheduled for this token, or no admin action is queued under this id.
+    NoPendingRescue = 34,
+    /// A configured limit is zero or above its compile-time ceiling, or a token already has
+    /// `MAX_STRATEGIES_PER_TOKEN` approved strategies, or a slippage bound is above
+    /// `MAX_SLIPPAGE_BPS`, or a deployment would take a strategy past its risk cap.
+    InvalidLimits = 35,
+    /// Refund schedule has out-of-range rates, unordered steps or too many steps, or a check-in
+    /// schedule has a zero period or requirement or too many periods, or a streaming period is
+    /// zero or above `MAX_STREAM_SECS`.
+    InvalidSchedule = 36,
+    /// Vault has already been snoozed `MAX_SNOOZES` times.
+    SnoozeLimitReached = 37,
+    /// Vault's deadline clock is paused; validation and settlement wait until it resumes.
+    VaultPaused = 38,
+    /// Pause would take the vault's total paused time past `MAX_PAUSE_SECS`.
+    PauseLimitExceeded = 39,
+    /// Vault has no pause in effect.
+    VaultNotPaused = 40,
+    /// Vault already has a bonus pool, or can no longer receive one.
+    BonusAlreadyFunded = 41,
+    /// The vault's `start_timestamp` has not been reached yet.
+    NotStarted = 42,
+    /// Retry allowance is zero or above `MAX_RETRIES`.
+    InvalidRetryLimit = 43,
+    /// Vault has not been flagged as abandoned.
+    NotAbandoned = 44,
+    /// Creator has not named a beneficiary.
+    NoBeneficiary = 45,
+    /// Creator showed a sign of life within `BENEFICIARY_INACTIVITY_SECS`.
+    CreatorStillActive = 46,
+    /// Guardian set is empty, too large, has duplicates or the creator, or a bad threshold.
+    InvalidGuardians = 47,
+    /// Vault has no recovery request in progress.
+    NoPendingRecovery = 48,
+    /// Creator already holds as many unsettled vaults as their cap allows.
+    CreatorCapReached = 49,
+    /// Vault has no milestone amendment awaiting approval, or it has lapsed.
+    NoPendingAmendment = 50,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every code as released. Extend it for new variants; never edit an existing row.
+    const CODES: [(Error, u32); 50] = [
+        (Error::VaultNotFound, 1),
+        (Error::NotAuthorized, 2),
+        (Error::VaultNotActive, 3),
+        (Error::InvalidTimestamp, 4),
+        (Error::MilestoneExpired, 5),
+        (Error::InvalidStatus, 6),
+        (Error::InvalidAmount, 7),
+        (Error::InvalidTimestamps, 8),
+        (Error::InvalidTeam, 9),
+        (Error::NotTeamMember, 10),
+        (Error::InvalidPair, 11),
+        (Error::AlreadyInitialized, 12),
+        (Error::NotInitialized, 13),
+        (Error::ArbiterNotRegistered, 14),
+        (Error::NoArbiter, 15),
+        (Error::InvalidFee, 16),
+        (Error::VaultFrozen, 17),
+        (Error::VaultNotFrozen, 18),
+        (Error::DestinationBlocked, 19),
+        (Error::NothingToClaim, 20),
+        (Error::ArithmeticOverflow, 21),
+        (Error::BatchTooLarge, 22),
+        (Error::InvalidToken, 23),
+        (Error::VaultIdTaken, 24),
+        (Error::InvalidMemo, 25),
+        (Error::InvariantViolated, 26),
+        (Error::InvalidProof, 27),
+        (Error::InvalidReveal, 28),
+        (Error::RevealPending, 29),
+        (Error::InvalidCommittee, 30),
+        (Error::InvalidAttestation, 31),
+        (Error::ExceedsSurplus, 32),
+        (Error::TimelockPending, 33),
+        (Error::NoPendingRescue, 34),
+        (Error::InvalidLimits, 35),
+        (Error::InvalidSchedule, 36),
+        (Error::SnoozeLimitReached, 37),
+        (Error::VaultPaused, 38),
+        (Error::PauseLimitExceeded, 39),
+        (Error::VaultNotPaused, 40),
+        (Error::BonusAlreadyFunded, 41),
+        (Error::NotStarted, 42),
+        (Error::InvalidRetryLimit, 43),
+        (Error::NotAbandoned, 44),
+        (Error::NoBeneficiary, 45),
+        (Error::CreatorStillActive, 46),
+        (Error::InvalidGuardians, 47),
+        (Error::NoPendingRecovery, 48),
+        (Error::CreatorCapReached, 49),
+        (Error::NoPendingAmendment, 50),
+    ];
+
+    #[test]
+    fn test_codes_are_stable() {
+        for (error, code) in CODES {
+            assert_eq!(error as u32, code, "{error:?} was renumbered");
+            assert_eq!(
+                soroban_sdk::Error::from(error),
+                soroban_sdk::Error::from_contract_error(code)
+            );
+        }
+    }
+
+    #[test]
+    fn test_codes_are_unique_and_contiguous() {
+        for (index, (_, code)) in CODES.iter().enumerate() {
+            assert_eq!(*code, index as u32 + 1);
+        }
+    }
+}
@@ -0,0 +1,182 @@
+//! Event schema and compatibility policy.
+//!
+//! Every event the contract emits goes through [`publish`] or [`publish_global`]:
+//!
+//! - Topics are `(name, EVENT_SCHEMA_VERSION, subject)`. `subject` is the vault id for vault
+//!   events and the address, token or registry key the event is about otherwise. Contract-wide
+//!   events with no subject (e.g. `default_arbiter_set`) use `(name, EVENT_SCHEMA_VERSION)`.
+//! - Key lifecycle events (`vault_created`, `milestone_validated`, `milestone_rejected`,
+//!   `funds_released`, `funds_redirected`, `vault_cancelled`) go through [`publish_vault`],
+//!   which appends the vault's creator: `(name, EVENT_SCHEMA_VERSION, vault_id, creator)`.
+//!   Hosts cap events at four topics, so the verifier gets a companion `verifier_notice` with
+//!   topics `("verifier_notice", EVENT_SCHEMA_VERSION, vault_id, verifier)` and data
+//!   `(EVENT_SCHEMA_VERSION, name)`. Subscribing to the fourth topic thus yields everything
+//!   about one address, as creator or verifier, without filtering all contract events.
+//! - Data is `(EVENT_SCHEMA_VERSION, payload)`, so a payload can be decoded without its topics.
+//!
+//! Compatibility policy, so indexers never break silently:
+//!
+//! - Within one schema version an event's name, topic layout and existing payload fields never
+//!   change. Payloads may only be extended by appending trailing tuple elements or struct
+//!   fields; decoders must ignore trailing data they do not know.
+//! - Removing, reordering or changing the meaning of a field, or changing topics, bumps
+//!   `EVENT_SCHEMA_VERSION`. Indexers should filter on `(name, version)` and treat an unknown
+//!   version as a schema they have to be upgraded for.
+
+use soroban_sdk::{Env, IntoVal, Symbol, Val};
+
+use crate::ProductivityVault;
+
+/// Current event schema version, carried in every event's topics and data.
+pub const EVENT_SCHEMA_VERSION: u32 = 2;
+
+/// Emit `name` about `subject` with `payload`.
+pub fn publish<S, D>(env: &Env, name: &str, subject: S, payload: D)
+where
+    S: IntoVal<Env, Val>,
+    D: IntoVal<Env, Val>,
+{
+    let subject: Val = subject.into_val(env);
+    let payload: Val = payload.into_val(env);
+    env.events().publish(
+        (Symbol::new(env, name), EVENT_SCHEMA_VERSION, subject),
+        (EVENT_SCHEMA_VERSION, payload),
+    );
+}
+
+/// Emit key lifecycle event `name` about `vault_id`, indexed by its creator and verifier.
+pub fn publish_vault<D>(env: &Env, name: &str, vault_id: u32, vault: &ProductivityVault, payload: D)
+where
+    D: IntoVal<Env, Val>,
+{
+    let name = Symbol::new(env, name);
+    let payload: Val = payload.into_val(env);
+    env.events().publish(
+        (
+            name.clone(),
+            EVENT_SCHEMA_VERSION,
+            vault_id,
+            vault.creator.clone(),
+        ),
+        (EVENT_SCHEMA_VERSION, payload),
+    );
+    match &vault.verifier {
+        Some(verifier) if *verifier != vault.creator => env.events().publish(
+            (
+                Symbol::new(env, "verifier_notice"),
+                EVENT_SCHEMA_VERSION,
+                vault_id,
+                verifier.clone(),
+            ),
+            (EVENT_SCHEMA_VERSION, name),
+        ),
+        _ => {}
+    }
+}
+
+/// Emit a contract-wide `name` event with no subject.
+pub fn publish_global<D>(env: &Env, name: &str, payload: D)
+where
+    D: IntoVal<Env, Val>,
+{
+    let payload: Val = payload.into_val(env);
+    env.events().publish(
+        (Symbol::new(env, name), EVENT_SCHEMA_VERSION),
+        (EVENT_SCHEMA_VERSION, payload),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use soroban_sdk::{
+        testutils::{Address as _, Events},
+        Address, TryFromVal, TryIntoVal,
+    };
+
+    #[test]
+    fn test_vault_events_carry_version_in_topics_and_data() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let vault_id = fixture.create_vault();
+
+        let (_, topics, data) = env
+            .events()
+            .all()
+            .iter()
+            .rfind(|(contract, topics, _)| {
+                *contract == fixture.contract_id
+                    && topics
+                        .get(0)
+                        .and_then(|name| Symbol::try_from_val(&env, &name).ok())
+                        == Some(Symbol::new(&env, "vault_created"))
+            })
+            .unwrap();
+        assert_eq!(topics.len(), 4);
+        let version: u32 = topics.get(1).unwrap().try_into_val(&env).unwrap();
+        let subject: u32 = topics.get(2).unwrap().try_into_val(&env).unwrap();
+        let creator: Address = topics.get(3).unwrap().try_into_val(&env).unwrap();
+        assert_eq!(version, EVENT_SCHEMA_VERSION);
+        assert_eq!(subject, vault_id);
+        assert_eq!(creator, fixture.creator);
+
+        let (data_version, _): (u32, Val) = data.try_into_val(&env).unwrap();
+        assert_eq!(data_version, EVENT_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_key_events_can_be_filtered_by_address() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+
+        // What an RPC filter on the fourth topic returns for `address`, in the last call.
+        let about = |address: &Address| -> std::vec::Vec<Symbol> {
+            env.events()
+                .all()
+                .iter()
+                .filter(|(contract, topics, _)| {
+                    *contract == fixture.contract_id
+                        && topics.len() == 4
+                        && Address::try_from_val(&env, &topics.get(3).unwrap()).ok()
+                            == Some(address.clone())
+                })
+                .map(|(_, topics, _)| Symbol::try_from_val(&env, &topics.get(0).unwrap()).unwrap())
+                .collect()
+        };
+        let notice = Symbol::new(&env, "verifier_notice");
+
+        let vault_id = fixture.create_vault();
+        assert_eq!(
+            about(&fixture.creator),
+            [Symbol::new(&env, "vault_created")]
+        );
+        assert_eq!(about(&fixture.verifier), std::vec![notice.clone()]);
+
+        fixture
+            .client()
+            .cancel_vault(&vault_id, &fixture.token, &None);
+        let cancelled = Symbol::new(&env, "vault_cancelled");
+        assert_eq!(about(&fixture.creator), std::vec![cancelled.clone()]);
+        assert_eq!(about(&fixture.verifier), std::vec![notice]);
+        let (_, _, data) = env.events().all().last().unwrap();
+        let (_, name): (u32, Symbol) = data.try_into_val(&env).unwrap();
+        assert_eq!(name, cancelled);
+    }
+
+    #[test]
+    fn test_global_events_have_no_subject() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        client.initialize(&Address::generate(&env));
+        client.set_default_arbiter(&None);
+
+        let (_, topics, _) = env.events().all().last().unwrap();
+        assert_eq!(topics.len(), 2);
+        let version: u32 = topics.get(1).unwrap().try_into_val(&env).unwrap();
+        assert_eq!(version, EVENT_SCHEMA_VERSION);
+    }
+}
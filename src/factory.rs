@@ -0,0 +1,256 @@
+//! Per-cohort contract instances and their registry.
+//!
+//! Large programs (corporate wellness, bootcamps) isolate each cohort in its own `DisciplrVault`
+//! instance, so one cohort's admin, treasury, freezes and limits never affect another's. Any
+//! instance can act as a factory: its admin calls `deploy_cohort` with the vault wasm hash, a
+//! cohort name and a [`CohortPreset`], and the new instance is deployed at an address derived
+//! from the name, initialized with the preset and recorded in this instance's registry.
+//! Instances deployed by other means can be configured and recorded with `adopt_cohort`.
+//!
+//! The registry is the list of official instances: apps and indexers enumerate it with
+//! `get_cohorts`, look an instance up by name (`get_cohort`) or by address (`get_instance`),
+//! and never need a hardcoded list. Each row carries the cohort name hash, admin, stake token
+//! and registration time.
+//!
+//! The preset is applied through the new instance's own `initialize` and admin setters, so the
+//! preset admin authorizes those calls and every setting emits its usual `config_changed` event
+//! on the new instance.
+
+use soroban_sdk::{contracttype, xdr::ToXdr, Address, BytesN, Env, Symbol};
+
+use crate::{config::Limits, events, DisciplrVaultClient, Error};
+
+/// Storage keys of the cohort registry.
+#[contracttype]
+#[derive(Clone)]
+pub enum FactoryKey {
+    /// Number of registered cohorts (instance storage).
+    CohortCount,
+    /// Registry position -> cohort entry.
+    Cohort(u32),
+    /// Cohort name -> its registry position.
+    CohortIndex(Symbol),
+    /// Cohort instance address -> its registry position.
+    InstanceIndex(Address),
+}
+
+/// Configuration applied to a new cohort instance.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CohortPreset {
+    /// Admin of the new instance.
+    pub admin: Address,
+    /// Token the cohort stakes in. Recorded in the registry for discovery; vaults on the
+    /// instance are not restricted to it.
+    pub token: Address,
+    pub treasury: Option<Address>,
+    pub max_freeze_secs: u64,
+    pub tombstone_mode: bool,
+    pub limits: Limits,
+}
+
+/// Registry row for one cohort instance.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CohortEntry {
+    pub cohort: Symbol,
+    /// `sha256` of the cohort name's XDR, also the instance's deployment salt.
+    pub cohort_hash: BytesN<32>,
+    pub instance: Address,
+    pub admin: Address,
+    pub token: Address,
+    pub created_at: u64,
+}
+
+/// `true` if every setting in `preset` would be accepted by the instance's setters.
+pub fn is_valid(preset: &CohortPreset) -> bool {
+    preset.max_freeze_secs > 0 && preset.limits.is_valid()
+}
+
+/// Deployment salt for `cohort`, so each name maps to one address per factory.
+pub fn salt(env: &Env, cohort: &Symbol) -> BytesN<32> {
+    env.crypto().sha256(&cohort.clone().to_xdr(env)).to_bytes()
+}
+
+/// Initialize the uninitialized instance at `instance` with `preset`.
+pub fn configure(env: &Env, instance: &Address, preset: &CohortPreset) -> Result<(), Error> {
+    let client = DisciplrVaultClient::new(env, instance);
+    if client.get_admin().is_some() {
+        return Err(Error::AlreadyInitialized);
+    }
+    client.initialize(&preset.admin);
+    if let Some(ref treasury) = preset.treasury {
+        client.set_treasury(treasury);
+    }
+    client.set_max_freeze_secs(&preset.max_freeze_secs);
+    client.set_tombstone_mode(&preset.tombstone_mode);
+    client.set_limits(&preset.limits);
+    Ok(())
+}
+
+/// Registry entry at position `index`, if any.
+pub fn get_at(env: &Env, index: u32) -> Option<CohortEntry> {
+    env.storage().persistent().get(&FactoryKey::Cohort(index))
+}
+
+/// Registered entry for `cohort`, if any.
+pub fn get(env: &Env, cohort: &Symbol) -> Option<CohortEntry> {
+    let index: u32 = env
+        .storage()
+        .persistent()
+        .get(&FactoryKey::CohortIndex(cohort.clone()))?;
+    get_at(env, index)
+}
+
+/// Registered entry for the instance at `instance`, if any.
+pub fn get_by_instance(env: &Env, instance: &Address) -> Option<CohortEntry> {
+    let index: u32 = env
+        .storage()
+        .persistent()
+        .get(&FactoryKey::InstanceIndex(instance.clone()))?;
+    get_at(env, index)
+}
+
+/// Number of registered cohorts.
+pub fn count(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&FactoryKey::CohortCount)
+        .unwrap_or(0)
+}
+
+/// Append `entry` to the registry; fails if its cohort name or instance is taken.
+pub fn register(env: &Env, entry: &CohortEntry) -> Result<(), Error> {
+    let storage = env.storage().persistent();
+    let name_key = FactoryKey::CohortIndex(entry.cohort.clone());
+    let instance_key = FactoryKey::InstanceIndex(entry.instance.clone());
+    if storage.has(&name_key) || storage.has(&instance_key) {
+        return Err(Error::VaultIdTaken);
+    }
+    let index = count(env);
+    storage.set(&name_key, &index);
+    storage.set(&instance_key, &index);
+    storage.set(&FactoryKey::Cohort(index), entry);
+    env.storage()
+        .instance()
+        .set(&FactoryKey::CohortCount, &(index + 1));
+    Ok(())
+}
+
+/// Configure `instance` with `preset` and record it under `cohort`.
+pub fn adopt(
+    env: &Env,
+    cohort: Symbol,
+    instance: Address,
+    preset: CohortPreset,
+) -> Result<(), Error> {
+    if get(env, &cohort).is_some() || get_by_instance(env, &instance).is_some() {
+        return Err(Error::VaultIdTaken);
+    }
+    configure(env, &instance, &preset)?;
+    let entry = CohortEntry {
+        cohort_hash: salt(env, &cohort),
+        cohort: cohort.clone(),
+        instance: instance.clone(),
+        admin: preset.admin,
+        token: preset.token,
+        created_at: env.ledger().timestamp(),
+    };
+    register(env, &entry)?;
+    events::publish(env, "cohort_registered", cohort, instance);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::{register_vault_contract, VaultFixture};
+    use soroban_sdk::testutils::Address as _;
+
+    fn preset(env: &Env) -> CohortPreset {
+        CohortPreset {
+            admin: Address::generate(env),
+            token: Address::generate(env),
+            treasury: Some(Address::generate(env)),
+            max_freeze_secs: 3_600,
+            tombstone_mode: true,
+            limits: Limits {
+                page_limit: 10,
+                ..Limits::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_adopted_cohort_is_configured_and_listed() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        // The preset admin authorizes the calls into the new instance, below the root call.
+        env.mock_all_auths_allowing_non_root_auth();
+        let factory = fixture.client();
+        factory.initialize(&Address::generate(&env));
+        let instance = register_vault_contract(&env);
+        let preset = preset(&env);
+        let cohort = Symbol::new(&env, "bootcamp_2026");
+
+        factory.adopt_cohort(&cohort, &instance, &preset);
+        let config = DisciplrVaultClient::new(&env, &instance).get_config();
+        assert_eq!(config.admin, Some(preset.admin.clone()));
+        assert_eq!(config.treasury, preset.treasury);
+        assert_eq!(config.max_freeze_secs, 3_600);
+        assert!(config.tombstone_mode);
+        assert_eq!(config.limits, preset.limits);
+
+        let entry = factory.get_cohort(&cohort).unwrap();
+        assert_eq!(entry.instance, instance);
+        assert_eq!(entry.admin, preset.admin);
+        assert_eq!(entry.token, preset.token);
+        assert_eq!(entry.cohort_hash, salt(&env, &cohort));
+        assert_eq!(factory.get_instance(&instance), Some(entry.clone()));
+        assert_eq!(factory.get_instance(&fixture.contract_id), None);
+        assert_eq!(factory.get_cohort_count(), 1);
+        assert_eq!(factory.get_cohorts(&0, &10).get(0), Some(entry));
+        assert_eq!(
+            factory.try_adopt_cohort(&cohort, &register_vault_contract(&env), &preset),
+            Err(Ok(Error::VaultIdTaken))
+        );
+        // One registry row per instance as well as per name.
+        assert_eq!(
+            factory.try_adopt_cohort(&Symbol::new(&env, "again"), &instance, &preset),
+            Err(Ok(Error::VaultIdTaken))
+        );
+    }
+
+    #[test]
+    fn test_adopt_rejects_initialized_instances_and_bad_presets() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let factory = fixture.client();
+        factory.initialize(&Address::generate(&env));
+        let cohort = Symbol::new(&env, "wellness");
+
+        // Already has an admin: the preset would not apply.
+        let taken = register_vault_contract(&env);
+        DisciplrVaultClient::new(&env, &taken).initialize(&Address::generate(&env));
+        assert_eq!(
+            factory.try_adopt_cohort(&cohort, &taken, &preset(&env)),
+            Err(Ok(Error::AlreadyInitialized))
+        );
+
+        let bad = CohortPreset {
+            max_freeze_secs: 0,
+            ..preset(&env)
+        };
+        assert_eq!(
+            factory.try_adopt_cohort(&cohort, &register_vault_contract(&env), &bad),
+            Err(Ok(Error::InvalidLimits))
+        );
+        assert_eq!(factory.get_cohort(&cohort), None);
+        assert_eq!(
+            factory.compute_cohort_address(&cohort),
+            env.deployer()
+                .with_address(fixture.contract_id.clone(), salt(&env, &cohort))
+                .deployed_address()
+        );
+    }
+}
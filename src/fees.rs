@@ -0,0 +1,146 @@
+//! Admin-managed protocol fee overrides.
+//!
+//! The admin can lower protocol fees for particular creators or for vaults paying a particular
+//! destination (e.g. zero fees for charity destinations, time-boxed promotional holidays). Each
+//! override is its own persistent entry keyed by `FeeSubject`, so the registry costs nothing for
+//! vaults it does not mention and only the three subjects of a vault are read when a fee is
+//! charged.
+//!
+//! Overrides only ever lower a fee: the effective rate is the lowest of the base rate and every
+//! unexpired override matching the vault. Arbiter fees belong to arbiters and are not affected.
+
+use soroban_sdk::{contracttype, Address, Env};
+
+use crate::{DataKey, ProductivityVault};
+
+/// What an override applies to.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum FeeSubject {
+    /// Every vault created by this address.
+    Creator(Address),
+    /// Every vault whose success or failure destination is this address.
+    Destination(Address),
+}
+
+/// Fee rate for one subject, optionally expiring.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeOverride {
+    pub fee_bps: u32,
+    /// The override lapses at this timestamp; `None` keeps it until removed.
+    pub until: Option<u64>,
+}
+
+impl FeeOverride {
+    fn is_active(&self, now: u64) -> bool {
+        self.until.is_none_or(|until| now < until)
+    }
+}
+
+/// Stored override for `subject`, whether or not it has lapsed.
+pub fn get(env: &Env, subject: &FeeSubject) -> Option<FeeOverride> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::FeeOverride(subject.clone()))
+}
+
+/// Protocol fee rate for `vault`: the lowest of `base_bps` and every active override for its
+/// creator or destinations.
+pub fn effective_bps(env: &Env, vault: &ProductivityVault, base_bps: u32) -> u32 {
+    let now = env.ledger().timestamp();
+    [
+        FeeSubject::Creator(vault.creator.clone()),
+        FeeSubject::Destination(vault.success_destination.clone()),
+        FeeSubject::Destination(vault.failure_destination.clone()),
+    ]
+    .iter()
+    .filter_map(|subject| get(env, subject))
+    .filter(|fee_override| fee_override.is_active(now))
+    .fold(base_bps, |bps, fee_override| bps.min(fee_override.fee_bps))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use crate::{Error, SNOOZE_FEE_BPS};
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger},
+        token::TokenClient,
+    };
+
+    #[test]
+    fn test_lowest_active_override_applies() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        client.initialize(&Address::generate(&env));
+        let vault_id = fixture.create_vault();
+        assert_eq!(
+            client.get_fee_bps(&vault_id, &SNOOZE_FEE_BPS),
+            SNOOZE_FEE_BPS
+        );
+
+        client.set_fee_override(
+            &FeeSubject::Creator(fixture.creator.clone()),
+            &Some(FeeOverride {
+                fee_bps: 200,
+                until: None,
+            }),
+        );
+        client.set_fee_override(
+            &FeeSubject::Destination(fixture.success_destination.clone()),
+            &Some(FeeOverride {
+                fee_bps: 0,
+                until: Some(50),
+            }),
+        );
+        assert_eq!(client.get_fee_bps(&vault_id, &SNOOZE_FEE_BPS), 0);
+        env.ledger().set_timestamp(50);
+        assert_eq!(client.get_fee_bps(&vault_id, &SNOOZE_FEE_BPS), 200);
+        // Overrides never raise a fee.
+        assert_eq!(client.get_fee_bps(&vault_id, &100), 100);
+
+        client.set_fee_override(&FeeSubject::Creator(fixture.creator.clone()), &None);
+        assert_eq!(
+            client.get_fee_bps(&vault_id, &SNOOZE_FEE_BPS),
+            SNOOZE_FEE_BPS
+        );
+        assert_eq!(
+            client.try_set_fee_override(
+                &FeeSubject::Creator(fixture.creator.clone()),
+                &Some(FeeOverride {
+                    fee_bps: crate::BPS_DENOMINATOR + 1,
+                    until: None,
+                }),
+            ),
+            Err(Ok(Error::InvalidFee))
+        );
+    }
+
+    #[test]
+    fn test_exempt_destination_snoozes_for_free() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let token = TokenClient::new(&env, &fixture.token);
+        client.initialize(&Address::generate(&env));
+        client.set_fee_override(
+            &FeeSubject::Destination(fixture.failure_destination.clone()),
+            &Some(FeeOverride {
+                fee_bps: 0,
+                until: None,
+            }),
+        );
+        let vault_id = fixture.create_vault();
+        env.ledger().set_timestamp(fixture.start_timestamp);
+
+        client.snooze(&vault_id, &60);
+        assert_eq!(token.balance(&fixture.failure_destination), 0);
+        assert_eq!(
+            client.get_vault_state(&vault_id).unwrap().end_timestamp,
+            fixture.end_timestamp + 60
+        );
+    }
+}
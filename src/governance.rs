@@ -0,0 +1,110 @@
+//! Governance adapter: the admin role held by a contract.
+//!
+//! The admin is an ordinary address, so it can be a governance contract (a DAO executing
+//! token votes, a multisig) instead of an account. Two entry points make that practical:
+//!
+//! - `transfer_admin(new_admin)` offers the role and `accept_admin` takes it, so a deployment
+//!   can start with a team account and hand over to governance later without redeploying.
+//!   Accepting requires the new admin's authorization: a contract gives it by making the call
+//!   itself, e.g. as the first executed proposal.
+//! - `execute(action)` runs any [`AdminAction`], covering every privileged setting with a
+//!   single payload type. A governance contract only has to encode the proposal's action and
+//!   make one call when it passes, and needs no upgrade when new settings appear (see
+//!   [`crate::timelock`] for the notice timelocked actions require).
+
+use soroban_sdk::{contracttype, Address, Env};
+
+#[cfg(doc)]
+use crate::timelock::AdminAction;
+
+/// Storage key of the admin handover.
+#[contracttype]
+#[derive(Clone)]
+pub enum GovernanceKey {
+    /// Address offered the admin role (instance storage).
+    PendingAdmin,
+}
+
+/// Address offered the admin role, if any.
+pub fn pending_admin(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&GovernanceKey::PendingAdmin)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use crate::timelock::AdminAction;
+    use crate::{DisciplrVaultClient, Error};
+    use soroban_sdk::{contract, contractimpl, testutils::Address as _};
+
+    /// Enacts whatever it is asked to, as a passed proposal would.
+    #[contract]
+    struct Dao;
+
+    #[contractimpl]
+    impl Dao {
+        pub fn accept(env: Env, vault: Address) {
+            DisciplrVaultClient::new(&env, &vault).accept_admin();
+        }
+
+        pub fn enact(env: Env, vault: Address, action: AdminAction) {
+            DisciplrVaultClient::new(&env, &vault).execute(&action);
+        }
+    }
+
+    #[test]
+    fn test_governance_contract_takes_admin_and_enacts_settings() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        client.initialize(&Address::generate(&env));
+        assert_eq!(client.try_accept_admin(), Err(Ok(Error::NotAuthorized)));
+        let dao = env.register(Dao, ());
+        let dao_client = DaoClient::new(&env, &dao);
+        client.transfer_admin(&dao);
+        assert_eq!(client.get_pending_admin(), Some(dao.clone()));
+
+        // From here on only real authorizations count: the DAO authorizes by calling.
+        env.set_auths(&[]);
+        dao_client.accept(&fixture.contract_id);
+        assert_eq!(client.get_admin(), Some(dao.clone()));
+        assert_eq!(client.get_pending_admin(), None);
+
+        let treasury = Address::generate(&env);
+        dao_client.enact(
+            &fixture.contract_id,
+            &AdminAction::SetTreasury(treasury.clone()),
+        );
+        dao_client.enact(&fixture.contract_id, &AdminAction::SetTombstoneMode(true));
+        assert_eq!(client.get_treasury(), Some(treasury));
+        assert!(client.get_tombstone_mode());
+        // The former admin account no longer can.
+        assert!(client.try_set_tombstone_mode(&false).is_err());
+    }
+
+    #[test]
+    fn test_execute_checks_arguments_and_timelock() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        assert_eq!(
+            client.try_execute(&AdminAction::SetTombstoneMode(true)),
+            Err(Ok(Error::NotInitialized))
+        );
+        client.initialize(&Address::generate(&env));
+        assert_eq!(
+            client.try_execute(&AdminAction::SetMaxFreezeSecs(0)),
+            Err(Ok(Error::InvalidTimestamp))
+        );
+
+        client.execute(&AdminAction::SetAdminDelay(60));
+        assert_eq!(
+            client.try_execute(&AdminAction::TransferAdmin(Address::generate(&env))),
+            Err(Ok(Error::TimelockPending))
+        );
+        // Settings outside the timelock stay direct.
+        client.execute(&AdminAction::SetMaxFreezeSecs(120));
+        assert_eq!(client.get_max_freeze_secs(), 120);
+    }
+}
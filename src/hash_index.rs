@@ -0,0 +1,281 @@
+//! Milestone hash -> vault ids index.
+//!
+//! A verifier or oracle often knows only the milestone commitment it attests to, not the vault
+//! id. Every vault is indexed under its current `milestone_hash` when it is created and moved
+//! when an amendment replaces the hash, so `find_vaults_by_milestone_hash` locates it without
+//! an indexer. Settled vaults stay listed (check their status) until archived in tombstone
+//! mode. Several vaults may share a hash, and anyone can add vaults under a public hash, so the
+//! ids are stored position-keyed (see [`lists`](crate::lists)): indexing or dropping a vault
+//! costs the same however many vaults share its hash, and reads page through the positions.
+//!
+//! The index also backs the optional duplication guard. Once the admin turns it on with
+//! `set_duplicate_guard`, creating a vault fails with `VaultIdTaken` when the same creator
+//! already has an `Active` vault with an identical `milestone_hash` and an overlapping time
+//! window, which catches accidental double submission from flaky frontends. A creator who
+//! means to stack such vaults opts out explicitly with `create_vault_allow_duplicate`. The
+//! guard reads a second index keyed by `(creator, hash)` that holds only the creator's
+//! unsettled vaults, so its size is bounded by the creator's vault cap and other accounts'
+//! vaults under the same hash cannot grow it.
+
+use soroban_sdk::{contracttype, Address, BytesN, Env, Vec};
+
+use crate::{
+    lists::{self, List},
+    packed::load_vault,
+    VaultParams, VaultStatus,
+};
+
+/// Storage keys of the duplication guard.
+#[contracttype]
+#[derive(Clone)]
+pub enum HashIndexKey {
+    /// Whether the duplication guard is on (instance storage).
+    DuplicateGuard,
+    /// (creator, milestone hash) -> ids of the creator's unsettled vaults under it.
+    CreatorVaults(Address, BytesN<32>),
+}
+
+/// Ids at index positions `start..start + limit` under `hash`.
+pub fn ids(env: &Env, hash: &BytesN<32>, start: u32, limit: u32) -> Vec<u32> {
+    lists::page(env, &List::Hash(hash.clone()), start, limit)
+}
+
+/// Ids of `creator`'s unsettled vaults under `hash`.
+fn creator_vaults(env: &Env, creator: &Address, hash: &BytesN<32>) -> Vec<u32> {
+    env.storage()
+        .persistent()
+        .get(&HashIndexKey::CreatorVaults(creator.clone(), hash.clone()))
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+/// Index `creator`'s unsettled vault `vault_id` under `hash`.
+pub fn add(env: &Env, creator: &Address, hash: &BytesN<32>, vault_id: u32) {
+    lists::push(env, &List::Hash(hash.clone()), vault_id);
+    let mut ids = creator_vaults(env, creator, hash);
+    ids.push_back(vault_id);
+    env.storage().persistent().set(
+        &HashIndexKey::CreatorVaults(creator.clone(), hash.clone()),
+        &ids,
+    );
+}
+
+/// Drop `vault_id` from the ids under `hash`.
+pub fn remove(env: &Env, creator: &Address, hash: &BytesN<32>, vault_id: u32) {
+    lists::remove(env, &List::Hash(hash.clone()), vault_id);
+    settle(env, creator, hash, vault_id);
+}
+
+/// Drop a settled `vault_id` from the guard's lookup; it stays findable under `hash`.
+pub fn settle(env: &Env, creator: &Address, hash: &BytesN<32>, vault_id: u32) {
+    let key = HashIndexKey::CreatorVaults(creator.clone(), hash.clone());
+    let mut ids = creator_vaults(env, creator, hash);
+    let Some(position) = ids.first_index_of(vault_id) else {
+        return;
+    };
+    ids.remove(position);
+    if ids.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, &ids);
+    }
+}
+
+/// Whether the duplication guard is on.
+pub fn guard_enabled(env: &Env) -> bool {
+    env.storage()
+        .instance()
+        .get(&HashIndexKey::DuplicateGuard)
+        .unwrap_or(false)
+}
+
+/// Id of an `Active` vault of the same creator, with the same milestone hash and an
+/// overlapping time window, that a vault created from `params` would duplicate.
+///
+/// Only the creator's own unsettled vaults under the hash are read.
+pub fn find_duplicate(env: &Env, params: &VaultParams) -> Option<u32> {
+    creator_vaults(env, &params.creator, &params.milestone_hash)
+        .iter()
+        .find(|&vault_id| {
+            load_vault(env, vault_id).is_some_and(|vault| {
+                vault.status == VaultStatus::Active
+                    && vault.start_timestamp < params.end_timestamp
+                    && params.start_timestamp < vault.end_timestamp
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::mint;
+    use crate::testutils::VaultFixture;
+    use crate::timelock::AdminAction;
+    use crate::Error;
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger},
+        vec, Address,
+    };
+
+    #[test]
+    fn test_vaults_are_found_by_milestone_hash() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let hash = fixture.params().milestone_hash;
+        let other = BytesN::from_array(&env, &[9; 32]);
+        let first = fixture.create_vault();
+        let second = fixture.create_vault();
+        let third = fixture.create_vault_with(&VaultParams {
+            milestone_hash: other.clone(),
+            ..fixture.params()
+        });
+
+        let found = client.find_vaults_by_milestone_hash(&hash, &0, &10);
+        assert_eq!(found, vec![&env, first, second]);
+        assert_eq!(
+            client.find_vaults_by_milestone_hash(&hash, &1, &10),
+            vec![&env, second]
+        );
+        assert_eq!(
+            client.find_vaults_by_milestone_hash(&other, &0, &10),
+            vec![&env, third]
+        );
+        assert!(client
+            .find_vaults_by_milestone_hash(&BytesN::from_array(&env, &[8; 32]), &0, &10)
+            .is_empty());
+
+        // An approved amendment moves the vault to its new hash.
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        client.propose_amendment(&first, &other);
+        client.approve_amendment(&first);
+        assert_eq!(
+            client.find_vaults_by_milestone_hash(&hash, &0, &10),
+            vec![&env, second]
+        );
+        assert_eq!(
+            client.find_vaults_by_milestone_hash(&other, &0, &10),
+            vec![&env, third, first]
+        );
+    }
+
+    #[test]
+    fn test_vaults_sharing_a_hash_are_stored_one_per_entry() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let hash = fixture.params().milestone_hash;
+        let ids = [
+            fixture.create_vault(),
+            fixture.create_vault(),
+            fixture.create_vault(),
+        ];
+        env.as_contract(&fixture.contract_id, || {
+            let list = List::Hash(hash.clone());
+            assert_eq!(lists::len(&env, &list), 3);
+            assert_eq!(lists::get(&env, &list, 2), Some(ids[2]));
+        });
+
+        // Moving the first vault away swaps the last one into its position.
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        client.propose_amendment(&ids[0], &BytesN::from_array(&env, &[9; 32]));
+        client.approve_amendment(&ids[0]);
+        assert_eq!(
+            client.find_vaults_by_milestone_hash(&hash, &0, &10),
+            vec![&env, ids[2], ids[1]]
+        );
+        assert_eq!(
+            client.find_vaults_by_milestone_hash(&hash, &1, &1),
+            vec![&env, ids[1]]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_guard_rejects_identical_active_vaults() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        client.initialize(&Address::generate(&env));
+        let first = fixture.create_vault();
+        // Off by default.
+        let second = fixture.create_vault();
+        client.set_duplicate_guard(&true);
+        assert!(client.get_duplicate_guard());
+
+        mint(&env, &fixture.token, &fixture.creator, fixture.amount);
+        assert_eq!(
+            client.try_create_vault_with_params(&fixture.params()),
+            Err(Ok(Error::VaultIdTaken))
+        );
+        // A window that only overlaps partly is still a duplicate.
+        let overlapping = VaultParams {
+            start_timestamp: fixture.end_timestamp - 1,
+            end_timestamp: fixture.end_timestamp + 500,
+            ..fixture.params()
+        };
+        assert_eq!(
+            client.try_create_vault_with_params(&overlapping),
+            Err(Ok(Error::VaultIdTaken))
+        );
+        // Back-to-back windows, another hash or another creator are not.
+        fixture.create_vault_with(&VaultParams {
+            start_timestamp: fixture.end_timestamp,
+            end_timestamp: fixture.end_timestamp + 500,
+            ..fixture.params()
+        });
+        fixture.create_vault_with(&VaultParams {
+            milestone_hash: BytesN::from_array(&env, &[9; 32]),
+            ..fixture.params()
+        });
+        let other_creator = Address::generate(&env);
+        mint(&env, &fixture.token, &other_creator, fixture.amount);
+        client.create_vault_with_params(&VaultParams {
+            creator: other_creator,
+            ..fixture.params()
+        });
+
+        // The explicit override, and settled vaults, are not guarded.
+        mint(&env, &fixture.token, &fixture.creator, fixture.amount);
+        let third = client.create_vault_allow_duplicate(&fixture.params());
+        for vault_id in [first, second, third] {
+            client.cancel_vault(&vault_id, &fixture.token, &None);
+        }
+        fixture.create_vault();
+    }
+
+    #[test]
+    fn test_duplicate_guard_reads_only_the_creators_unsettled_vaults() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        client.initialize(&Address::generate(&env));
+        client.execute(&AdminAction::SetDuplicateGuard(true));
+        assert!(client.get_duplicate_guard());
+        let hash = fixture.params().milestone_hash;
+
+        // Other accounts reusing the hash do not grow the creator's lookup.
+        for _ in 0..3 {
+            let other = Address::generate(&env);
+            mint(&env, &fixture.token, &other, fixture.amount);
+            client.create_vault_with_params(&VaultParams {
+                creator: other,
+                ..fixture.params()
+            });
+        }
+        let vault_id = fixture.create_vault();
+        env.as_contract(&fixture.contract_id, || {
+            assert_eq!(creator_vaults(&env, &fixture.creator, &hash).len(), 1);
+            assert_eq!(lists::len(&env, &List::Hash(hash.clone())), 4);
+        });
+
+        // Settling drops it from the lookup but keeps it findable by hash.
+        client.cancel_vault(&vault_id, &fixture.token, &None);
+        env.as_contract(&fixture.contract_id, || {
+            assert!(creator_vaults(&env, &fixture.creator, &hash).is_empty());
+        });
+        assert_eq!(
+            client.find_vaults_by_milestone_hash(&hash, &0, &10).len(),
+            4
+        );
+        fixture.create_vault();
+    }
+}
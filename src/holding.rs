@@ -0,0 +1,123 @@
+//! Minimum holding period between creating and cancelling a vault.
+//!
+//! Bots churning create/cancel cycles spam the indexes and event stream at no cost, since a
+//! cancel before start refunds in full. The admin can require a vault to exist for a number of
+//! ledgers before its creator may cancel it. Each vault records its creation ledger in
+//! temporary storage that lives just as long as the hold, so the check costs no permanent rent
+//! and the entry disappears on its own once cancelling is allowed.
+//!
+//! Vaults are funded when they are created, so every vault is covered; there is no unfunded
+//! state to exempt. The hold in effect at creation applies: raising it later does not affect
+//! existing vaults.
+
+use soroban_sdk::{contracttype, Env};
+
+use crate::Error;
+
+/// Longest hold the admin can set, about a day of ledgers.
+pub const MAX_CANCEL_HOLD_LEDGERS: u32 = 17_280;
+
+/// Storage keys of the cancel hold.
+#[contracttype]
+#[derive(Clone)]
+pub enum HoldKey {
+    /// Ledgers a vault must exist before it can be cancelled (instance storage).
+    CancelHoldLedgers,
+    /// Vault id -> `(created_ledger, hold)`, in temporary storage until the hold ends.
+    CreatedLedger(u32),
+}
+
+/// Configured hold in ledgers; zero (no hold) until set.
+pub fn hold_ledgers(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&HoldKey::CancelHoldLedgers)
+        .unwrap_or(0)
+}
+
+/// Record that `vault_id` was created in this ledger, if a hold is configured.
+pub fn record_creation(env: &Env, vault_id: u32) {
+    let hold = hold_ledgers(env);
+    if hold == 0 {
+        return;
+    }
+    let key = HoldKey::CreatedLedger(vault_id);
+    let storage = env.storage().temporary();
+    storage.set(&key, &(env.ledger().sequence(), hold));
+    storage.extend_ttl(&key, hold, hold);
+}
+
+/// Fail with `TimelockPending` while `vault_id` is within its hold.
+pub fn ensure_cancellable(env: &Env, vault_id: u32) -> Result<(), Error> {
+    let Some((created, hold)) = env
+        .storage()
+        .temporary()
+        .get::<_, (u32, u32)>(&HoldKey::CreatedLedger(vault_id))
+    else {
+        return Ok(());
+    };
+    if env.ledger().sequence() < created.saturating_add(hold) {
+        return Err(Error::TimelockPending);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use crate::SettlementOutcome;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+    use soroban_sdk::Address;
+
+    #[test]
+    fn test_cancel_waits_out_the_hold() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        client.initialize(&Address::generate(&env));
+        let unheld = fixture.create_vault();
+        client.set_cancel_hold(&10);
+        assert_eq!(client.get_cancel_hold(), 10);
+
+        let vault_id = fixture.create_vault();
+        let created = env.ledger().sequence();
+        assert_eq!(
+            client.try_cancel_vault(&vault_id, &fixture.token, &None),
+            Err(Ok(Error::TimelockPending))
+        );
+        assert_eq!(
+            client.try_preview_settlement(&vault_id, &SettlementOutcome::Cancel),
+            Err(Ok(Error::TimelockPending))
+        );
+        // Vaults created before the hold was set are not affected.
+        client.cancel_vault(&unheld, &fixture.token, &None);
+
+        env.ledger().set_sequence_number(created + 9);
+        assert!(client
+            .try_cancel_vault(&vault_id, &fixture.token, &None)
+            .is_err());
+        env.ledger().set_sequence_number(created + 10);
+        client.cancel_vault(&vault_id, &fixture.token, &None);
+    }
+
+    #[test]
+    fn test_cancel_hold_is_admin_only_and_bounded() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        assert_eq!(
+            client.try_set_cancel_hold(&10),
+            Err(Ok(Error::NotInitialized))
+        );
+        client.initialize(&Address::generate(&env));
+        assert_eq!(
+            client.try_set_cancel_hold(&(MAX_CANCEL_HOLD_LEDGERS + 1)),
+            Err(Ok(Error::InvalidLimits))
+        );
+        client.set_cancel_hold(&MAX_CANCEL_HOLD_LEDGERS);
+        client.set_cancel_hold(&0);
+        let vault_id = fixture.create_vault();
+        client.cancel_vault(&vault_id, &fixture.token, &None);
+    }
+}
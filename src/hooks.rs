@@ -0,0 +1,191 @@
+//! Optional settlement hook for downstream contracts.
+//!
+//! The admin can register one hook contract (e.g. an arbitration or reputation contract) with
+//! `set_settle_hook`. After a dispute is resolved and its funds have moved, the vault contract
+//! calls the hook's `on_dispute_resolved` with the same [`DisputeResolution`] it publishes in
+//! the `dispute_resolved` event.
+//!
+//! The hook is notified, not consulted: it runs after the vault's state is saved, and a hook
+//! that fails or is missing cannot block or revert the resolution. A failed call emits
+//! `("hook_failed", vault_id)` with data `(hook, entry_point)` instead.
+
+use soroban_sdk::{contractclient, contracttype, Address, Env, Symbol, Vec};
+
+use crate::{config, events, DisputeRuling, PayoutLeg, VaultStatus};
+
+/// Outcome of one dispute resolution.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DisputeResolution {
+    pub vault_id: u32,
+    pub arbiter: Address,
+    pub ruling: DisputeRuling,
+    /// Arbiter fee deducted from the stake.
+    pub arbiter_fee: i128,
+    /// Every transfer the resolution made, fee included.
+    pub legs: Vec<PayoutLeg>,
+    /// Status the vault moved to from `Disputed`.
+    pub resulting_status: VaultStatus,
+}
+
+/// Entry points a settle hook contract implements.
+#[contractclient(name = "SettleHookClient")]
+pub trait SettleHook {
+    fn on_dispute_resolved(env: Env, resolution: DisputeResolution);
+}
+
+/// Publish `dispute_resolved` and notify the configured settle hook, if any.
+pub fn dispute_resolved(env: &Env, resolution: &DisputeResolution) {
+    events::publish(
+        env,
+        "dispute_resolved",
+        resolution.vault_id,
+        resolution.clone(),
+    );
+    let Some(hook) = config::load(env).settle_hook else {
+        return;
+    };
+    if SettleHookClient::new(env, &hook)
+        .try_on_dispute_resolved(resolution)
+        .is_err()
+    {
+        events::publish(
+            env,
+            "hook_failed",
+            resolution.vault_id,
+            (hook, Symbol::new(env, "on_dispute_resolved")),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use crate::{PayoutKind, VaultParams};
+    use soroban_sdk::{
+        contract, contractimpl,
+        testutils::{Address as _, Events},
+        token::TokenClient,
+        TryFromVal, TryIntoVal,
+    };
+
+    #[contract]
+    struct RecordingHook;
+
+    #[contractimpl]
+    impl RecordingHook {
+        pub fn on_dispute_resolved(env: Env, resolution: DisputeResolution) {
+            env.storage().instance().set(&0u32, &resolution);
+        }
+
+        pub fn last(env: Env) -> Option<DisputeResolution> {
+            env.storage().instance().get(&0u32)
+        }
+    }
+
+    // Separate module: each contract's entry points share one namespace per module.
+    mod failing {
+        use super::*;
+
+        #[contract]
+        pub struct FailingHook;
+
+        #[contractimpl]
+        impl FailingHook {
+            pub fn on_dispute_resolved(_env: Env, _resolution: DisputeResolution) {
+                panic!("hook unavailable");
+            }
+        }
+    }
+
+    /// A disputed vault with an arbiter charging 10%, and the arbiter.
+    fn disputed_vault(fixture: &VaultFixture) -> (u32, Address) {
+        let client = fixture.client();
+        let arbiter = Address::generate(&fixture.env);
+        client.initialize(&Address::generate(&fixture.env));
+        client.register_arbiter(&arbiter, &1_000);
+        let vault_id = fixture.create_vault_with(&VaultParams {
+            arbiter: Some(arbiter.clone()),
+            ..fixture.params()
+        });
+        client.open_dispute(&vault_id, &fixture.creator);
+        (vault_id, arbiter)
+    }
+
+    #[test]
+    fn test_resolution_is_published_and_sent_to_hook() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let (vault_id, arbiter) = disputed_vault(&fixture);
+        let hook = env.register(RecordingHook, ());
+        client.set_settle_hook(&Some(hook.clone()));
+        assert_eq!(client.get_config().settle_hook, Some(hook.clone()));
+
+        client.resolve_dispute(&vault_id, &DisputeRuling::Redirect, &fixture.token);
+        let fee = fixture.amount / 10;
+        let resolution = DisputeResolution {
+            vault_id,
+            arbiter: arbiter.clone(),
+            ruling: DisputeRuling::Redirect,
+            arbiter_fee: fee,
+            legs: soroban_sdk::vec![
+                &env,
+                PayoutLeg {
+                    recipient: arbiter,
+                    amount: fee,
+                    kind: PayoutKind::ArbiterFee,
+                    deferred: false,
+                },
+                PayoutLeg {
+                    recipient: fixture.failure_destination.clone(),
+                    amount: fixture.amount - fee,
+                    kind: PayoutKind::Principal,
+                    deferred: false,
+                },
+            ],
+            resulting_status: VaultStatus::Failed,
+        };
+        let (_, _, data) = env
+            .events()
+            .all()
+            .iter()
+            .find(|(contract, topics, _)| {
+                *contract == fixture.contract_id
+                    && Symbol::try_from_val(&env, &topics.get(0).unwrap())
+                        == Ok(Symbol::new(&env, "dispute_resolved"))
+            })
+            .unwrap();
+        let (_, published): (u32, DisputeResolution) = data.try_into_val(&env).unwrap();
+        assert_eq!(published, resolution);
+        assert_eq!(
+            RecordingHookClient::new(&env, &hook).last(),
+            Some(resolution)
+        );
+    }
+
+    #[test]
+    fn test_failing_hook_does_not_block_resolution() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let (vault_id, _) = disputed_vault(&fixture);
+        client.set_settle_hook(&Some(env.register(failing::FailingHook, ())));
+
+        client.resolve_dispute(&vault_id, &DisputeRuling::Release, &fixture.token);
+        let (_, topics, _) = env.events().all().last().unwrap();
+        assert_eq!(
+            Symbol::try_from_val(&env, &topics.get(0).unwrap()),
+            Ok(Symbol::new(&env, "hook_failed"))
+        );
+        assert_eq!(
+            client.get_vault_state(&vault_id).unwrap().status,
+            VaultStatus::Completed
+        );
+        assert_eq!(
+            TokenClient::new(&env, &fixture.token).balance(&fixture.success_destination),
+            fixture.amount - fixture.amount / 10
+        );
+    }
+}
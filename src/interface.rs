@@ -0,0 +1,240 @@
+//! Typed interface for contracts that create and query Disciplr vaults cross-contract.
+//!
+//! Enabled with the `interface` cargo feature. Composing contracts depend on this crate with
+//! that feature and use [`DisciplrVaultInterfaceClient`] together with the re-exported contract
+//! types, instead of copying struct definitions that can drift from the on-chain XDR.
+//!
+//! [`DisciplrVault`] implements [`DisciplrVaultInterface`] by delegating to its entry points, so
+//! any signature change in the contract fails to compile here until the interface is updated.
+
+use soroban_sdk::{contractclient, Address, Bytes, BytesN, Env, Symbol, Vec};
+
+pub use crate::{
+    DisputeRuling, Error, PayoutKind, PayoutLeg, ProductivityVault, SettlementOutcome,
+    SettlementPreview, VaultParams, VaultStatus, VaultSummary,
+};
+
+use crate::DisciplrVault;
+
+/// Vault creation, settlement and query entry points callable by other contracts.
+#[contractclient(name = "DisciplrVaultInterfaceClient")]
+pub trait DisciplrVaultInterface {
+    fn create_vault_with_params(env: Env, params: VaultParams) -> Result<u32, Error>;
+
+    fn create_vault_deterministic(
+        env: Env,
+        params: VaultParams,
+        salt: BytesN<32>,
+    ) -> Result<u32, Error>;
+
+    fn compute_deterministic_id(env: Env, creator: Address, salt: BytesN<32>) -> u32;
+
+    fn clone_vault(env: Env, vault_id: u32, new_start: u64, new_end: u64) -> Result<u32, Error>;
+
+    fn validate_milestone(env: Env, vault_id: u32, memo: Option<Bytes>) -> Result<bool, Error>;
+
+    fn release_funds(
+        env: Env,
+        vault_id: u32,
+        usdc_token: Address,
+        memo: Option<Bytes>,
+    ) -> Result<bool, Error>;
+
+    fn redirect_funds(
+        env: Env,
+        vault_id: u32,
+        usdc_token: Address,
+        memo: Option<Bytes>,
+    ) -> Result<bool, Error>;
+
+    fn cancel_vault(
+        env: Env,
+        vault_id: u32,
+        usdc_token: Address,
+        memo: Option<Bytes>,
+    ) -> Result<bool, Error>;
+
+    fn preview_settlement(
+        env: Env,
+        vault_id: u32,
+        outcome: SettlementOutcome,
+    ) -> Result<SettlementPreview, Error>;
+
+    fn get_vault_state(env: Env, vault_id: u32) -> Option<ProductivityVault>;
+
+    fn get_vault_states(
+        env: Env,
+        vault_ids: Vec<u32>,
+    ) -> Result<Vec<Option<ProductivityVault>>, Error>;
+
+    fn get_vault_summaries(env: Env, vault_ids: Vec<u32>) -> Result<Vec<VaultSummary>, Error>;
+
+    fn has_expired(env: Env, vault_id: u32) -> Result<bool, Error>;
+
+    fn time_remaining(env: Env, vault_id: u32) -> Result<u64, Error>;
+
+    fn list_public_vaults(env: Env, start: u32, limit: u32) -> Vec<u32>;
+
+    fn get_public_vault_count(env: Env) -> u32;
+
+    fn get_vaults_by_status(env: Env, status: VaultStatus, start: u32, limit: u32) -> Vec<u32>;
+
+    fn version(env: Env) -> (u32, u32, u32);
+
+    fn supports_feature(env: Env, feature: Symbol) -> bool;
+}
+
+impl DisciplrVaultInterface for DisciplrVault {
+    fn create_vault_with_params(env: Env, params: VaultParams) -> Result<u32, Error> {
+        DisciplrVault::create_vault_with_params(env, params)
+    }
+
+    fn create_vault_deterministic(
+        env: Env,
+        params: VaultParams,
+        salt: BytesN<32>,
+    ) -> Result<u32, Error> {
+        DisciplrVault::create_vault_deterministic(env, params, salt)
+    }
+
+    fn compute_deterministic_id(env: Env, creator: Address, salt: BytesN<32>) -> u32 {
+        DisciplrVault::compute_deterministic_id(env, creator, salt)
+    }
+
+    fn clone_vault(env: Env, vault_id: u32, new_start: u64, new_end: u64) -> Result<u32, Error> {
+        DisciplrVault::clone_vault(env, vault_id, new_start, new_end)
+    }
+
+    fn validate_milestone(env: Env, vault_id: u32, memo: Option<Bytes>) -> Result<bool, Error> {
+        DisciplrVault::validate_milestone(env, vault_id, memo)
+    }
+
+    fn release_funds(
+        env: Env,
+        vault_id: u32,
+        usdc_token: Address,
+        memo: Option<Bytes>,
+    ) -> Result<bool, Error> {
+        DisciplrVault::release_funds(env, vault_id, usdc_token, memo)
+    }
+
+    fn redirect_funds(
+        env: Env,
+        vault_id: u32,
+        usdc_token: Address,
+        memo: Option<Bytes>,
+    ) -> Result<bool, Error> {
+        DisciplrVault::redirect_funds(env, vault_id, usdc_token, memo)
+    }
+
+    fn cancel_vault(
+        env: Env,
+        vault_id: u32,
+        usdc_token: Address,
+        memo: Option<Bytes>,
+    ) -> Result<bool, Error> {
+        DisciplrVault::cancel_vault(env, vault_id, usdc_token, memo)
+    }
+
+    fn preview_settlement(
+        env: Env,
+        vault_id: u32,
+        outcome: SettlementOutcome,
+    ) -> Result<SettlementPreview, Error> {
+        DisciplrVault::preview_settlement(env, vault_id, outcome)
+    }
+
+    fn get_vault_state(env: Env, vault_id: u32) -> Option<ProductivityVault> {
+        DisciplrVault::get_vault_state(env, vault_id)
+    }
+
+    fn get_vault_states(
+        env: Env,
+        vault_ids: Vec<u32>,
+    ) -> Result<Vec<Option<ProductivityVault>>, Error> {
+        DisciplrVault::get_vault_states(env, vault_ids)
+    }
+
+    fn get_vault_summaries(env: Env, vault_ids: Vec<u32>) -> Result<Vec<VaultSummary>, Error> {
+        DisciplrVault::get_vault_summaries(env, vault_ids)
+    }
+
+    fn has_expired(env: Env, vault_id: u32) -> Result<bool, Error> {
+        DisciplrVault::has_expired(env, vault_id)
+    }
+
+    fn time_remaining(env: Env, vault_id: u32) -> Result<u64, Error> {
+        DisciplrVault::time_remaining(env, vault_id)
+    }
+
+    fn list_public_vaults(env: Env, start: u32, limit: u32) -> Vec<u32> {
+        DisciplrVault::list_public_vaults(env, start, limit)
+    }
+
+    fn get_public_vault_count(env: Env) -> u32 {
+        DisciplrVault::get_public_vault_count(env)
+    }
+
+    fn get_vaults_by_status(env: Env, status: VaultStatus, start: u32, limit: u32) -> Vec<u32> {
+        DisciplrVault::get_vaults_by_status(env, status, start, limit)
+    }
+
+    fn version(env: Env) -> (u32, u32, u32) {
+        DisciplrVault::version(env)
+    }
+
+    fn supports_feature(env: Env, feature: Symbol) -> bool {
+        DisciplrVault::supports_feature(env, feature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger},
+        token::StellarAssetClient,
+    };
+
+    #[test]
+    fn test_interface_client_creates_and_reads_vault() {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().set_timestamp(100);
+
+        let contract_id = env.register(DisciplrVault, ());
+        let client = DisciplrVaultInterfaceClient::new(&env, &contract_id);
+
+        let token_admin = Address::generate(&env);
+        let token = env
+            .register_stellar_asset_contract_v2(token_admin)
+            .address();
+        let creator = Address::generate(&env);
+        StellarAssetClient::new(&env, &token).mint(&creator, &500);
+
+        let params = VaultParams {
+            usdc_token: token.clone(),
+            creator: creator.clone(),
+            amount: 500,
+            start_timestamp: 100,
+            end_timestamp: 1_000,
+            milestone_hash: BytesN::from_array(&env, &[1u8; 32]),
+            verifier: None,
+            success_destination: Address::generate(&env),
+            failure_destination: Address::generate(&env),
+            is_public: true,
+            arbiter: None,
+        };
+        let vault_id = client.create_vault_with_params(&params);
+
+        let vault = client.get_vault_state(&vault_id).unwrap();
+        assert_eq!(vault.creator, creator);
+        assert_eq!(vault.status, VaultStatus::Active);
+        assert_eq!(client.get_public_vault_count(), 1);
+        assert_eq!(client.time_remaining(&vault_id), 900);
+        assert_eq!(
+            client.try_get_vault_summaries(&Vec::from_array(&env, [vault_id; 51])),
+            Err(Ok(Error::BatchTooLarge))
+        );
+    }
+}
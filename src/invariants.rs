@@ -0,0 +1,757 @@
+//! Global and per-vault invariants.
+//!
+//! Shared by the on-chain `check_invariants` and `audit_vault` entry points and the
+//! state-machine tests, so the properties a monitoring bot can audit are exactly the ones the
+//! test suite enforces.
+
+use soroban_sdk::{contracttype, token, Env, Symbol, Vec};
+
+use crate::{
+    bonus::VaultBonus,
+    lists::{self, List},
+    math, pricing, strategies, DataKey, EscrowBreakdown, ProductivityVault, Settlement,
+    VaultStatus, EXPIRY_BUCKET_SECS,
+};
+
+/// Report of `audit_vault`: the accounting re-derived for one vault next to what is stored.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VaultAudit {
+    pub vault_id: u32,
+    pub status: VaultStatus,
+    /// Stake the contract should still escrow for the vault.
+    pub expected_escrow: i128,
+    /// Arbiter fee that should be reserved, and the fee the vault has reserved.
+    pub expected_reserved_fee: i128,
+    pub reserved_fee: i128,
+    /// Bonus pool escrowed for the vault.
+    pub bonus_pool: i128,
+    /// Total paid out by the settlement receipt (zero without one), and the part of it whose
+    /// legs were deferred to a claimable balance at settlement.
+    pub settled: i128,
+    pub deferred: i128,
+    /// Tracked escrow for the vault's token, across all vaults.
+    pub token_escrow: EscrowBreakdown,
+    /// Name of every failed check, empty when the vault is consistent:
+    /// - `structure`: fails `vault_is_consistent`.
+    /// - `reserved_fee`: `reserved_fee` differs from `expected_reserved_fee`.
+    /// - `expiry_index` / `status_index`: the index lists the vault when it should not, or not
+    ///   when it should.
+    /// - `receipt`: a settled vault's receipt does not add up to its stake.
+    /// - `escrow_shortfall`: the token's tracked escrow cannot cover this vault's stake or
+    ///   bonus pool.
+    /// - `balance_shortfall`: the contract holds less of the token than it tracks, counting
+    ///   principal deployed to yield strategies as held.
+    pub violations: Vec<Symbol>,
+}
+
+/// `true` if a vault may move from `from` to `to` (staying put is always allowed).
+///
+/// `Active` may move to any other status; `Disputed` only to the two ruling outcomes;
+/// `Completed`, `Failed` and `Cancelled` are final.
+pub fn is_valid_transition(from: VaultStatus, to: VaultStatus) -> bool {
+    if from == to {
+        return true;
+    }
+    match from {
+        VaultStatus::Active => true,
+        VaultStatus::Disputed => matches!(to, VaultStatus::Completed | VaultStatus::Failed),
+        VaultStatus::Completed | VaultStatus::Failed | VaultStatus::Cancelled => false,
+    }
+}
+
+/// `true` while the contract still holds the vault's stake.
+pub fn is_live(status: VaultStatus) -> bool {
+    matches!(status, VaultStatus::Active | VaultStatus::Disputed)
+}
+
+/// Amount the contract owes on behalf of `vault`: the full stake while live, zero once settled.
+pub fn escrowed_amount(vault: &ProductivityVault) -> i128 {
+    if is_live(vault.status) {
+        vault.amount
+    } else {
+        0
+    }
+}
+
+/// Structural checks on a single stored vault.
+pub fn vault_is_consistent(vault: &ProductivityVault) -> bool {
+    vault.amount > 0
+        && vault.end_timestamp > vault.start_timestamp
+        && vault.dispute_fee_reserved >= 0
+        && vault.dispute_fee_reserved <= vault.amount
+        && (vault.arbiter.is_some() || vault.status != VaultStatus::Disputed)
+}
+
+/// `true` if the expiry index lists `vault_id` exactly when the vault is live.
+pub fn expiry_index_is_consistent(env: &Env, vault_id: u32, vault: &ProductivityVault) -> bool {
+    let list = List::Expiry(vault.end_timestamp / EXPIRY_BUCKET_SECS);
+    let indexed = lists::contains(env, &list, vault_id);
+    indexed == is_live(vault.status)
+}
+
+/// `true` if the status index lists `vault_id` exactly when the vault is live, under its
+/// current status.
+pub fn status_index_is_consistent(env: &Env, vault_id: u32, vault: &ProductivityVault) -> bool {
+    let storage = env.storage().persistent();
+    match storage.get::<_, (VaultStatus, u32)>(&DataKey::StatusSlot(vault_id)) {
+        Some((status, position)) => {
+            status == vault.status
+                && storage.get::<_, u32>(&DataKey::StatusVault(status, position)) == Some(vault_id)
+        }
+        None => !is_live(vault.status),
+    }
+}
+
+/// `true` if `settlement` pays out exactly `gross`, and `gross` is the vault's stake.
+pub fn settlement_is_consistent(vault: &ProductivityVault, settlement: &Settlement) -> bool {
+    let net = settlement
+        .net_by_destination
+        .iter()
+        .try_fold(0i128, |sum, leg| math::add(sum, leg.amount));
+    settlement.gross == vault.amount
+        && net.and_then(|net| math::add(net, settlement.fee)) == Some(settlement.gross)
+}
+
+/// Re-derive `vault_id`'s accounting and compare it with stored state.
+pub fn audit(env: &Env, vault_id: u32, vault: &ProductivityVault) -> VaultAudit {
+    let storage = env.storage().persistent();
+    let mut violations = Vec::new(env);
+    let mut check = |ok: bool, name: &str| {
+        if !ok {
+            violations.push_back(Symbol::new(env, name));
+        }
+    };
+
+    let expected_escrow = escrowed_amount(vault);
+    let expected_reserved_fee = match vault.status {
+        VaultStatus::Active => 0,
+        VaultStatus::Disputed => {
+            pricing::arbiter_fee(vault.amount, vault.arbiter_fee_bps).unwrap_or(-1)
+        }
+        // Settled vaults keep whatever a ruling used.
+        _ => vault.dispute_fee_reserved,
+    };
+    let bonus_pool = storage
+        .get::<_, VaultBonus>(&DataKey::Bonus(vault_id))
+        .map_or(0, |bonus| bonus.amount);
+    let receipt: Option<Settlement> = storage.get(&DataKey::Settlement(vault_id));
+    let (settled, deferred) = receipt.as_ref().map_or((0, 0), |settlement| {
+        settlement.net_by_destination.iter().fold(
+            (settlement.fee, 0i128),
+            |(settled, deferred), leg| {
+                (
+                    settled.saturating_add(leg.amount),
+                    deferred.saturating_add(if leg.deferred { leg.amount } else { 0 }),
+                )
+            },
+        )
+    });
+    let token_escrow: EscrowBreakdown = storage
+        .get(&DataKey::Escrow(vault.token.clone()))
+        .unwrap_or_default();
+    let held = token::Client::new(env, &vault.token)
+        .balance(&env.current_contract_address())
+        .saturating_add(strategies::deployed_total(env, &vault.token));
+
+    check(vault_is_consistent(vault), "structure");
+    check(
+        vault.dispute_fee_reserved == expected_reserved_fee,
+        "reserved_fee",
+    );
+    check(
+        expiry_index_is_consistent(env, vault_id, vault),
+        "expiry_index",
+    );
+    check(
+        status_index_is_consistent(env, vault_id, vault),
+        "status_index",
+    );
+    check(
+        is_live(vault.status)
+            || receipt
+                .as_ref()
+                .is_none_or(|settlement| settlement_is_consistent(vault, settlement)),
+        "receipt",
+    );
+    check(
+        token_escrow.active_escrow >= expected_escrow && token_escrow.bonus_pools >= bonus_pool,
+        "escrow_shortfall",
+    );
+    check(
+        token_escrow.total().is_some_and(|total| held >= total),
+        "balance_shortfall",
+    );
+
+    VaultAudit {
+        vault_id,
+        status: vault.status,
+        expected_escrow,
+        expected_reserved_fee,
+        reserved_fee: vault.dispute_fee_reserved,
+        bonus_pool,
+        settled,
+        deferred,
+        token_escrow,
+        violations,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pricing::snooze_fee;
+    use crate::testutils::VaultFixture;
+    use crate::{DisputeRuling, VaultParams};
+    use soroban_sdk::{
+        testutils::{Address as _, EnvTestConfig, Ledger},
+        token::TokenClient,
+        Address,
+    };
+    use std::collections::BTreeSet;
+    use std::format;
+    use std::vec::Vec as StdVec;
+
+    extern crate std;
+
+    /// Deterministic xorshift so failing sequences are reproducible from the printed seed.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: u64) -> u64 {
+            self.next() % n
+        }
+    }
+
+    /// One call the harness makes. `usize` fields pick a created vault (or a recipient) modulo
+    /// how many exist when the op runs, so a sequence stays meaningful after shrinking drops
+    /// some of its ops.
+    #[derive(Clone, Debug)]
+    enum Op {
+        Create {
+            amount: i128,
+            secs: u64,
+            arbiter: bool,
+            retries: u32,
+        },
+        Advance(u64),
+        Fund(usize, i128),
+        Submit(usize),
+        Validate(usize),
+        Reject(usize),
+        Extend(usize, u64),
+        Release(usize),
+        Redirect(usize),
+        Cancel(usize),
+        Dispute(usize),
+        Resolve(usize, bool),
+        Block(usize),
+        Claim(usize),
+    }
+
+    impl Op {
+        /// Draw an op: creations and clock moves are weighted up so sequences have live vaults
+        /// that reach their deadlines.
+        fn arbitrary(rng: &mut Rng) -> Op {
+            let pick = rng.below(1 << 16) as usize;
+            match rng.below(16) {
+                0..=2 => Op::Create {
+                    amount: 1 + rng.below(5_000) as i128,
+                    secs: 1 + rng.below(3 * EXPIRY_BUCKET_SECS),
+                    arbiter: rng.below(2) == 0,
+                    retries: rng.below(3) as u32,
+                },
+                3 | 4 => Op::Advance(rng.below(2 * EXPIRY_BUCKET_SECS)),
+                5 => Op::Fund(pick, 1 + rng.below(1_000) as i128),
+                6 => Op::Submit(pick),
+                7 => Op::Validate(pick),
+                8 => Op::Reject(pick),
+                9 => Op::Extend(pick, 1 + rng.below(crate::MAX_SNOOZE_SECS)),
+                10 => Op::Release(pick),
+                11 => Op::Redirect(pick),
+                12 => Op::Cancel(pick),
+                13 => Op::Dispute(pick),
+                14 => Op::Resolve(pick, rng.below(2) == 0),
+                _ if rng.below(2) == 0 => Op::Block(pick),
+                _ => Op::Claim(pick),
+            }
+        }
+
+        fn sequence(seed: u64, len: usize) -> StdVec<Op> {
+            let mut rng = Rng(seed);
+            (0..len).map(|_| Op::arbitrary(&mut rng)).collect()
+        }
+    }
+
+    /// The contract under test and the model it is checked against: what was minted and
+    /// charged, plus the expected status, deadline and rejections of each created vault and
+    /// the vaults expected in the verifier's review queue.
+    struct Harness {
+        fixture: VaultFixture,
+        arbiter: Address,
+        sponsor: Address,
+        recipients: StdVec<Address>,
+        minted: i128,
+        fees: i128,
+        vault_ids: StdVec<u32>,
+        statuses: StdVec<VaultStatus>,
+        deadlines: StdVec<u64>,
+        rejections: StdVec<u32>,
+        in_review: BTreeSet<u32>,
+    }
+
+    impl Harness {
+        fn new() -> Self {
+            let env = Env::new_with_config(EnvTestConfig {
+                capture_snapshot_at_drop: false,
+            });
+            let fixture = VaultFixture::new(&env);
+            env.ledger().set_timestamp(fixture.start_timestamp);
+            let client = fixture.client();
+            let arbiter = Address::generate(&env);
+            let sponsor = Address::generate(&env);
+            client.initialize(&Address::generate(&env));
+            client.register_arbiter(&arbiter, &300);
+
+            let recipients = std::vec![
+                fixture.creator.clone(),
+                fixture.success_destination.clone(),
+                fixture.failure_destination.clone(),
+                arbiter.clone(),
+                sponsor.clone(),
+            ];
+            Harness {
+                fixture,
+                arbiter,
+                sponsor,
+                recipients,
+                minted: 0,
+                fees: 0,
+                vault_ids: StdVec::new(),
+                statuses: StdVec::new(),
+                deadlines: StdVec::new(),
+                rejections: StdVec::new(),
+                in_review: BTreeSet::new(),
+            }
+        }
+
+        /// Position and id of the created vault `pick` selects, if there are any.
+        fn vault(&self, pick: usize) -> Option<(usize, u32)> {
+            let i = pick.checked_rem(self.vault_ids.len())?;
+            Some((i, self.vault_ids[i]))
+        }
+
+        fn mint(&mut self, to: &Address, amount: i128) {
+            crate::testutils::mint(&self.fixture.env, &self.fixture.token, to, amount);
+            self.minted += amount;
+        }
+
+        fn apply(&mut self, op: &Op) {
+            let env = self.fixture.env.clone();
+            let client = crate::DisciplrVaultClient::new(&env, &self.fixture.contract_id);
+            let token = self.fixture.token.clone();
+            match op {
+                Op::Create {
+                    amount,
+                    secs,
+                    arbiter,
+                    retries,
+                } => {
+                    let now = self.fixture.env.ledger().timestamp();
+                    let params = VaultParams {
+                        amount: *amount,
+                        start_timestamp: now,
+                        end_timestamp: now + secs,
+                        arbiter: arbiter.then(|| self.arbiter.clone()),
+                        ..self.fixture.params()
+                    };
+                    self.mint(&params.creator.clone(), *amount);
+                    // Fails (and keeps the mint with the creator) while a destination is blocked.
+                    let created = if *retries == 0 {
+                        client.try_create_vault_with_params(&params)
+                    } else {
+                        client.try_create_vault_with_retries(&params, retries)
+                    };
+                    if let Ok(Ok(vault_id)) = created {
+                        self.vault_ids.push(vault_id);
+                        self.statuses.push(VaultStatus::Active);
+                        self.deadlines.push(params.end_timestamp);
+                        self.rejections.push(0);
+                    }
+                }
+                Op::Advance(secs) => {
+                    let now = self.fixture.env.ledger().timestamp();
+                    self.fixture.env.ledger().set_timestamp(now + secs);
+                }
+                Op::Fund(pick, amount) => {
+                    if let Some((_, id)) = self.vault(*pick) {
+                        let sponsor = self.sponsor.clone();
+                        self.mint(&sponsor, *amount);
+                        let _ = client.try_fund_bonus(&id, &sponsor, amount, &100);
+                    }
+                }
+                Op::Submit(pick) => {
+                    if let Some((_, id)) = self.vault(*pick) {
+                        if let Ok(Ok(_)) = client.try_submit_for_review(&id) {
+                            self.in_review.insert(id);
+                        }
+                    }
+                }
+                Op::Validate(pick) => {
+                    if let Some((_, id)) = self.vault(*pick) {
+                        if let Ok(Ok(_)) = client.try_validate_milestone(&id, &None) {
+                            self.in_review.remove(&id);
+                        }
+                    }
+                }
+                Op::Reject(pick) => {
+                    if let Some((i, id)) = self.vault(*pick) {
+                        if let Ok(Ok(settled)) = client.try_reject_milestone(&id, &token, &None) {
+                            self.in_review.remove(&id);
+                            if !settled {
+                                self.rejections[i] += 1;
+                            }
+                        }
+                    }
+                }
+                Op::Extend(pick, secs) => {
+                    if let Some((i, id)) = self.vault(*pick) {
+                        // Give the creator the fee, then book what the snooze actually charged.
+                        let creator = self.fixture.creator.clone();
+                        let amount = client.get_vault_state(&id).unwrap().amount;
+                        self.mint(&creator, snooze_fee(amount, crate::SNOOZE_FEE_BPS).unwrap());
+                        let balance = TokenClient::new(&env, &token);
+                        let before = balance.balance(&creator);
+                        if let Ok(Ok(_)) = client.try_snooze(&id, secs) {
+                            self.deadlines[i] += secs;
+                            self.fees += before - balance.balance(&creator);
+                        }
+                    }
+                }
+                Op::Release(pick) => {
+                    if let Some((_, id)) = self.vault(*pick) {
+                        let _ = client.try_release_funds(&id, &token, &None);
+                    }
+                }
+                Op::Redirect(pick) => {
+                    if let Some((_, id)) = self.vault(*pick) {
+                        let _ = client.try_redirect_funds(&id, &token, &None);
+                    }
+                }
+                Op::Cancel(pick) => {
+                    if let Some((_, id)) = self.vault(*pick) {
+                        let _ = client.try_cancel_vault(&id, &token, &None);
+                    }
+                }
+                Op::Dispute(pick) => {
+                    if let Some((_, id)) = self.vault(*pick) {
+                        let _ = client.try_open_dispute(&id, &self.fixture.creator);
+                    }
+                }
+                Op::Resolve(pick, release) => {
+                    if let Some((_, id)) = self.vault(*pick) {
+                        let ruling = if *release {
+                            DisputeRuling::Release
+                        } else {
+                            DisputeRuling::Redirect
+                        };
+                        let _ = client.try_resolve_dispute(&id, &ruling, &token);
+                    }
+                }
+                Op::Block(pick) => {
+                    // Toggle the blocklist on a destination.
+                    let target = &self.recipients[1 + pick % 2];
+                    if client.is_address_blocked(target) {
+                        client.unblock_address(target);
+                    } else {
+                        client.block_address(target);
+                    }
+                }
+                Op::Claim(pick) => {
+                    let _ =
+                        client.try_claim(&self.recipients[pick % self.recipients.len()], &token);
+                }
+            }
+        }
+
+        /// Check every invariant, describing the first that fails.
+        fn check(&mut self) -> Result<(), std::string::String> {
+            let f = &self.fixture;
+            let client = f.client();
+            let token = TokenClient::new(&f.env, &f.token);
+
+            let mut escrowed = 0i128;
+            let mut bonus_pools = 0i128;
+            for (i, id) in self.vault_ids.iter().enumerate() {
+                let vault = client.get_vault_state(id).unwrap();
+                let audit = client.audit_vault(id);
+                if !audit.violations.is_empty() {
+                    return Err(format!("vault {id} audit failed: {:?}", audit.violations));
+                }
+                if !is_valid_transition(self.statuses[i], vault.status) {
+                    return Err(format!(
+                        "vault {id} moved {:?} -> {:?}",
+                        self.statuses[i], vault.status
+                    ));
+                }
+                self.statuses[i] = vault.status;
+                // Only snoozes move a deadline here, by exactly what they asked for.
+                if vault.end_timestamp != self.deadlines[i] {
+                    return Err(format!(
+                        "vault {id} ends at {} instead of {}",
+                        vault.end_timestamp, self.deadlines[i]
+                    ));
+                }
+                // A rejection with retries left keeps the vault open for the next attempt.
+                let rejections = client.get_retry_policy(id).map_or(0, |policy| {
+                    assert!(policy.rejections <= policy.max_retries);
+                    policy.rejections
+                });
+                if rejections != self.rejections[i] {
+                    return Err(format!(
+                        "vault {id} has {rejections} rejections instead of {}",
+                        self.rejections[i]
+                    ));
+                }
+                // Validation and settlement take a vault out of its verifier's queue.
+                if !is_live(vault.status) || vault.milestone_validated {
+                    self.in_review.remove(id);
+                }
+                escrowed += escrowed_amount(&vault);
+                bonus_pools += client.get_bonus(id).map_or(0, |bonus| bonus.amount);
+            }
+
+            let limit = client.get_limits().page_limit;
+            let verifier = &f.verifier;
+            let mut queued = BTreeSet::new();
+            let mut start = 0;
+            while start < client.get_verifier_workload(verifier) {
+                for review in client.get_pending_reviews(verifier, &start, &limit).iter() {
+                    queued.insert(review.vault_id);
+                }
+                start += limit;
+            }
+            if queued != self.in_review {
+                return Err(format!(
+                    "review queue {queued:?}, expected {:?}",
+                    self.in_review
+                ));
+            }
+            if client.get_verifier_workload(verifier) as usize != queued.len() {
+                return Err("verifier workload differs from its queue".into());
+            }
+
+            let mut claimable = 0i128;
+            let mut paid_out = 0i128;
+            for recipient in &self.recipients {
+                claimable += client.get_claimable(recipient, &f.token);
+                paid_out += token.balance(recipient);
+            }
+
+            // Escrow balance conservation: the contract holds exactly live stakes, claimables,
+            // bonus pools and accrued fees, and its own accounting agrees.
+            let held = token.balance(&f.contract_id);
+            let breakdown = client.get_escrow_breakdown(&f.token);
+            let expected = [
+                ("active escrow", breakdown.active_escrow, escrowed),
+                ("pending claims", breakdown.pending_claims, claimable),
+                ("bonus pools", breakdown.bonus_pools, bonus_pools),
+                ("accrued fees", breakdown.accrued_fees, self.fees),
+                ("tracked total", breakdown.total().unwrap_or(-1), held),
+                (
+                    "escrow",
+                    held,
+                    escrowed + claimable + bonus_pools + self.fees,
+                ),
+                // No double payouts: nothing is created or destroyed across all holders.
+                ("conserved value", held + paid_out, self.minted),
+            ];
+            for (name, actual, expected) in expected {
+                if actual != expected {
+                    return Err(format!("{name}: {actual} != {expected}"));
+                }
+            }
+
+            let count = self.vault_ids.len() as u32;
+            let mut start = 0;
+            while start < count {
+                if client.try_check_invariants(&start, &crate::MAX_BATCH_SIZE) != Ok(Ok(())) {
+                    return Err(format!("check_invariants failed from {start}"));
+                }
+                start += crate::MAX_BATCH_SIZE;
+            }
+            Ok(())
+        }
+    }
+
+    /// Run `ops` on a fresh contract, checking the invariants after each; on failure returns
+    /// the index of the op after which they broke and what broke.
+    fn run(ops: &[Op]) -> Result<(), (usize, std::string::String)> {
+        let mut harness = Harness::new();
+        for (step, op) in ops.iter().enumerate() {
+            harness.apply(op);
+            harness.check().map_err(|failure| (step, failure))?;
+        }
+        Ok(())
+    }
+
+    /// Shrink a failing sequence: cut it after the failing op, then drop runs of ops, halving
+    /// the run length down to single ops, for as long as the rest still fails. Passes repeat
+    /// until one removes nothing, so ops that only matter together (a block and its unblock)
+    /// still go.
+    fn shrink(
+        mut ops: StdVec<Op>,
+        mut failure: (usize, std::string::String),
+    ) -> (StdVec<Op>, std::string::String) {
+        ops.truncate(failure.0 + 1);
+        loop {
+            let before = ops.len();
+            let mut chunk = ops.len() / 2;
+            while chunk > 0 {
+                let mut start = 0;
+                while start + chunk <= ops.len() {
+                    let mut candidate = ops.clone();
+                    candidate.drain(start..start + chunk);
+                    match run(&candidate) {
+                        Err(smaller) => {
+                            candidate.truncate(smaller.0 + 1);
+                            ops = candidate;
+                            failure = smaller;
+                        }
+                        Ok(()) => start += chunk,
+                    }
+                }
+                chunk /= 2;
+            }
+            if ops.len() == before {
+                return (ops, failure.1);
+            }
+        }
+    }
+
+    /// Generation and shrinking are done here rather than with `proptest`, which the offline
+    /// build cannot fetch; a failure prints its seed and the shrunk sequence.
+    #[test]
+    fn prop_random_operation_sequences_preserve_invariants() {
+        for seed in [
+            0x9E37_79B9_7F4A_7C15u64,
+            0xD1B5_4A32_D192_ED03,
+            0x2545_F491,
+            0x5851_F42D_4C95_7F2D,
+        ] {
+            let ops = Op::sequence(seed, 80);
+            if let Err(failure) = run(&ops) {
+                let (minimal, failure) = shrink(ops, failure);
+                panic!("seed {seed:#x}: {failure} after {minimal:#?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_retry_review_and_snooze_path_preserves_invariants() {
+        let create = Op::Create {
+            amount: 4_000,
+            secs: 2 * EXPIRY_BUCKET_SECS,
+            arbiter: false,
+            retries: 1,
+        };
+        let ops = std::vec![
+            create,
+            Op::Fund(0, 500),
+            Op::Submit(0),
+            Op::Reject(0),
+            Op::Extend(0, 3_600),
+            Op::Submit(0),
+            Op::Reject(0),
+        ];
+        let mut harness = Harness::new();
+        for op in &ops {
+            harness.apply(op);
+            harness.check().unwrap();
+        }
+        assert_eq!(harness.rejections, std::vec![1]);
+        assert_eq!(harness.statuses, std::vec![VaultStatus::Failed]);
+        assert_eq!(harness.deadlines[0], 100 + 2 * EXPIRY_BUCKET_SECS + 3_600);
+        assert!(harness.fees > 0);
+        assert!(harness.in_review.is_empty());
+    }
+
+    #[test]
+    fn test_check_invariants_detects_missing_expiry_entry() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let vault_id = fixture.create_vault();
+        let client = fixture.client();
+        assert_eq!(client.try_check_invariants(&0, &10), Ok(Ok(())));
+
+        env.as_contract(&fixture.contract_id, || {
+            env.storage().persistent().remove(&lists::ListKey::Position(
+                List::Expiry(fixture.end_timestamp / EXPIRY_BUCKET_SECS),
+                vault_id,
+            ));
+        });
+        assert_eq!(
+            client.try_check_invariants(&vault_id, &1),
+            Err(Ok(crate::Error::InvariantViolated))
+        );
+    }
+
+    #[test]
+    fn test_audit_vault_reports_accounting_drift() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let vault_id = fixture.create_vault();
+        let audit = client.audit_vault(&vault_id);
+        assert_eq!(audit.status, VaultStatus::Active);
+        assert_eq!(audit.expected_escrow, fixture.amount);
+        assert_eq!(audit.token_escrow.active_escrow, fixture.amount);
+        assert!(audit.violations.is_empty());
+
+        env.as_contract(&fixture.contract_id, || {
+            env.storage().persistent().set(
+                &DataKey::Escrow(fixture.token.clone()),
+                &EscrowBreakdown::default(),
+            );
+            env.storage()
+                .persistent()
+                .remove(&DataKey::StatusSlot(vault_id));
+        });
+        let audit = client.audit_vault(&vault_id);
+        assert_eq!(
+            audit.violations,
+            soroban_sdk::vec![
+                &env,
+                Symbol::new(&env, "status_index"),
+                Symbol::new(&env, "escrow_shortfall"),
+            ]
+        );
+
+        let settled = fixture.create_vault();
+        client.cancel_vault(&settled, &fixture.token, &None);
+        let audit = client.audit_vault(&settled);
+        assert_eq!(audit.expected_escrow, 0);
+        assert_eq!(audit.settled, fixture.amount);
+        assert_eq!(
+            client.try_audit_vault(&99),
+            Err(Ok(crate::Error::VaultNotFound))
+        );
+    }
+
+    #[test]
+    fn test_transition_table() {
+        use VaultStatus::*;
+        assert!(is_valid_transition(Active, Disputed));
+        assert!(is_valid_transition(Disputed, Completed));
+        assert!(!is_valid_transition(Disputed, Cancelled));
+        assert!(!is_valid_transition(Completed, Active));
+        assert!(!is_valid_transition(Cancelled, Failed));
+    }
+}
@@ -0,0 +1,247 @@
+//! Keeper bounties for redirecting expired vaults.
+//!
+//! Keepers call `redirect_expired` to settle vaults whose deadline passed unvalidated, and are
+//! paid a bounty out of each redirected stake under the admin's [`BountyPolicy`]. The bounty is
+//! the first leg of the redirect (`PayoutKind::KeeperBounty`); the failure destination gets the
+//! rest.
+//!
+//! Creators who want their vault settled promptly can raise the rate on it with
+//! `set_vault_bounty`, paid from their own stake; keepers read the resulting amount with
+//! `get_effective_bounty` to prioritize.
+//!
+//! Keepers registered by the admin earn the full bounty, credited to their claimable balance
+//! instead of transferred, so a keeper settling many vaults withdraws once with `claim` rather
+//! than paying for a transfer per vault. Unregistered callers are paid inline and at most
+//! `unregistered_cap` per vault, which keeps sniping other keepers' transactions unprofitable.
+
+use soroban_sdk::{contracttype, Address, Env};
+
+use crate::pricing;
+
+/// Highest bounty rate the admin can set, in basis points of the stake.
+pub const MAX_KEEPER_BOUNTY_BPS: u32 = 500;
+
+/// Storage keys of the keeper registry and bounty policy.
+#[contracttype]
+#[derive(Clone)]
+pub enum KeeperKey {
+    /// The protocol bounty policy (instance storage).
+    BountyPolicy,
+    /// Registered keeper -> `true`.
+    Keeper(Address),
+    /// Vault id -> bounty rate its creator set, in basis points.
+    VaultBounty(u32),
+}
+
+/// Protocol-level keeper bounty.
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BountyPolicy {
+    /// Bounty per redirected vault, in basis points of its stake (rounded down).
+    pub bounty_bps: u32,
+    /// Most an unregistered caller is paid per vault.
+    pub unregistered_cap: i128,
+}
+
+/// Stored bounty policy; no bounty until the admin sets one.
+pub fn policy(env: &Env) -> BountyPolicy {
+    env.storage()
+        .instance()
+        .get(&KeeperKey::BountyPolicy)
+        .unwrap_or_default()
+}
+
+/// Whether `keeper` is registered.
+pub fn is_registered(env: &Env, keeper: &Address) -> bool {
+    env.storage()
+        .persistent()
+        .has(&KeeperKey::Keeper(keeper.clone()))
+}
+
+/// Rate the creator of `vault_id` set, if any.
+pub fn vault_bps(env: &Env, vault_id: u32) -> Option<u32> {
+    env.storage()
+        .persistent()
+        .get(&KeeperKey::VaultBounty(vault_id))
+}
+
+/// Set or clear the creator's rate for `vault_id`.
+pub fn set_vault_bps(env: &Env, vault_id: u32, bounty_bps: Option<u32>) {
+    let key = KeeperKey::VaultBounty(vault_id);
+    match bounty_bps {
+        Some(bps) => env.storage().persistent().set(&key, &bps),
+        None => env.storage().persistent().remove(&key),
+    }
+}
+
+/// Bounty rate for `vault_id`: the protocol rate, or the creator's if higher.
+pub fn effective_bps(env: &Env, vault_id: u32) -> u32 {
+    let base = policy(env).bounty_bps;
+    vault_bps(env, vault_id).map_or(base, |bps| bps.max(base))
+}
+
+/// Full bounty for redirecting `vault_id` with a stake of `amount`, as a registered keeper
+/// earns it.
+pub fn full_bounty(env: &Env, vault_id: u32, amount: i128) -> Option<i128> {
+    pricing::keeper_bounty(amount, effective_bps(env, vault_id), None)
+}
+
+/// Bounty `keeper` earns for redirecting `vault_id` with a stake of `amount`.
+pub fn bounty(env: &Env, vault_id: u32, keeper: &Address, amount: i128) -> Option<i128> {
+    let cap = if is_registered(env, keeper) {
+        None
+    } else {
+        Some(policy(env).unregistered_cap)
+    };
+    pricing::keeper_bounty(amount, effective_bps(env, vault_id), cap)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use crate::{Error, VaultStatus};
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger},
+        token::TokenClient,
+        vec,
+    };
+
+    #[test]
+    fn test_registered_keeper_accrues_and_unregistered_is_capped() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let token = TokenClient::new(&env, &fixture.token);
+        client.initialize(&Address::generate(&env));
+        let policy = BountyPolicy {
+            bounty_bps: 100,
+            unregistered_cap: 10,
+        };
+        client.set_bounty_policy(&policy);
+        assert_eq!(client.get_bounty_policy(), policy);
+        let keeper = Address::generate(&env);
+        client.register_keeper(&keeper);
+        assert!(client.is_keeper(&keeper));
+
+        let first = fixture.create_vault();
+        let second = fixture.create_vault();
+        let third = fixture.create_vault();
+        env.ledger().set_timestamp(fixture.end_timestamp);
+        assert_eq!(
+            client.redirect_expired(&keeper, &vec![&env, first, second]),
+            2
+        );
+
+        // Accrued, not transferred: one withdrawal for the whole batch.
+        let bounty = fixture.amount / 100;
+        assert_eq!(token.balance(&keeper), 0);
+        assert_eq!(client.get_claimable(&keeper, &fixture.token), 2 * bounty);
+        assert_eq!(
+            token.balance(&fixture.failure_destination),
+            2 * (fixture.amount - bounty)
+        );
+        let receipt = client.get_settlement(&first).unwrap();
+        assert_eq!(receipt.fee, bounty);
+        assert_eq!(receipt.actor, Some(keeper.clone()));
+        assert_eq!(client.claim(&keeper, &fixture.token), 2 * bounty);
+        assert_eq!(token.balance(&keeper), 2 * bounty);
+
+        let sniper = Address::generate(&env);
+        client.redirect_expired(&sniper, &vec![&env, third]);
+        assert_eq!(token.balance(&sniper), 10);
+        let receipt = client.get_settlement(&third).unwrap();
+        assert_eq!(receipt.fee, 10);
+        assert_eq!(
+            client.get_vault_state(&third).unwrap().status,
+            VaultStatus::Failed
+        );
+        assert_eq!(
+            client.get_escrow_breakdown(&fixture.token).pending_claims,
+            0
+        );
+    }
+
+    #[test]
+    fn test_keeper_admin_gating_and_policy_bounds() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let keeper = Address::generate(&env);
+        assert_eq!(
+            client.try_register_keeper(&keeper),
+            Err(Ok(Error::NotInitialized))
+        );
+        client.initialize(&Address::generate(&env));
+        assert_eq!(
+            client.try_set_bounty_policy(&BountyPolicy {
+                bounty_bps: MAX_KEEPER_BOUNTY_BPS + 1,
+                unregistered_cap: 0,
+            }),
+            Err(Ok(Error::InvalidFee))
+        );
+        assert_eq!(
+            client.try_set_bounty_policy(&BountyPolicy {
+                bounty_bps: 0,
+                unregistered_cap: -1,
+            }),
+            Err(Ok(Error::InvalidAmount))
+        );
+
+        client.register_keeper(&keeper);
+        client.remove_keeper(&keeper);
+        assert!(!client.is_keeper(&keeper));
+
+        // Without a policy a redirect pays the whole stake to the failure destination.
+        let vault_id = fixture.create_vault();
+        env.ledger().set_timestamp(fixture.end_timestamp);
+        client.redirect_expired(&keeper, &vec![&env, vault_id]);
+        assert_eq!(
+            TokenClient::new(&env, &fixture.token).balance(&fixture.failure_destination),
+            fixture.amount
+        );
+        assert_eq!(client.get_settlement(&vault_id).unwrap().fee, 0);
+    }
+
+    #[test]
+    fn test_creator_raises_bounty_on_own_vault() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let token = TokenClient::new(&env, &fixture.token);
+        client.initialize(&Address::generate(&env));
+        client.set_bounty_policy(&BountyPolicy {
+            bounty_bps: 100,
+            unregistered_cap: i128::MAX,
+        });
+        let vault_id = fixture.create_vault();
+        assert_eq!(client.get_effective_bounty(&vault_id), fixture.amount / 100);
+
+        client.set_vault_bounty(&vault_id, &Some(300));
+        assert_eq!(
+            client.get_effective_bounty(&vault_id),
+            fixture.amount * 3 / 100
+        );
+        // A rate below the protocol's never lowers the bounty.
+        client.set_vault_bounty(&vault_id, &Some(50));
+        assert_eq!(client.get_effective_bounty(&vault_id), fixture.amount / 100);
+        assert_eq!(
+            client.try_set_vault_bounty(&vault_id, &Some(MAX_KEEPER_BOUNTY_BPS + 1)),
+            Err(Ok(Error::InvalidFee))
+        );
+        client.set_vault_bounty(&vault_id, &Some(300));
+
+        let keeper = Address::generate(&env);
+        env.ledger().set_timestamp(fixture.end_timestamp);
+        client.redirect_expired(&keeper, &vec![&env, vault_id]);
+        assert_eq!(token.balance(&keeper), fixture.amount * 3 / 100);
+        assert_eq!(
+            token.balance(&fixture.failure_destination),
+            fixture.amount * 97 / 100
+        );
+        assert_eq!(
+            client.try_set_vault_bounty(&vault_id, &None),
+            Err(Ok(Error::VaultNotActive))
+        );
+    }
+}
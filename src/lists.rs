@@ -0,0 +1,154 @@
+//! Position-keyed vault id lists.
+//!
+//! Indexes that anyone can grow (every vault expiring on one day, every vault sharing a
+//! milestone hash, every vault naming a verifier) cannot live in one `Vec` entry: each insert and removal would rewrite the
+//! whole list, so flooding it with cheap vaults would eventually push the entry past the
+//! ledger's size or budget limits and stop the real vaults in it from settling. Like the
+//! per-status index, each list here is stored one id per entry, with a length and a reverse
+//! id -> position entry, so adding or removing an id touches a constant number of entries
+//! however long the list is. Removal swaps the last id into the freed position, so lists stay
+//! dense but are not kept in insertion order.
+
+use soroban_sdk::{contracttype, Address, BytesN, Env, Vec};
+
+/// A vault id list.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum List {
+    /// Live vaults whose `end_timestamp` falls in the expiry bucket (day number).
+    Expiry(u64),
+    /// Vaults whose current milestone hash is the given hash (see `hash_index`).
+    Hash(BytesN<32>),
+    /// Vaults submitted for the verifier's review.
+    Review(Address),
+}
+
+/// Storage keys of the position-keyed lists.
+#[contracttype]
+#[derive(Clone)]
+pub enum ListKey {
+    /// Number of ids in a list.
+    Len(List),
+    /// Position in a list -> vault id.
+    Entry(List, u32),
+    /// Vault id -> its position in a list.
+    Position(List, u32),
+}
+
+/// Number of ids in `list`.
+pub fn len(env: &Env, list: &List) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&ListKey::Len(list.clone()))
+        .unwrap_or(0)
+}
+
+/// Id at `position` in `list`, if any.
+pub fn get(env: &Env, list: &List, position: u32) -> Option<u32> {
+    env.storage()
+        .persistent()
+        .get(&ListKey::Entry(list.clone(), position))
+}
+
+/// Whether `list` holds `vault_id`.
+pub fn contains(env: &Env, list: &List, vault_id: u32) -> bool {
+    env.storage()
+        .persistent()
+        .has(&ListKey::Position(list.clone(), vault_id))
+}
+
+/// Ids at positions `start..start + limit` of `list`.
+pub fn page(env: &Env, list: &List, start: u32, limit: u32) -> Vec<u32> {
+    let end = start.saturating_add(limit).min(len(env, list));
+    let mut ids = Vec::new(env);
+    for position in start..end {
+        if let Some(vault_id) = get(env, list, position) {
+            ids.push_back(vault_id);
+        }
+    }
+    ids
+}
+
+/// Append `vault_id` to `list`, unless it is already there.
+pub fn push(env: &Env, list: &List, vault_id: u32) {
+    if contains(env, list, vault_id) {
+        return;
+    }
+    let storage = env.storage().persistent();
+    let position = len(env, list);
+    storage.set(&ListKey::Entry(list.clone(), position), &vault_id);
+    storage.set(&ListKey::Position(list.clone(), vault_id), &position);
+    storage.set(&ListKey::Len(list.clone()), &(position + 1));
+}
+
+/// Drop `vault_id` from `list`, moving the last id into its position.
+pub fn remove(env: &Env, list: &List, vault_id: u32) {
+    let storage = env.storage().persistent();
+    let position_key = ListKey::Position(list.clone(), vault_id);
+    let Some(position) = storage.get::<_, u32>(&position_key) else {
+        return;
+    };
+    storage.remove(&position_key);
+    let last = len(env, list).saturating_sub(1);
+    let last_key = ListKey::Entry(list.clone(), last);
+    if position != last {
+        if let Some(moved) = storage.get::<_, u32>(&last_key) {
+            storage.set(&ListKey::Entry(list.clone(), position), &moved);
+            storage.set(&ListKey::Position(list.clone(), moved), &position);
+        }
+    }
+    storage.remove(&last_key);
+    if last == 0 {
+        storage.remove(&ListKey::Len(list.clone()));
+    } else {
+        storage.set(&ListKey::Len(list.clone()), &last);
+    }
+}
+
+/// Keys holding `vault_id`'s membership of `list`, for TTL maintenance.
+pub fn keys_of(env: &Env, list: &List, vault_id: u32) -> Vec<ListKey> {
+    let mut keys = Vec::new(env);
+    if let Some(position) = env
+        .storage()
+        .persistent()
+        .get::<_, u32>(&ListKey::Position(list.clone(), vault_id))
+    {
+        keys.push_back(ListKey::Len(list.clone()));
+        keys.push_back(ListKey::Entry(list.clone(), position));
+        keys.push_back(ListKey::Position(list.clone(), vault_id));
+    }
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use soroban_sdk::vec;
+
+    #[test]
+    fn test_removal_swaps_the_last_id_into_the_gap() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        env.as_contract(&fixture.contract_id, || {
+            let list = List::Expiry(7);
+            for vault_id in [10, 11, 12, 13] {
+                push(&env, &list, vault_id);
+            }
+            push(&env, &list, 11);
+            assert_eq!(len(&env, &list), 4);
+
+            remove(&env, &list, 11);
+            assert_eq!(page(&env, &list, 0, 10), vec![&env, 10, 13, 12]);
+            assert!(!contains(&env, &list, 11));
+            remove(&env, &list, 12);
+            remove(&env, &list, 99);
+            assert_eq!(page(&env, &list, 1, 10), vec![&env, 13]);
+
+            remove(&env, &list, 10);
+            remove(&env, &list, 13);
+            assert_eq!(len(&env, &list), 0);
+            assert!(!env.storage().persistent().has(&ListKey::Len(list.clone())));
+        });
+    }
+}
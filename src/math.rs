@@ -0,0 +1,204 @@
+//! Overflow-safe arithmetic for fee, split and penalty calculations.
+//!
+//! Every entry point that derives an amount from a stake goes through these helpers instead of
+//! doing raw `i128` math, so rounding direction is always explicit and overflow surfaces as
+//! `None` (mapped to `Error::ArithmeticOverflow` by callers) rather than a wrapped value.
+//!
+//! All helpers operate on non-negative amounts; negative inputs return `None`.
+
+use crate::BPS_DENOMINATOR;
+
+/// Rounding direction for divisions.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Rounding {
+    /// Round towards zero. Use for amounts paid *out* of a stake (fees, payouts to third parties)
+    /// so the contract never pays more than it holds.
+    Down,
+    /// Round away from zero. Use for amounts *reserved* or withheld.
+    Up,
+}
+
+/// Compute `value * numerator / denominator` with the given rounding.
+///
+/// Returns `None` if any input is negative, `denominator` is zero, or the result does not fit in
+/// an `i128`. Intermediate products that overflow are handled by splitting `value` into quotient
+/// and remainder, so `mul_div(i128::MAX, 1, 1)` succeeds.
+pub fn mul_div(
+    value: i128,
+    numerator: i128,
+    denominator: i128,
+    rounding: Rounding,
+) -> Option<i128> {
+    if value < 0 || numerator < 0 || denominator <= 0 {
+        return None;
+    }
+
+    let (quotient, remainder) = match value.checked_mul(numerator) {
+        Some(product) => (product / denominator, product % denominator),
+        None => {
+            // value * numerator = (q * d + r) * numerator = q * numerator * d + r * numerator
+            let q = value / denominator;
+            let r = value % denominator;
+            let r_product = r.checked_mul(numerator)?;
+            let whole = q.checked_mul(numerator)?;
+            (
+                whole.checked_add(r_product / denominator)?,
+                r_product % denominator,
+            )
+        }
+    };
+
+    match rounding {
+        Rounding::Down => Some(quotient),
+        Rounding::Up if remainder > 0 => quotient.checked_add(1),
+        Rounding::Up => Some(quotient),
+    }
+}
+
+/// Apply a basis-point rate to `amount`. `bps` above `BPS_DENOMINATOR` returns `None`.
+pub fn apply_bps(amount: i128, bps: u32, rounding: Rounding) -> Option<i128> {
+    if bps > BPS_DENOMINATOR {
+        return None;
+    }
+    mul_div(amount, bps as i128, BPS_DENOMINATOR as i128, rounding)
+}
+
+/// Split `amount` into `(part, rest)` where `part = apply_bps(amount, bps, Down)`.
+///
+/// `part + rest == amount` always holds, so splits never create or destroy value.
+pub fn split_bps(amount: i128, bps: u32) -> Option<(i128, i128)> {
+    let part = apply_bps(amount, bps, Rounding::Down)?;
+    Some((part, amount.checked_sub(part)?))
+}
+
+/// `a + b` for non-negative amounts, `None` on overflow or negative input.
+pub fn add(a: i128, b: i128) -> Option<i128> {
+    if a < 0 || b < 0 {
+        return None;
+    }
+    a.checked_add(b)
+}
+
+/// `a - b` for non-negative amounts, `None` if `b > a` or on negative input.
+pub fn sub(a: i128, b: i128) -> Option<i128> {
+    if a < 0 || b < 0 || b > a {
+        return None;
+    }
+    Some(a - b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic pseudo-random sample of non-negative amounts, biased towards edge values.
+    fn sample_amounts() -> [i128; 64] {
+        let mut out = [0i128; 64];
+        let edges = [
+            0,
+            1,
+            2,
+            9_999,
+            10_000,
+            10_001,
+            1_000_000,
+            i64::MAX as i128,
+            i128::MAX / 10_000,
+            i128::MAX / 2,
+            i128::MAX - 1,
+            i128::MAX,
+        ];
+        out[..edges.len()].copy_from_slice(&edges);
+        let mut state: u128 = 0x2545_F491_4F6C_DD1D;
+        for slot in out.iter_mut().skip(edges.len()) {
+            // xorshift
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            *slot = (state >> 1) as i128 >> (state % 100) as u32;
+        }
+        out
+    }
+
+    const SAMPLE_BPS: [u32; 8] = [0, 1, 25, 333, 5_000, 9_999, 10_000, 7_777];
+
+    #[test]
+    fn test_mul_div_matches_simple_cases() {
+        assert_eq!(mul_div(10, 3, 4, Rounding::Down), Some(7));
+        assert_eq!(mul_div(10, 3, 4, Rounding::Up), Some(8));
+        assert_eq!(mul_div(12, 3, 4, Rounding::Up), Some(9));
+        assert_eq!(mul_div(i128::MAX, 1, 1, Rounding::Down), Some(i128::MAX));
+        assert_eq!(mul_div(i128::MAX, 2, 1, Rounding::Down), None);
+        assert_eq!(mul_div(1, 1, 0, Rounding::Down), None);
+        assert_eq!(mul_div(-1, 1, 1, Rounding::Down), None);
+    }
+
+    #[test]
+    fn prop_apply_bps_never_exceeds_amount_and_rounding_differs_by_at_most_one() {
+        for amount in sample_amounts() {
+            for bps in SAMPLE_BPS {
+                let down = apply_bps(amount, bps, Rounding::Down).unwrap();
+                let up = apply_bps(amount, bps, Rounding::Up).unwrap();
+                assert!(down >= 0 && down <= amount, "{amount} @ {bps}");
+                assert!(up <= amount, "{amount} @ {bps}");
+                assert!(up - down <= 1, "{amount} @ {bps}");
+            }
+        }
+    }
+
+    #[test]
+    fn prop_apply_bps_is_monotone_in_rate() {
+        for amount in sample_amounts() {
+            let mut previous = 0;
+            for bps in (0..=BPS_DENOMINATOR).step_by(250) {
+                let fee = apply_bps(amount, bps, Rounding::Down).unwrap();
+                assert!(fee >= previous, "{amount} @ {bps}");
+                previous = fee;
+            }
+            assert_eq!(previous, amount);
+        }
+    }
+
+    #[test]
+    fn prop_split_bps_conserves_value() {
+        for amount in sample_amounts() {
+            for bps in SAMPLE_BPS {
+                let (part, rest) = split_bps(amount, bps).unwrap();
+                assert_eq!(part + rest, amount, "{amount} @ {bps}");
+                assert!(part >= 0 && rest >= 0);
+            }
+        }
+    }
+
+    #[test]
+    fn prop_mul_div_agrees_with_wide_arithmetic_when_product_fits() {
+        for value in sample_amounts() {
+            for numerator in [0i128, 1, 3, 10_000, 1 << 40] {
+                for denominator in [1i128, 3, 7, 10_000, 1 << 50] {
+                    let Some(product) = value.checked_mul(numerator) else {
+                        continue;
+                    };
+                    let expected_down = product / denominator;
+                    let expected_up = expected_down + i128::from(product % denominator != 0);
+                    assert_eq!(
+                        mul_div(value, numerator, denominator, Rounding::Down),
+                        Some(expected_down)
+                    );
+                    assert_eq!(
+                        mul_div(value, numerator, denominator, Rounding::Up),
+                        Some(expected_up)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rejects_invalid_inputs() {
+        assert_eq!(apply_bps(100, BPS_DENOMINATOR + 1, Rounding::Down), None);
+        assert_eq!(add(i128::MAX, 1), None);
+        assert_eq!(add(-1, 1), None);
+        assert_eq!(sub(1, 2), None);
+        assert_eq!(sub(5, 2), Some(3));
+    }
+}
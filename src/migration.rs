@@ -0,0 +1,212 @@
+//! Moving live vaults to a newer contract instance.
+//!
+//! When a new major version is deployed, creators move their vaults instead of cancelling and
+//! re-creating them. The creator calls `import_vault(source, vault_id)` on the new instance,
+//! which calls `export_vault(vault_id, to)` on the old one in the same transaction:
+//!
+//! 1. The old instance checks the vault can move, transfers its escrowed stake to the new
+//!    instance, marks the vault `Cancelled` (its settlement receipt names the new instance) and
+//!    returns a [`VaultMigration`] describing it.
+//! 2. The new instance checks that exactly the stake arrived and re-creates the vault from the
+//!    payload under a new id.
+//!
+//! The payload is authenticated by the call itself: `export_vault` only pays out to the
+//! instance that invoked it and authorized the call, and `import_vault` trusts the payload only
+//! after seeing the stake in its own balance. Either step failing reverts both.
+//!
+//! Vaults with a process in flight (dispute, pause, freeze, bonus pool, amendment, recovery or
+//! abandonment notice) cannot move until it is resolved; the rest of their configuration
+//! travels with the payload.
+
+use soroban_sdk::{contracttype, Address, BytesN, Env, Vec};
+
+use crate::{
+    attestation::BlsCommittee, keepers, recovery::GuardianSet, refund::RefundSchedule,
+    zk::Groth16VerifyingKey, DataKey, RetryPolicy, VaultParams,
+};
+
+/// A vault as exported by `export_vault`, everything `import_vault` needs to re-create it.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VaultMigration {
+    /// Instance the vault was exported from.
+    pub source: Address,
+    pub source_vault_id: u32,
+    /// Creation parameters; `amount` is the stake transferred with the export.
+    pub params: VaultParams,
+    pub milestone_validated: bool,
+    pub exported_at: u64,
+    /// Optional configuration the vault was created with.
+    pub entries: Vec<MigratedEntry>,
+}
+
+/// One piece of optional vault configuration carried by a [`VaultMigration`].
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MigratedEntry {
+    VerifyingKey(Groth16VerifyingKey),
+    Committee(BlsCommittee),
+    FailureCommitment(BytesN<32>),
+    RefundSchedule(RefundSchedule),
+    Retries(RetryPolicy),
+    Guardians(GuardianSet),
+    /// The creator's keeper bounty rate.
+    KeeperBounty(u32),
+}
+
+/// `true` if `vault_id` has an unresolved process that cannot be carried to another instance.
+pub fn has_process_in_flight(env: &Env, vault_id: u32) -> bool {
+    let storage = env.storage().persistent();
+    [
+        DataKey::Bonus(vault_id),
+        DataKey::Amendment(vault_id),
+        DataKey::Recovery(vault_id),
+        DataKey::Abandonment(vault_id),
+    ]
+    .iter()
+    .any(|key| storage.has(key))
+}
+
+/// Build the payload for `vault_id` from its stored configuration.
+pub fn payload(
+    env: &Env,
+    vault_id: u32,
+    params: VaultParams,
+    milestone_validated: bool,
+) -> VaultMigration {
+    let storage = env.storage().persistent();
+    let mut entries = Vec::new(env);
+    if let Some(key) = storage.get(&DataKey::ZkVerifyingKey(vault_id)) {
+        entries.push_back(MigratedEntry::VerifyingKey(key));
+    }
+    if let Some(committee) = storage.get(&DataKey::Committee(vault_id)) {
+        entries.push_back(MigratedEntry::Committee(committee));
+    }
+    if let Some(commitment) = storage.get(&DataKey::FailureCommitment(vault_id)) {
+        entries.push_back(MigratedEntry::FailureCommitment(commitment));
+    }
+    if let Some(schedule) = storage.get(&DataKey::RefundSchedule(vault_id)) {
+        entries.push_back(MigratedEntry::RefundSchedule(schedule));
+    }
+    if let Some(policy) = storage.get(&DataKey::Retries(vault_id)) {
+        entries.push_back(MigratedEntry::Retries(policy));
+    }
+    if let Some(guardians) = storage.get(&DataKey::Guardians(vault_id)) {
+        entries.push_back(MigratedEntry::Guardians(guardians));
+    }
+    if let Some(bps) = keepers::vault_bps(env, vault_id) {
+        entries.push_back(MigratedEntry::KeeperBounty(bps));
+    }
+    VaultMigration {
+        source: env.current_contract_address(),
+        source_vault_id: vault_id,
+        params,
+        milestone_validated,
+        exported_at: env.ledger().timestamp(),
+        entries,
+    }
+}
+
+/// Store the configuration carried by `migration` for the re-created `vault_id`.
+pub fn restore(env: &Env, vault_id: u32, migration: &VaultMigration) {
+    let storage = env.storage().persistent();
+    for entry in migration.entries.iter() {
+        match entry {
+            MigratedEntry::VerifyingKey(key) => {
+                storage.set(&DataKey::ZkVerifyingKey(vault_id), &key)
+            }
+            MigratedEntry::Committee(committee) => {
+                storage.set(&DataKey::Committee(vault_id), &committee)
+            }
+            MigratedEntry::FailureCommitment(commitment) => {
+                storage.set(&DataKey::FailureCommitment(vault_id), &commitment)
+            }
+            MigratedEntry::RefundSchedule(schedule) => {
+                storage.set(&DataKey::RefundSchedule(vault_id), &schedule)
+            }
+            MigratedEntry::Retries(policy) => storage.set(&DataKey::Retries(vault_id), &policy),
+            MigratedEntry::Guardians(guardians) => {
+                storage.set(&DataKey::Guardians(vault_id), &guardians)
+            }
+            MigratedEntry::KeeperBounty(bps) => keepers::set_vault_bps(env, vault_id, Some(bps)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::{register_vault_contract, VaultFixture};
+    use crate::{DisciplrVaultClient, Error, SettlementOutcome, VaultStatus};
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger},
+        token::TokenClient,
+    };
+
+    #[test]
+    fn test_import_moves_vault_and_stake_to_new_instance() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        // The creator's authorization of the export sits below the import call.
+        env.mock_all_auths_allowing_non_root_auth();
+        let old = fixture.client();
+        let new_id = register_vault_contract(&env);
+        let new = DisciplrVaultClient::new(&env, &new_id);
+        let token = TokenClient::new(&env, &fixture.token);
+        let vault_id = fixture.create_vault();
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        old.validate_milestone(&vault_id, &None);
+
+        let imported = new.import_vault(&fixture.contract_id, &vault_id);
+        assert_eq!(token.balance(&fixture.contract_id), 0);
+        assert_eq!(token.balance(&new_id), fixture.amount);
+        let exported = old.get_vault_state(&vault_id).unwrap();
+        assert_eq!(exported.status, VaultStatus::Cancelled);
+        let receipt = old.get_settlement(&vault_id).unwrap();
+        assert_eq!(receipt.outcome, SettlementOutcome::Cancel);
+        assert_eq!(receipt.actor, Some(new_id.clone()));
+
+        let vault = new.get_vault_state(&imported).unwrap();
+        assert_eq!(vault.status, VaultStatus::Active);
+        assert!(vault.milestone_validated);
+        assert_eq!(vault.creator, fixture.creator);
+        assert_eq!(vault.amount, fixture.amount);
+        assert_eq!(vault.end_timestamp, fixture.end_timestamp);
+        assert_eq!(
+            new.get_escrow_breakdown(&fixture.token).active_escrow,
+            fixture.amount
+        );
+
+        new.release_funds(&imported, &fixture.token, &None);
+        assert_eq!(token.balance(&fixture.success_destination), fixture.amount);
+    }
+
+    #[test]
+    fn test_import_rejects_busy_and_settled_vaults() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        env.mock_all_auths_allowing_non_root_auth();
+        let old = fixture.client();
+        let new = DisciplrVaultClient::new(&env, &register_vault_contract(&env));
+        let vault_id = fixture.create_vault();
+
+        let funder = Address::generate(&env);
+        crate::testutils::mint(&env, &fixture.token, &funder, 100);
+        old.fund_bonus(&vault_id, &funder, &100, &10);
+        assert_eq!(
+            new.try_import_vault(&fixture.contract_id, &vault_id),
+            Err(Ok(Error::InvalidStatus))
+        );
+
+        let settled = fixture.create_vault();
+        old.cancel_vault(&settled, &fixture.token, &None);
+        assert_eq!(
+            new.try_import_vault(&fixture.contract_id, &settled),
+            Err(Ok(Error::VaultNotActive))
+        );
+        assert_eq!(
+            new.try_import_vault(&fixture.contract_id, &99),
+            Err(Ok(Error::VaultNotFound))
+        );
+    }
+}
@@ -0,0 +1,106 @@
+//! Canonical milestone encoding and hashing.
+//!
+//! Frontends that build `milestone_hash` from a human-readable description must agree byte for
+//! byte, or a later preimage reveal will not match. The encoding is:
+//!
+//! ```text
+//! "disciplr:milestone:v1"          domain tag (21 bytes, ASCII)
+//! u32 big-endian                   length of description in bytes
+//! description                      UTF-8, no normalization or trimming
+//! u64 big-endian                   deadline (vault end_timestamp, seconds)
+//! creator XDR                      ScVal::Address encoding of the creator
+//! ```
+//!
+//! `milestone_hash = sha256(encoding)`. Off-chain clients can reproduce this with any SHA-256
+//! implementation and the Stellar XDR library, or simulate `compute_milestone_hash`.
+
+use soroban_sdk::{xdr::ToXdr, Address, Bytes, BytesN, Env};
+
+/// Domain separation tag prefixed to every encoded milestone.
+pub const MILESTONE_HASH_DOMAIN: &[u8] = b"disciplr:milestone:v1";
+
+/// Canonical byte encoding of a milestone (see module docs).
+pub fn encode_milestone(env: &Env, description: &Bytes, deadline: u64, creator: &Address) -> Bytes {
+    let mut encoded = Bytes::from_slice(env, MILESTONE_HASH_DOMAIN);
+    encoded.extend_from_array(&description.len().to_be_bytes());
+    encoded.append(description);
+    encoded.extend_from_array(&deadline.to_be_bytes());
+    encoded.append(&creator.clone().to_xdr(env));
+    encoded
+}
+
+/// `sha256(encode_milestone(..))`.
+pub fn milestone_hash(
+    env: &Env,
+    description: &Bytes,
+    deadline: u64,
+    creator: &Address,
+) -> BytesN<32> {
+    env.crypto()
+        .sha256(&encode_milestone(env, description, deadline, creator))
+        .to_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use soroban_sdk::testutils::Address as _;
+
+    #[test]
+    fn test_encoding_layout() {
+        let env = Env::default();
+        let creator = Address::generate(&env);
+        let description = Bytes::from_slice(&env, b"run 5k");
+
+        let encoded = encode_milestone(&env, &description, 1_000, &creator);
+
+        let mut expected = Bytes::from_slice(&env, MILESTONE_HASH_DOMAIN);
+        expected.extend_from_array(&[0, 0, 0, 6]);
+        expected.extend_from_array(b"run 5k");
+        expected.extend_from_array(&1_000u64.to_be_bytes());
+        expected.append(&creator.clone().to_xdr(&env));
+        assert_eq!(encoded, expected);
+    }
+
+    #[test]
+    fn test_hash_binds_every_field() {
+        let env = Env::default();
+        let creator = Address::generate(&env);
+        let other = Address::generate(&env);
+        let text = Bytes::from_slice(&env, b"ship v1");
+
+        let base = milestone_hash(&env, &text, 1_000, &creator);
+        assert_eq!(base, milestone_hash(&env, &text, 1_000, &creator));
+        assert_ne!(
+            base,
+            milestone_hash(&env, &Bytes::from_slice(&env, b"ship v2"), 1_000, &creator)
+        );
+        assert_ne!(base, milestone_hash(&env, &text, 1_001, &creator));
+        assert_ne!(base, milestone_hash(&env, &text, 1_000, &other));
+    }
+
+    #[test]
+    fn test_contract_views_match_helper() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let text = Bytes::from_slice(&env, b"read 12 books");
+        let hash = milestone_hash(&env, &text, fixture.end_timestamp, &fixture.creator);
+        assert_eq!(
+            client.compute_milestone_hash(&text, &fixture.end_timestamp, &fixture.creator),
+            hash
+        );
+
+        let vault_id = fixture.create_vault_with(&crate::VaultParams {
+            milestone_hash: hash,
+            ..fixture.params()
+        });
+        assert!(client.verify_milestone_preimage(&vault_id, &text));
+        assert!(!client.verify_milestone_preimage(&vault_id, &Bytes::from_slice(&env, b"x")));
+        assert_eq!(
+            client.try_verify_milestone_preimage(&99, &text),
+            Err(Ok(crate::Error::VaultNotFound))
+        );
+    }
+}
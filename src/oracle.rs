@@ -0,0 +1,207 @@
+//! Quantified goals fed by a signed data oracle (fitness trackers and the like).
+//!
+//! A goal vault commits to reaching a numeric `target` (steps, workouts, minutes) measured by a
+//! trusted data signer instead of a verifier's judgment. The creator fixes the signer's ed25519
+//! public key and the target at creation with `create_vault_with_goal`. The signer then
+//! periodically signs [`Reading`]s of progress since its previous reading, and anyone submits
+//! them with `submit_reading`:
+//!
+//! - each reading is signed over `reading_message` (see [`reading_message`]), which binds the
+//!   contract, the vault and the reading, so it cannot be replayed elsewhere;
+//! - readings carry strictly increasing `seq` numbers, so none is counted twice;
+//! - values accumulate into `progress`, announced with `("goal_progress", vault_id)`;
+//! - the reading that brings `progress` to `target` validates the milestone and releases the
+//!   stake in the same call, completing the vault.
+//!
+//! Readings follow the same timing rules as validation: not before `start_timestamp`, not
+//! while paused, not at or after `end_timestamp`. A goal vault is never validated by hand; if
+//! the target is missed it is redirected as usual.
+
+use soroban_sdk::{contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env};
+
+/// Domain tag prefixed to every reading message.
+pub const READING_DOMAIN: &[u8] = b"disciplr:reading:v1";
+
+/// Storage key of goal vaults.
+#[contracttype]
+#[derive(Clone)]
+pub enum GoalKey {
+    /// Vault id -> its [`QuantifiedGoal`].
+    Goal(u32),
+}
+
+/// The target of a goal vault and the progress counted so far.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QuantifiedGoal {
+    /// Ed25519 public key of the data signer.
+    pub signer: BytesN<32>,
+    pub target: u64,
+    pub progress: u64,
+    /// Lowest `seq` the next reading may carry.
+    pub next_seq: u64,
+}
+
+/// Progress measured by the data signer since its previous reading.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Reading {
+    pub seq: u64,
+    pub value: u64,
+}
+
+/// Goal of `vault_id`, if it is a goal vault.
+pub fn get(env: &Env, vault_id: u32) -> Option<QuantifiedGoal> {
+    env.storage().persistent().get(&GoalKey::Goal(vault_id))
+}
+
+/// Store the goal of `vault_id`.
+pub fn save(env: &Env, vault_id: u32, goal: &QuantifiedGoal) {
+    env.storage()
+        .persistent()
+        .set(&GoalKey::Goal(vault_id), goal);
+}
+
+/// Drop the goal of an archived vault.
+pub fn remove(env: &Env, vault_id: u32) {
+    env.storage().persistent().remove(&GoalKey::Goal(vault_id));
+}
+
+/// Message the data signer signs for `reading` of `vault_id`:
+/// `READING_DOMAIN || contract_xdr || u32_be(vault_id) || u64_be(seq) || u64_be(value)`.
+pub fn reading_message(env: &Env, contract: &Address, vault_id: u32, reading: &Reading) -> Bytes {
+    let mut message = Bytes::from_slice(env, READING_DOMAIN);
+    message.append(&contract.clone().to_xdr(env));
+    message.extend_from_array(&vault_id.to_be_bytes());
+    message.extend_from_array(&reading.seq.to_be_bytes());
+    message.extend_from_array(&reading.value.to_be_bytes());
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cosign::CosignPolicy;
+    use crate::testutils::VaultFixture;
+    use crate::{Error, VaultStatus};
+    use ed25519_dalek::{Signer, SigningKey};
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    fn sign(env: &Env, key: &SigningKey, message: &Bytes) -> BytesN<64> {
+        let mut buffer = [0u8; 256];
+        let len = message.len() as usize;
+        message.copy_into_slice(&mut buffer[..len]);
+        BytesN::from_array(env, &key.sign(&buffer[..len]).to_bytes())
+    }
+
+    fn goal_vault(fixture: &VaultFixture, key: &SigningKey, target: u64) -> u32 {
+        let signer = BytesN::from_array(&fixture.env, &key.verifying_key().to_bytes());
+        crate::testutils::mint(
+            &fixture.env,
+            &fixture.token,
+            &fixture.creator,
+            fixture.amount,
+        );
+        fixture
+            .client()
+            .create_vault_with_goal(&fixture.params(), &signer, &target)
+    }
+
+    #[test]
+    fn test_signed_readings_complete_vault_at_target() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let key = SigningKey::from_bytes(&[3; 32]);
+        let vault_id = goal_vault(&fixture, &key, 10_000);
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        assert_eq!(
+            client.try_validate_milestone(&vault_id, &None),
+            Err(Ok(Error::NotAuthorized))
+        );
+
+        let first = Reading {
+            seq: 0,
+            value: 6_000,
+        };
+        let signature = sign(&env, &key, &client.reading_message(&vault_id, &first));
+        assert_eq!(client.submit_reading(&vault_id, &first, &signature), 6_000);
+        // The same reading cannot be counted twice.
+        assert_eq!(
+            client.try_submit_reading(&vault_id, &first, &signature),
+            Err(Ok(Error::InvalidAttestation))
+        );
+
+        let second = Reading {
+            seq: 1,
+            value: 4_500,
+        };
+        let signature = sign(&env, &key, &client.reading_message(&vault_id, &second));
+        assert_eq!(
+            client.submit_reading(&vault_id, &second, &signature),
+            10_500
+        );
+        let vault = client.get_vault_state(&vault_id).unwrap();
+        assert_eq!(vault.status, VaultStatus::Completed);
+        assert_eq!(client.get_goal(&vault_id).unwrap().progress, 10_500);
+    }
+
+    #[test]
+    fn test_reading_signed_by_another_key_is_rejected() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let key = SigningKey::from_bytes(&[3; 32]);
+        assert_eq!(
+            client.try_create_vault_with_goal(
+                &fixture.params(),
+                &BytesN::from_array(&env, &key.verifying_key().to_bytes()),
+                &0
+            ),
+            Err(Ok(Error::InvalidAmount))
+        );
+        let vault_id = goal_vault(&fixture, &key, 100);
+        env.ledger().set_timestamp(fixture.start_timestamp);
+
+        let reading = Reading { seq: 0, value: 100 };
+        let forged = sign(
+            &env,
+            &SigningKey::from_bytes(&[4; 32]),
+            &client.reading_message(&vault_id, &reading),
+        );
+        assert!(client
+            .try_submit_reading(&vault_id, &reading, &forged)
+            .is_err());
+        assert_eq!(client.get_goal(&vault_id).unwrap().progress, 0);
+    }
+
+    #[test]
+    fn test_target_reading_only_validates_while_cosign_is_pending() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        client.initialize(&Address::generate(&env));
+        client.set_cosign_policy(&Some(CosignPolicy {
+            threshold: fixture.amount,
+            cosigner: Address::generate(&env),
+            delay_secs: 600,
+        }));
+        let key = SigningKey::from_bytes(&[3; 32]);
+        let vault_id = goal_vault(&fixture, &key, 100);
+        env.ledger().set_timestamp(fixture.start_timestamp);
+
+        let reading = Reading { seq: 0, value: 100 };
+        let signature = sign(&env, &key, &client.reading_message(&vault_id, &reading));
+        assert_eq!(client.submit_reading(&vault_id, &reading, &signature), 100);
+        let vault = client.get_vault_state(&vault_id).unwrap();
+        assert!(vault.milestone_validated);
+        assert_eq!(vault.status, VaultStatus::Active);
+
+        client.cosign_settlement(&vault_id);
+        client.release_funds(&vault_id, &fixture.token, &None);
+        assert_eq!(
+            client.get_vault_state(&vault_id).unwrap().status,
+            VaultStatus::Completed
+        );
+    }
+}
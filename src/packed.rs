@@ -0,0 +1,301 @@
+//! Compact on-ledger encoding of vault records.
+//!
+//! `ProductivityVault` is the stable external type returned by views and events. Storage holds a
+//! `PackedVault` instead:
+//!
+//! - `status`, `milestone_validated`, `is_public` and `arbiter_fee_bps` share one `u32` (`flags`).
+//! - The token is a small id into a shared token table rather than a full `Address` per vault.
+//! - Fields that only matter for arbitrated vaults (`arbiter`, `dispute_fee_reserved`) live in a
+//!   separate `VaultExtras` entry, written only when one of them is set.
+//!
+//! With tombstone mode on, a settled vault's record is replaced by a `VaultTombstone` (status and
+//! settlement time only); its full final state survives only in the `vault_archived` event.
+//!
+//! All reads and writes of `DataKey::Vault` go through `load_vault` / `save_vault`.
+
+use soroban_sdk::{contracttype, Address, BytesN, Env};
+
+use crate::{DataKey, Error, ProductivityVault, VaultStatus, VaultTombstone};
+
+const STATUS_MASK: u32 = 0b111;
+const VALIDATED_BIT: u32 = 1 << 3;
+const PUBLIC_BIT: u32 = 1 << 4;
+const EXTRAS_BIT: u32 = 1 << 5;
+const FEE_SHIFT: u32 = 16;
+
+/// Stored form of a vault (see module docs).
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PackedVault {
+    pub creator: Address,
+    pub amount: i128,
+    pub start_timestamp: u64,
+    pub end_timestamp: u64,
+    pub milestone_hash: BytesN<32>,
+    pub verifier: Option<Address>,
+    pub success_destination: Address,
+    pub failure_destination: Address,
+    /// Id in the shared token table (`DataKey::TokenRef` / `DataKey::TokenById`).
+    pub token_ref: u32,
+    /// Status (bits 0-2), validated (bit 3), public (bit 4), has extras (bit 5),
+    /// arbiter fee bps (bits 16-31).
+    pub flags: u32,
+}
+
+/// Rarely-set vault fields, stored under `DataKey::VaultExtras` only when non-default.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VaultExtras {
+    pub arbiter: Option<Address>,
+    pub dispute_fee_reserved: i128,
+}
+
+/// Pack status, both booleans and the arbiter fee into one word.
+///
+/// `arbiter_fee_bps` must fit in 16 bits (it is capped at `BPS_DENOMINATOR`).
+pub fn pack_flags(
+    status: VaultStatus,
+    milestone_validated: bool,
+    is_public: bool,
+    arbiter_fee_bps: u32,
+) -> u32 {
+    let mut flags = status as u32 | (arbiter_fee_bps << FEE_SHIFT);
+    if milestone_validated {
+        flags |= VALIDATED_BIT;
+    }
+    if is_public {
+        flags |= PUBLIC_BIT;
+    }
+    flags
+}
+
+/// Status stored in `flags`.
+pub fn status_of(flags: u32) -> VaultStatus {
+    match flags & STATUS_MASK {
+        0 => VaultStatus::Active,
+        1 => VaultStatus::Completed,
+        2 => VaultStatus::Failed,
+        3 => VaultStatus::Cancelled,
+        // Only `pack_flags` writes this field, so the remaining value is always 4.
+        _ => VaultStatus::Disputed,
+    }
+}
+
+/// Id of `token` in the shared token table, assigning the next id on first use.
+pub fn token_ref(env: &Env, token: &Address) -> u32 {
+    let key = DataKey::TokenRef(token.clone());
+    if let Some(id) = env.storage().instance().get(&key) {
+        return id;
+    }
+    let id: u32 = env
+        .storage()
+        .instance()
+        .get(&DataKey::TokenCount)
+        .unwrap_or(0);
+    env.storage().instance().set(&key, &id);
+    env.storage().instance().set(&DataKey::TokenById(id), token);
+    env.storage()
+        .instance()
+        .set(&DataKey::TokenCount, &(id + 1));
+    id
+}
+
+/// `true` if a vault (or its tombstone) is stored under `vault_id`.
+pub fn vault_exists(env: &Env, vault_id: u32) -> bool {
+    env.storage().instance().has(&DataKey::Vault(vault_id))
+        || env
+            .storage()
+            .persistent()
+            .has(&DataKey::Tombstone(vault_id))
+}
+
+/// Load the vault stored under `vault_id`.
+///
+/// Returns `Error::VaultNotActive` for a tombstoned vault and `Error::VaultNotFound` if the id
+/// was never used.
+pub fn require_vault(env: &Env, vault_id: u32) -> Result<ProductivityVault, Error> {
+    load_vault(env, vault_id).ok_or_else(|| {
+        if load_tombstone(env, vault_id).is_some() {
+            Error::VaultNotActive
+        } else {
+            Error::VaultNotFound
+        }
+    })
+}
+
+/// Load and expand the vault stored under `vault_id`.
+pub fn load_vault(env: &Env, vault_id: u32) -> Option<ProductivityVault> {
+    let packed: PackedVault = env.storage().instance().get(&DataKey::Vault(vault_id))?;
+    let extras: Option<VaultExtras> = if packed.flags & EXTRAS_BIT != 0 {
+        env.storage()
+            .instance()
+            .get(&DataKey::VaultExtras(vault_id))
+    } else {
+        None
+    };
+    let (arbiter, dispute_fee_reserved) = match extras {
+        Some(extras) => (extras.arbiter, extras.dispute_fee_reserved),
+        None => (None, 0),
+    };
+    let token = env
+        .storage()
+        .instance()
+        .get(&DataKey::TokenById(packed.token_ref))?;
+
+    Some(ProductivityVault {
+        creator: packed.creator,
+        amount: packed.amount,
+        start_timestamp: packed.start_timestamp,
+        end_timestamp: packed.end_timestamp,
+        milestone_hash: packed.milestone_hash,
+        verifier: packed.verifier,
+        success_destination: packed.success_destination,
+        failure_destination: packed.failure_destination,
+        status: status_of(packed.flags),
+        milestone_validated: packed.flags & VALIDATED_BIT != 0,
+        is_public: packed.flags & PUBLIC_BIT != 0,
+        arbiter,
+        arbiter_fee_bps: packed.flags >> FEE_SHIFT,
+        dispute_fee_reserved,
+        token,
+    })
+}
+
+/// Pack and store `vault` under `vault_id`, writing or clearing its extras entry as needed.
+pub fn save_vault(env: &Env, vault_id: u32, vault: &ProductivityVault) {
+    let mut flags = pack_flags(
+        vault.status,
+        vault.milestone_validated,
+        vault.is_public,
+        vault.arbiter_fee_bps,
+    );
+    let extras_key = DataKey::VaultExtras(vault_id);
+    if vault.arbiter.is_some() || vault.dispute_fee_reserved != 0 {
+        flags |= EXTRAS_BIT;
+        let extras = VaultExtras {
+            arbiter: vault.arbiter.clone(),
+            dispute_fee_reserved: vault.dispute_fee_reserved,
+        };
+        env.storage().instance().set(&extras_key, &extras);
+    } else {
+        env.storage().instance().remove(&extras_key);
+    }
+
+    let packed = PackedVault {
+        creator: vault.creator.clone(),
+        amount: vault.amount,
+        start_timestamp: vault.start_timestamp,
+        end_timestamp: vault.end_timestamp,
+        milestone_hash: vault.milestone_hash.clone(),
+        verifier: vault.verifier.clone(),
+        success_destination: vault.success_destination.clone(),
+        failure_destination: vault.failure_destination.clone(),
+        token_ref: token_ref(env, &vault.token),
+        flags,
+    };
+    env.storage()
+        .instance()
+        .set(&DataKey::Vault(vault_id), &packed);
+}
+
+/// Drop the full record of a settled vault and keep only `tombstone`.
+pub fn tombstone_vault(env: &Env, vault_id: u32, tombstone: &VaultTombstone) {
+    env.storage().instance().remove(&DataKey::Vault(vault_id));
+    env.storage()
+        .instance()
+        .remove(&DataKey::VaultExtras(vault_id));
+    env.storage()
+        .persistent()
+        .set(&DataKey::Tombstone(vault_id), tombstone);
+}
+
+/// Tombstone left by a settled vault, if it was archived.
+pub fn load_tombstone(env: &Env, vault_id: u32) -> Option<VaultTombstone> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Tombstone(vault_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use soroban_sdk::{testutils::Address as _, xdr::ToXdr};
+
+    #[test]
+    fn test_flags_round_trip() {
+        use VaultStatus::*;
+        for status in [Active, Completed, Failed, Cancelled, Disputed] {
+            for (validated, public) in [(false, false), (true, false), (false, true), (true, true)]
+            {
+                let flags = pack_flags(status, validated, public, crate::BPS_DENOMINATOR);
+                assert_eq!(status_of(flags), status);
+                assert_eq!(flags & VALIDATED_BIT != 0, validated);
+                assert_eq!(flags & PUBLIC_BIT != 0, public);
+                assert_eq!(flags >> FEE_SHIFT, crate::BPS_DENOMINATOR);
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip_with_and_without_extras() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let mut vault = ProductivityVault {
+            creator: fixture.creator.clone(),
+            amount: 42,
+            start_timestamp: 1,
+            end_timestamp: 2,
+            milestone_hash: BytesN::from_array(&env, &[7u8; 32]),
+            verifier: Some(fixture.verifier.clone()),
+            success_destination: fixture.success_destination.clone(),
+            failure_destination: fixture.failure_destination.clone(),
+            status: VaultStatus::Disputed,
+            milestone_validated: true,
+            is_public: true,
+            arbiter: Some(Address::generate(&env)),
+            arbiter_fee_bps: 250,
+            dispute_fee_reserved: 1,
+            token: fixture.token.clone(),
+        };
+
+        env.as_contract(&fixture.contract_id, || {
+            save_vault(&env, 5, &vault);
+            assert_eq!(load_vault(&env, 5), Some(vault.clone()));
+
+            // Clearing the optional fields drops the extras entry.
+            vault.arbiter = None;
+            vault.dispute_fee_reserved = 0;
+            save_vault(&env, 5, &vault);
+            assert!(!env.storage().instance().has(&DataKey::VaultExtras(5)));
+            assert_eq!(load_vault(&env, 5), Some(vault.clone()));
+
+            assert_eq!(load_vault(&env, 6), None);
+        });
+    }
+
+    #[test]
+    fn test_packed_record_is_smaller_and_tokens_are_shared() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let first = fixture.create_vault();
+        let second = fixture.create_vault();
+
+        env.as_contract(&fixture.contract_id, || {
+            let packed: PackedVault = env
+                .storage()
+                .instance()
+                .get(&DataKey::Vault(first))
+                .unwrap();
+            let other: PackedVault = env
+                .storage()
+                .instance()
+                .get(&DataKey::Vault(second))
+                .unwrap();
+            assert_eq!(packed.token_ref, other.token_ref);
+
+            let full = load_vault(&env, first).unwrap();
+            assert!(packed.to_xdr(&env).len() < full.to_xdr(&env).len());
+        });
+    }
+}
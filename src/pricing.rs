@@ -0,0 +1,182 @@
+//! Pure fee, penalty, bounty and split arithmetic.
+//!
+//! Every amount the contract charges or splits off a stake is computed by one of these
+//! functions, from explicit rates only. Settlement paths call them with the rates in effect
+//! for a vault; `quote_fees` calls them with rates the caller supplies, so a wallet can show
+//! the exact numbers for a vault it has not created yet by simulating one read-only call.
+
+use soroban_sdk::contracttype;
+
+use crate::{
+    math::{self, Rounding},
+    Error, BPS_DENOMINATOR, SNOOZE_FEE_BPS,
+};
+
+/// Rates for a hypothetical vault, passed to `quote_fees`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QuoteOverrides {
+    /// Snooze fee rate; `None` quotes the base `SNOOZE_FEE_BPS` (before any fee override).
+    pub snooze_fee_bps: Option<u32>,
+    /// The chosen arbiter's fee rate.
+    pub arbiter_fee_bps: u32,
+    /// Keeper bounty rate: the protocol rate, or the creator's if higher.
+    pub keeper_bounty_bps: u32,
+    /// Most an unregistered keeper is paid; `None` for no cap.
+    pub unregistered_cap: Option<i128>,
+    /// Share of the stake refunded on cancellation (from the refund schedule at the planned
+    /// cancel time); `BPS_DENOMINATOR` for a full refund.
+    pub cancel_refund_bps: u32,
+}
+
+/// Amounts for a stake of `amount` under a [`QuoteOverrides`].
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeQuote {
+    pub amount: i128,
+    /// Fee for one snooze, paid by the creator on top of the stake.
+    pub snooze_fee: i128,
+    /// Arbiter fee reserved when a dispute opens, and what the ruling's destination gets.
+    pub arbiter_fee: i128,
+    pub dispute_payout: i128,
+    /// Bounty a registered keeper earns for a redirect, and what an unregistered one earns.
+    pub keeper_bounty: i128,
+    pub unregistered_bounty: i128,
+    /// What the failure destination gets from a keeper redirect.
+    pub redirect_payout: i128,
+    /// Creator refund and forfeit on cancellation.
+    pub cancel_refund: i128,
+    pub cancel_penalty: i128,
+}
+
+/// Snooze fee on a stake of `amount` (rounded up).
+pub fn snooze_fee(amount: i128, fee_bps: u32) -> Option<i128> {
+    math::apply_bps(amount, fee_bps, Rounding::Up)
+}
+
+/// Arbiter fee reserved from a stake of `amount` (rounded down).
+pub fn arbiter_fee(amount: i128, fee_bps: u32) -> Option<i128> {
+    math::apply_bps(amount, fee_bps, Rounding::Down)
+}
+
+/// Open verifier's fee on a stake of `amount` (rounded down).
+pub fn verifier_fee(amount: i128, fee_bps: u32) -> Option<i128> {
+    math::apply_bps(amount, fee_bps, Rounding::Down)
+}
+
+/// Keeper bounty on a stake of `amount` (rounded down), limited to `cap` if given.
+pub fn keeper_bounty(amount: i128, bounty_bps: u32, cap: Option<i128>) -> Option<i128> {
+    let full = math::apply_bps(amount, bounty_bps, Rounding::Down)?;
+    Some(cap.map_or(full, |cap| full.min(cap)))
+}
+
+/// `(refund, penalty)` of cancelling a stake of `amount` at `refund_bps`.
+pub fn cancel_split(amount: i128, refund_bps: u32) -> Option<(i128, i128)> {
+    math::split_bps(amount, refund_bps)
+}
+
+/// Quote every fee and split for a stake of `amount`. Rates above `BPS_DENOMINATOR` fail with
+/// `InvalidFee`, a non-positive amount or a negative cap with `InvalidAmount`.
+pub fn quote(amount: i128, overrides: &QuoteOverrides) -> Result<FeeQuote, Error> {
+    if amount <= 0 || overrides.unregistered_cap.is_some_and(|cap| cap < 0) {
+        return Err(Error::InvalidAmount);
+    }
+    let snooze_fee_bps = overrides.snooze_fee_bps.unwrap_or(SNOOZE_FEE_BPS);
+    if [
+        snooze_fee_bps,
+        overrides.arbiter_fee_bps,
+        overrides.keeper_bounty_bps,
+        overrides.cancel_refund_bps,
+    ]
+    .iter()
+    .any(|bps| *bps > BPS_DENOMINATOR)
+    {
+        return Err(Error::InvalidFee);
+    }
+
+    let overflow = Error::ArithmeticOverflow;
+    let arbiter_fee = arbiter_fee(amount, overrides.arbiter_fee_bps).ok_or(overflow)?;
+    let keeper_bounty = keeper_bounty(amount, overrides.keeper_bounty_bps, None).ok_or(overflow)?;
+    let (cancel_refund, cancel_penalty) =
+        cancel_split(amount, overrides.cancel_refund_bps).ok_or(overflow)?;
+    Ok(FeeQuote {
+        amount,
+        snooze_fee: snooze_fee(amount, snooze_fee_bps).ok_or(overflow)?,
+        arbiter_fee,
+        dispute_payout: math::sub(amount, arbiter_fee).ok_or(overflow)?,
+        keeper_bounty,
+        unregistered_bounty: overrides
+            .unregistered_cap
+            .map_or(keeper_bounty, |cap| keeper_bounty.min(cap)),
+        redirect_payout: math::sub(amount, keeper_bounty).ok_or(overflow)?,
+        cancel_refund,
+        cancel_penalty,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use soroban_sdk::Env;
+
+    fn overrides() -> QuoteOverrides {
+        QuoteOverrides {
+            snooze_fee_bps: None,
+            arbiter_fee_bps: 250,
+            keeper_bounty_bps: 100,
+            unregistered_cap: Some(500),
+            cancel_refund_bps: 7_500,
+        }
+    }
+
+    #[test]
+    fn test_quote_fees_matches_settlement_arithmetic() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+
+        let quote = client.quote_fees(&1_000_001, &overrides());
+        assert_eq!(quote.snooze_fee, 50_001);
+        assert_eq!(quote.arbiter_fee, 25_000);
+        assert_eq!(quote.dispute_payout, 975_001);
+        assert_eq!(quote.keeper_bounty, 10_000);
+        assert_eq!(quote.unregistered_bounty, 500);
+        assert_eq!(quote.redirect_payout, 990_001);
+        assert_eq!(quote.cancel_refund, 750_000);
+        assert_eq!(quote.cancel_penalty, 250_001);
+        // Quotes read no state: uninitialized instances answer too.
+        assert_eq!(client.get_admin(), None);
+    }
+
+    #[test]
+    fn test_quote_fees_rejects_bad_inputs() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        assert_eq!(
+            client.try_quote_fees(&0, &overrides()),
+            Err(Ok(Error::InvalidAmount))
+        );
+        assert_eq!(
+            client.try_quote_fees(
+                &100,
+                &QuoteOverrides {
+                    unregistered_cap: Some(-1),
+                    ..overrides()
+                }
+            ),
+            Err(Ok(Error::InvalidAmount))
+        );
+        assert_eq!(
+            client.try_quote_fees(
+                &100,
+                &QuoteOverrides {
+                    cancel_refund_bps: BPS_DENOMINATOR + 1,
+                    ..overrides()
+                }
+            ),
+            Err(Ok(Error::InvalidFee))
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! Social recovery of vault control.
+//!
+//! A creator may register `threshold`-of-N guardians at creation. If the creator loses their
+//! key, guardians propose a replacement creator address and approve it; once `threshold`
+//! guardians agree, the change becomes executable after `RECOVERY_DELAY_SECS`. The current
+//! creator can cancel a request at any point before it executes, so a live key always wins and
+//! no single guardian (or minority) can take over a vault.
+
+use soroban_sdk::{contracttype, Address, Vec};
+
+/// Delay between the threshold being reached and the recovery becoming executable (7 days).
+pub const RECOVERY_DELAY_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// Guardians registered for one vault.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GuardianSet {
+    pub guardians: Vec<Address>,
+    pub threshold: u32,
+}
+
+/// Pending reassignment of a vault's creator.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecoveryRequest {
+    pub new_creator: Address,
+    pub approvals: Vec<Address>,
+    /// Set once `approvals` reaches the threshold.
+    pub executable_at: Option<u64>,
+}
+
+/// `true` if `set` has 1..=`max_guardians` distinct guardians, none of them `creator`, and a
+/// threshold between 1 and the number of guardians.
+pub fn is_valid(set: &GuardianSet, creator: &Address, max_guardians: u32) -> bool {
+    let count = set.guardians.len();
+    if count == 0 || count > max_guardians || set.threshold == 0 || set.threshold > count {
+        return false;
+    }
+    for (i, guardian) in set.guardians.iter().enumerate() {
+        if guardian == *creator || set.guardians.first_index_of(&guardian) != Some(i as u32) {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use crate::Error;
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger},
+        vec, Env,
+    };
+
+    #[test]
+    fn test_guardian_set_validation() {
+        let env = Env::default();
+        let creator = Address::generate(&env);
+        let a = Address::generate(&env);
+        let b = Address::generate(&env);
+        let set = |guardians: Vec<Address>, threshold| GuardianSet {
+            guardians,
+            threshold,
+        };
+        assert!(is_valid(
+            &set(vec![&env, a.clone(), b.clone()], 2),
+            &creator,
+            10
+        ));
+        assert!(!is_valid(
+            &set(vec![&env, a.clone(), b.clone()], 3),
+            &creator,
+            10
+        ));
+        assert!(!is_valid(
+            &set(vec![&env, a.clone(), b.clone()], 0),
+            &creator,
+            10
+        ));
+        assert!(!is_valid(
+            &set(vec![&env, a.clone(), a.clone()], 1),
+            &creator,
+            10
+        ));
+        assert!(!is_valid(
+            &set(vec![&env, a.clone(), creator.clone()], 1),
+            &creator,
+            10
+        ));
+        assert!(!is_valid(&set(vec![&env, a, b], 1), &creator, 1));
+    }
+
+    #[test]
+    fn test_threshold_guardians_reassign_creator_after_delay() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let guardians = vec![
+            &env,
+            Address::generate(&env),
+            Address::generate(&env),
+            Address::generate(&env),
+        ];
+        let params = fixture.params();
+        crate::testutils::mint(&env, &fixture.token, &params.creator, params.amount);
+        let set = GuardianSet {
+            guardians: guardians.clone(),
+            threshold: 2,
+        };
+        let vault_id = client.create_vault_with_guardians(&params, &set);
+        let new_creator = Address::generate(&env);
+
+        client.propose_recovery(&vault_id, &guardians.get(0).unwrap(), &new_creator);
+        assert_eq!(
+            client.try_approve_recovery(&vault_id, &guardians.get(0).unwrap()),
+            Err(Ok(Error::NotAuthorized))
+        );
+        assert_eq!(
+            client.try_execute_recovery(&vault_id),
+            Err(Ok(Error::TimelockPending))
+        );
+        assert_eq!(
+            client.approve_recovery(&vault_id, &guardians.get(2).unwrap()),
+            2
+        );
+        let executable_at = client
+            .get_recovery(&vault_id)
+            .unwrap()
+            .executable_at
+            .unwrap();
+        assert_eq!(executable_at, RECOVERY_DELAY_SECS);
+
+        env.ledger().set_timestamp(executable_at - 1);
+        assert_eq!(
+            client.try_execute_recovery(&vault_id),
+            Err(Ok(Error::TimelockPending))
+        );
+        env.ledger().set_timestamp(executable_at);
+        client.execute_recovery(&vault_id);
+        assert_eq!(
+            client.get_vault_state(&vault_id).unwrap().creator,
+            new_creator
+        );
+        assert_eq!(client.get_recovery(&vault_id), None);
+    }
+
+    #[test]
+    fn test_creator_cancels_recovery_and_outsiders_cannot_propose() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let guardian = Address::generate(&env);
+        let params = fixture.params();
+        crate::testutils::mint(&env, &fixture.token, &params.creator, params.amount);
+        let set = GuardianSet {
+            guardians: vec![&env, guardian.clone()],
+            threshold: 1,
+        };
+        let vault_id = client.create_vault_with_guardians(&params, &set);
+        let outsider = Address::generate(&env);
+
+        assert_eq!(
+            client.try_propose_recovery(&vault_id, &outsider, &outsider),
+            Err(Ok(Error::NotAuthorized))
+        );
+        client.propose_recovery(&vault_id, &guardian, &outsider);
+        assert!(client
+            .get_recovery(&vault_id)
+            .unwrap()
+            .executable_at
+            .is_some());
+
+        client.cancel_recovery(&vault_id);
+        assert_eq!(
+            client.try_execute_recovery(&vault_id),
+            Err(Ok(Error::NoPendingRecovery))
+        );
+        assert_eq!(
+            client.get_vault_state(&vault_id).unwrap().creator,
+            fixture.creator
+        );
+
+        let no_guardians = fixture.create_vault();
+        assert_eq!(
+            client.try_propose_recovery(&no_guardians, &guardian, &outsider),
+            Err(Ok(Error::NotAuthorized))
+        );
+    }
+}
@@ -0,0 +1,225 @@
+//! Decaying refund schedules for cancellation.
+//!
+//! A vault created with a `RefundSchedule` refunds only part of its stake on `cancel_vault`; the
+//! rest is forfeited to the failure destination. The refund depends on how much of the vault's
+//! duration (`start_timestamp..end_timestamp`) has elapsed, measured in basis points, so a
+//! creator who quits early keeps more than one who quits at the last minute.
+
+use soroban_sdk::{contracttype, Vec};
+
+use crate::{
+    math::{self, Rounding},
+    BPS_DENOMINATOR,
+};
+
+/// Full refund until `full_until_bps` of the duration has elapsed, then a linear decay that
+/// reaches `floor_bps` at the deadline.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LinearRefund {
+    pub full_until_bps: u32,
+    pub floor_bps: u32,
+}
+
+/// From `from_elapsed_bps` of the duration onward, `refund_bps` of the stake is refunded.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RefundStep {
+    pub from_elapsed_bps: u32,
+    pub refund_bps: u32,
+}
+
+/// Refund curve stored per vault.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RefundSchedule {
+    Linear(LinearRefund),
+    /// Steps ordered by strictly increasing `from_elapsed_bps` with non-increasing `refund_bps`.
+    /// Before the first step the refund is 100%.
+    Steps(Vec<RefundStep>),
+}
+
+/// `true` if the schedule is well formed and has at most `max_steps` steps.
+pub fn is_valid(schedule: &RefundSchedule, max_steps: u32) -> bool {
+    match schedule {
+        RefundSchedule::Linear(linear) => {
+            linear.full_until_bps <= BPS_DENOMINATOR && linear.floor_bps <= BPS_DENOMINATOR
+        }
+        RefundSchedule::Steps(steps) => {
+            if steps.is_empty() || steps.len() > max_steps {
+                return false;
+            }
+            let mut previous: Option<RefundStep> = None;
+            for step in steps.iter() {
+                if step.from_elapsed_bps > BPS_DENOMINATOR || step.refund_bps > BPS_DENOMINATOR {
+                    return false;
+                }
+                if let Some(prev) = previous {
+                    if step.from_elapsed_bps <= prev.from_elapsed_bps
+                        || step.refund_bps > prev.refund_bps
+                    {
+                        return false;
+                    }
+                }
+                previous = Some(step);
+            }
+            true
+        }
+    }
+}
+
+/// Share of the vault's duration elapsed at `now`, in basis points (clamped to `0..=10_000`).
+pub fn elapsed_bps(start: u64, end: u64, now: u64) -> u32 {
+    if now <= start || end <= start {
+        return 0;
+    }
+    if now >= end {
+        return BPS_DENOMINATOR;
+    }
+    math::mul_div(
+        (now - start) as i128,
+        BPS_DENOMINATOR as i128,
+        (end - start) as i128,
+        Rounding::Down,
+    )
+    .map_or(BPS_DENOMINATOR, |bps| bps as u32)
+}
+
+/// Refund rate (bps of the stake) after `elapsed` bps of the duration.
+pub fn refund_bps(schedule: &RefundSchedule, elapsed: u32) -> u32 {
+    match schedule {
+        RefundSchedule::Linear(linear) => {
+            if elapsed <= linear.full_until_bps || linear.floor_bps >= BPS_DENOMINATOR {
+                return BPS_DENOMINATOR;
+            }
+            let decay_span = (BPS_DENOMINATOR - linear.full_until_bps) as i128;
+            let decayed = math::mul_div(
+                (BPS_DENOMINATOR - linear.floor_bps) as i128,
+                (elapsed - linear.full_until_bps) as i128,
+                decay_span,
+                Rounding::Up,
+            )
+            .unwrap_or(0) as u32;
+            BPS_DENOMINATOR - decayed
+        }
+        RefundSchedule::Steps(steps) => {
+            let mut rate = BPS_DENOMINATOR;
+            for step in steps.iter() {
+                if elapsed < step.from_elapsed_bps {
+                    break;
+                }
+                rate = step.refund_bps;
+            }
+            rate
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use crate::{DataKey, Error, VaultStatus};
+    use soroban_sdk::{testutils::Ledger, token::TokenClient, vec, Env};
+
+    fn linear(full_until_bps: u32, floor_bps: u32) -> RefundSchedule {
+        RefundSchedule::Linear(LinearRefund {
+            full_until_bps,
+            floor_bps,
+        })
+    }
+
+    #[test]
+    fn test_linear_curve() {
+        let schedule = linear(1_000, 2_000);
+        assert_eq!(refund_bps(&schedule, 0), 10_000);
+        assert_eq!(refund_bps(&schedule, 1_000), 10_000);
+        assert_eq!(refund_bps(&schedule, 5_500), 6_000);
+        assert_eq!(refund_bps(&schedule, 10_000), 2_000);
+        // Decay rounds against the creator, never above the straight line.
+        assert_eq!(refund_bps(&schedule, 1_001), 9_999);
+    }
+
+    #[test]
+    fn test_step_curve_and_validation() {
+        let env = Env::default();
+        let step = |from_elapsed_bps, refund_bps| RefundStep {
+            from_elapsed_bps,
+            refund_bps,
+        };
+        let schedule = RefundSchedule::Steps(vec![&env, step(2_500, 7_500), step(7_500, 2_500)]);
+        assert!(is_valid(&schedule, 10));
+        assert_eq!(refund_bps(&schedule, 2_499), 10_000);
+        assert_eq!(refund_bps(&schedule, 2_500), 7_500);
+        assert_eq!(refund_bps(&schedule, 9_999), 2_500);
+
+        assert!(!is_valid(&schedule, 1));
+        assert!(!is_valid(&RefundSchedule::Steps(vec![&env]), 10));
+        assert!(!is_valid(
+            &RefundSchedule::Steps(vec![&env, step(5_000, 1_000), step(6_000, 2_000)]),
+            10
+        ));
+        assert!(!is_valid(&linear(10_001, 0), 10));
+    }
+
+    #[test]
+    fn test_elapsed_bps_clamps() {
+        assert_eq!(elapsed_bps(100, 200, 50), 0);
+        assert_eq!(elapsed_bps(100, 200, 150), 5_000);
+        assert_eq!(elapsed_bps(100, 200, 250), 10_000);
+    }
+
+    #[test]
+    fn test_cancel_splits_stake_by_schedule() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let token = TokenClient::new(&env, &fixture.token);
+        let params = fixture.params();
+        crate::testutils::mint(&env, &fixture.token, &params.creator, params.amount);
+        let vault_id = client.create_vault_with_refund_curve(&params, &linear(1_000, 2_000));
+        assert_eq!(
+            client.get_refund_schedule(&vault_id),
+            Some(linear(1_000, 2_000))
+        );
+
+        // Halfway through the 900s duration: 1_000 + (10_000 - 1_000) / 2 elapsed.
+        env.ledger().set_timestamp(fixture.start_timestamp + 495);
+        let refund = client.preview_cancel_refund(&vault_id);
+        assert_eq!(refund, fixture.amount * 6_000 / 10_000);
+
+        client.cancel_vault(&vault_id, &fixture.token, &None);
+        assert_eq!(token.balance(&fixture.creator), refund);
+        assert_eq!(
+            token.balance(&fixture.failure_destination),
+            fixture.amount - refund
+        );
+        assert_eq!(
+            client.get_vault_state(&vault_id).unwrap().status,
+            VaultStatus::Cancelled
+        );
+
+        assert_eq!(
+            client.try_create_vault_with_refund_curve(&params, &linear(0, 10_001)),
+            Err(Ok(Error::InvalidSchedule))
+        );
+        env.as_contract(&fixture.contract_id, || {
+            assert!(env
+                .storage()
+                .persistent()
+                .has(&DataKey::RefundSchedule(vault_id)));
+        });
+    }
+
+    #[test]
+    fn test_vault_without_schedule_refunds_in_full() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let vault_id = fixture.create_vault();
+        env.ledger().set_timestamp(fixture.end_timestamp - 1);
+        assert_eq!(
+            fixture.client().preview_cancel_refund(&vault_id),
+            fixture.amount
+        );
+    }
+}
@@ -0,0 +1,193 @@
+//! Outcomes mirrored to a public results registry.
+//!
+//! Vault records can be archived (tombstone mode) and live in one instance each, so they are a
+//! poor source for a user's track record. The admin can register a separate results-registry
+//! contract with `set_results_registry`; every vault that settles is then recorded there as a
+//! compact [`OutcomeEntry`] (plus the vault's category, if tagged) through the
+//! [`ResultsRegistry`] interface, and reputation consumers can query a creator's history across
+//! instances and after archival.
+//!
+//! Entries disclose only an order of magnitude of the stake (`amount_band`), not the amount.
+//! Like the settle hook, the registry is notified, not consulted: a registry that fails cannot
+//! block a settlement, and a failed call emits `("hook_failed", vault_id)` with data
+//! `(registry, "record_outcome")` instead.
+
+use soroban_sdk::{contractclient, contracttype, Address, Env, Symbol};
+
+use crate::{categories, events, Category, ProductivityVault, VaultStatus};
+
+/// Storage key of the results registry.
+#[contracttype]
+#[derive(Clone)]
+pub enum ResultsKey {
+    /// The results registry contract (instance storage).
+    ResultsRegistry,
+}
+
+/// One settled vault, as recorded in the results registry.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OutcomeEntry {
+    /// Vault contract instance the vault lived in.
+    pub instance: Address,
+    pub vault_id: u32,
+    pub creator: Address,
+    /// `Completed`, `Failed` or `Cancelled`.
+    pub outcome: VaultStatus,
+    /// Stake order of magnitude: `10^amount_band <= amount < 10^(amount_band + 1)`, in the
+    /// token's smallest unit.
+    pub amount_band: u32,
+    pub settled_at: u64,
+}
+
+/// Entry points a results registry contract implements.
+#[contractclient(name = "ResultsRegistryClient")]
+pub trait ResultsRegistry {
+    /// Record a settled vault, tagged with `category` if it had one. The calling vault contract
+    /// is `entry.instance`.
+    fn record_outcome(env: Env, entry: OutcomeEntry, category: Option<Category>);
+}
+
+/// The configured results registry, if any.
+pub fn registry(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&ResultsKey::ResultsRegistry)
+}
+
+/// Order of magnitude of a positive `amount` (number of decimal digits minus one).
+pub fn amount_band(amount: i128) -> u32 {
+    if amount <= 0 {
+        return 0;
+    }
+    amount.ilog10()
+}
+
+/// Record the settled `vault` in the configured registry, if any.
+pub fn record(env: &Env, vault_id: u32, vault: &ProductivityVault) {
+    let Some(registry) = registry(env) else {
+        return;
+    };
+    let entry = OutcomeEntry {
+        instance: env.current_contract_address(),
+        vault_id,
+        creator: vault.creator.clone(),
+        outcome: vault.status,
+        amount_band: amount_band(vault.amount),
+        settled_at: env.ledger().timestamp(),
+    };
+    if ResultsRegistryClient::new(env, &registry)
+        .try_record_outcome(&entry, &categories::get(env, vault_id))
+        .is_err()
+    {
+        events::publish(
+            env,
+            "hook_failed",
+            vault_id,
+            (registry, Symbol::new(env, "record_outcome")),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::{mint, VaultFixture};
+    use soroban_sdk::{
+        contract, contractimpl,
+        testutils::{Address as _, Ledger},
+        Vec,
+    };
+
+    #[contract]
+    struct RecordingRegistry;
+
+    #[contractimpl]
+    impl RecordingRegistry {
+        pub fn record_outcome(env: Env, entry: OutcomeEntry, category: Option<Category>) {
+            let mut entries: Vec<(OutcomeEntry, Option<Category>)> = env
+                .storage()
+                .instance()
+                .get(&0u32)
+                .unwrap_or_else(|| Vec::new(&env));
+            entries.push_back((entry, category));
+            env.storage().instance().set(&0u32, &entries);
+        }
+
+        pub fn entries(env: Env) -> Vec<(OutcomeEntry, Option<Category>)> {
+            env.storage().instance().get(&0u32).unwrap()
+        }
+    }
+
+    mod broken {
+        use super::*;
+
+        #[contract]
+        pub struct BrokenRegistry;
+
+        #[contractimpl]
+        impl BrokenRegistry {
+            pub fn record_outcome(_env: Env, _entry: OutcomeEntry, _category: Option<Category>) {
+                panic!("registry is down");
+            }
+        }
+    }
+
+    #[test]
+    fn test_settled_vaults_are_recorded_in_registry() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        client.initialize(&Address::generate(&env));
+        let registry = env.register(RecordingRegistry, ());
+        client.set_results_registry(&Some(registry.clone()));
+        assert_eq!(client.get_results_registry(), Some(registry.clone()));
+
+        mint(&env, &fixture.token, &fixture.creator, fixture.amount);
+        let completed = client.create_vault_with_category(&fixture.params(), &Category::Learning);
+        let failed = fixture.create_vault();
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        client.validate_milestone(&completed, &None);
+        client.release_funds(&completed, &fixture.token, &None);
+        env.ledger().set_timestamp(fixture.end_timestamp);
+        client.redirect_funds(&failed, &fixture.token, &None);
+
+        let entries = RecordingRegistryClient::new(&env, &registry).entries();
+        assert_eq!(
+            entries.get(0).unwrap(),
+            (
+                OutcomeEntry {
+                    instance: fixture.contract_id.clone(),
+                    vault_id: completed,
+                    creator: fixture.creator.clone(),
+                    outcome: VaultStatus::Completed,
+                    amount_band: 6,
+                    settled_at: fixture.start_timestamp,
+                },
+                Some(Category::Learning)
+            )
+        );
+        let (second, category) = entries.get(1).unwrap();
+        assert_eq!(
+            (second.vault_id, category, second.outcome),
+            (failed, None, VaultStatus::Failed)
+        );
+    }
+
+    #[test]
+    fn test_failing_registry_does_not_block_settlement() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        client.initialize(&Address::generate(&env));
+        client.set_results_registry(&Some(env.register(broken::BrokenRegistry, ())));
+        let vault_id = fixture.create_vault();
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        client.validate_milestone(&vault_id, &None);
+        client.release_funds(&vault_id, &fixture.token, &None);
+        assert_eq!(
+            client.get_vault_state(&vault_id).unwrap().status,
+            VaultStatus::Completed
+        );
+        assert_eq!(amount_band(999), 2);
+        assert_eq!(amount_band(1_000), 3);
+    }
+}
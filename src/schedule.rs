@@ -0,0 +1,116 @@
+//! Scheduling metadata for off-chain settlement jobs.
+//!
+//! Keepers and indexers that settle vaults on time need to know when each vault next becomes
+//! actionable. Rather than have them re-derive deadline rules, the contract emits
+//! `("schedule_updated", vault_id)` with a [`VaultSchedule`] at creation and whenever the
+//! deadline moves (snooze, pause, resume). A scheduler keeps one job per vault, replaces it on
+//! every `schedule_updated`, and drops it when the vault settles. `get_schedule` returns the
+//! same data on demand, e.g. to resynchronize.
+
+use soroban_sdk::{contracttype, Env};
+
+use crate::{events, ProductivityVault};
+
+/// Next thing a scheduler can act on for a vault.
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ScheduledAction {
+    /// The commitment window opens and the milestone can be validated.
+    ReviewOpens = 0,
+    /// Validation closes and an unvalidated vault can be redirected.
+    RedirectEligible = 1,
+}
+
+/// Deadlines of a live vault, as of its latest deadline change.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct VaultSchedule {
+    pub next_action: ScheduledAction,
+    pub next_action_at: u64,
+    /// Validation is accepted from this timestamp (`start_timestamp`).
+    pub review_opens_at: u64,
+    /// Validation is accepted strictly before this timestamp.
+    pub review_expires_at: u64,
+    /// An unvalidated vault can be redirected from this timestamp.
+    pub redirect_eligible_at: u64,
+}
+
+/// Schedule of `vault` at the current ledger time.
+pub fn of(env: &Env, vault: &ProductivityVault) -> VaultSchedule {
+    let (next_action, next_action_at) = if env.ledger().timestamp() < vault.start_timestamp {
+        (ScheduledAction::ReviewOpens, vault.start_timestamp)
+    } else {
+        (ScheduledAction::RedirectEligible, vault.end_timestamp)
+    };
+    VaultSchedule {
+        next_action,
+        next_action_at,
+        review_opens_at: vault.start_timestamp,
+        review_expires_at: vault.end_timestamp,
+        redirect_eligible_at: vault.end_timestamp,
+    }
+}
+
+/// Emit `schedule_updated` for `vault_id`.
+pub fn publish(env: &Env, vault_id: u32, vault: &ProductivityVault) {
+    events::publish(env, "schedule_updated", vault_id, of(env, vault));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use soroban_sdk::{
+        testutils::{Events, Ledger},
+        Symbol, TryFromVal,
+    };
+
+    fn last_schedule(fixture: &VaultFixture) -> VaultSchedule {
+        let env = &fixture.env;
+        let (_, _, data) = env
+            .events()
+            .all()
+            .iter()
+            .filter(|(contract, topics, _)| {
+                *contract == fixture.contract_id
+                    && Symbol::try_from_val(env, &topics.get(0).unwrap()).unwrap()
+                        == Symbol::new(env, "schedule_updated")
+            })
+            .last()
+            .unwrap();
+        let (_, schedule): (u32, VaultSchedule) =
+            <(u32, VaultSchedule)>::try_from_val(env, &data).unwrap();
+        schedule
+    }
+
+    #[test]
+    fn test_schedule_follows_deadline_changes() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let vault_id = fixture.create_vault();
+        let created = last_schedule(&fixture);
+        assert_eq!(created.next_action, ScheduledAction::ReviewOpens);
+        assert_eq!(created.next_action_at, fixture.start_timestamp);
+        assert_eq!(created.redirect_eligible_at, fixture.end_timestamp);
+        assert_eq!(client.get_schedule(&vault_id), created);
+
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        client.pause_vault(
+            &vault_id,
+            &fixture.verifier,
+            &(fixture.start_timestamp + 50),
+        );
+        let paused = last_schedule(&fixture);
+        assert_eq!(paused.next_action, ScheduledAction::RedirectEligible);
+        assert_eq!(paused.next_action_at, fixture.end_timestamp + 50);
+        assert_eq!(paused.review_expires_at, fixture.end_timestamp + 50);
+
+        env.ledger().set_timestamp(fixture.start_timestamp + 20);
+        client.resume_vault(&vault_id, &fixture.creator);
+        assert_eq!(
+            last_schedule(&fixture).next_action_at,
+            fixture.end_timestamp + 20
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Optional address screening for regulated deployments.
+//!
+//! The admin can register one screener contract with `set_screener`. The vault contract asks
+//! it, through the [`Screener`] interface, whether an address may take part before accepting
+//! it:
+//!
+//! - the creator of every new vault, as role `creator` (denied: `NotAuthorized`);
+//! - every success and failure destination when it is set, as role `destination` (denied:
+//!   `DestinationBlocked`);
+//! - every payout recipient and claim destination when funds move, also as `destination`. A
+//!   denied payout is credited to the recipient's claimable balance (`payout_deferred` with
+//!   reason `screened`) so the settlement still completes; a denied claim fails with
+//!   `DestinationBlocked`.
+//!
+//! Unlike the settle hook, the screener is consulted, and it fails closed: a screener that
+//! traps or is missing denies. Without a screener nothing is screened.
+
+use soroban_sdk::{contractclient, contracttype, Address, Env, Symbol};
+
+/// Storage key of the screener.
+#[contracttype]
+#[derive(Clone)]
+pub enum ScreenerKey {
+    /// The screener contract (instance storage).
+    Screener,
+}
+
+/// Entry points a screener contract implements.
+#[contractclient(name = "ScreenerClient")]
+pub trait Screener {
+    /// Whether `address` may take part as `role` (`creator` or `destination`).
+    fn is_allowed(env: Env, address: Address, role: Symbol) -> bool;
+}
+
+/// The configured screener, if any.
+pub fn screener(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&ScreenerKey::Screener)
+}
+
+/// Whether the screener allows `address` as `role`; `true` without a screener.
+pub fn allows(env: &Env, address: &Address, role: &str) -> bool {
+    let Some(screener) = screener(env) else {
+        return true;
+    };
+    matches!(
+        ScreenerClient::new(env, &screener).try_is_allowed(address, &Symbol::new(env, role)),
+        Ok(Ok(true))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use crate::{Error, VaultParams};
+    use soroban_sdk::{
+        contract, contractimpl,
+        testutils::{Address as _, Ledger},
+        token::TokenClient,
+    };
+
+    /// Denies the addresses it is told to.
+    #[contract]
+    struct DenyList;
+
+    #[contractimpl]
+    impl DenyList {
+        pub fn is_allowed(env: Env, address: Address, _role: Symbol) -> bool {
+            !env.storage().instance().has(&address)
+        }
+
+        pub fn deny(env: Env, address: Address, denied: bool) {
+            if denied {
+                env.storage().instance().set(&address, &());
+            } else {
+                env.storage().instance().remove(&address);
+            }
+        }
+    }
+
+    #[test]
+    fn test_screener_gates_creators_and_destinations() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        client.initialize(&Address::generate(&env));
+        let screener = env.register(DenyList, ());
+        let list = DenyListClient::new(&env, &screener);
+        client.set_screener(&Some(screener.clone()));
+        assert_eq!(client.get_screener(), Some(screener));
+
+        list.deny(&fixture.creator, &true);
+        assert_eq!(
+            client.try_create_vault_with_params(&fixture.params()),
+            Err(Ok(Error::NotAuthorized))
+        );
+        list.deny(&fixture.creator, &false);
+        let denied = Address::generate(&env);
+        list.deny(&denied, &true);
+        assert_eq!(
+            client.try_create_vault_with_params(&VaultParams {
+                success_destination: denied,
+                ..fixture.params()
+            }),
+            Err(Ok(Error::DestinationBlocked))
+        );
+
+        // Denied after creation: the payout is held until the screener allows it again.
+        let vault_id = fixture.create_vault();
+        let success = &fixture.success_destination;
+        list.deny(success, &true);
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        client.validate_milestone(&vault_id, &None);
+        client.release_funds(&vault_id, &fixture.token, &None);
+        assert_eq!(
+            client.get_claimable(success, &fixture.token),
+            fixture.amount
+        );
+        assert_eq!(
+            client.try_claim(success, &fixture.token),
+            Err(Ok(Error::DestinationBlocked))
+        );
+        list.deny(success, &false);
+        assert_eq!(client.claim(success, &fixture.token), fixture.amount);
+        assert_eq!(
+            TokenClient::new(&env, &fixture.token).balance(success),
+            fixture.amount
+        );
+    }
+
+    #[test]
+    fn test_unreachable_screener_denies() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        assert_eq!(
+            client.try_set_screener(&None),
+            Err(Ok(Error::NotInitialized))
+        );
+        client.initialize(&Address::generate(&env));
+        // Not a screener contract: every call fails, so everyone is denied.
+        client.set_screener(&Some(fixture.token.clone()));
+        assert_eq!(
+            client.try_create_vault_with_params(&fixture.params()),
+            Err(Ok(Error::NotAuthorized))
+        );
+        client.set_screener(&None);
+        fixture.create_vault();
+    }
+}
@@ -0,0 +1,180 @@
+//! Milestones attested by an external attestation service (e.g. GitHub activity).
+//!
+//! Some goals are checked by a service rather than a person: "50 commits to repo X by June"
+//! can be read off the GitHub API. For these the creator commits to the goal in
+//! `milestone_hash` as usual and fixes, at creation with `create_vault_with_service`, the
+//! ed25519 public key of the attestation service that will vouch for it. The vault's
+//! [`crate::VerificationMode`] is then `Service`: `validate_milestone` is rejected and the
+//! milestone is validated only by `validate_with_service`, with a signature by that key.
+//!
+//! The service publishes its evidence off chain (the attestation document: the repository,
+//! commit range, counts) and signs [`service_message`], which binds the contract, the vault,
+//! its milestone hash and the `sha256` of that document. The document hash is recorded on
+//! chain (`get_service_attestation`), so anyone holding the document can check what the
+//! vault was settled on.
+
+use soroban_sdk::{contracttype, xdr::ToXdr, Address, Bytes, BytesN, Env};
+
+/// Domain tag prefixed to every service attestation message.
+pub const SERVICE_DOMAIN: &[u8] = b"disciplr:service:v1";
+
+/// Storage keys of service-attested vaults.
+#[contracttype]
+#[derive(Clone)]
+pub enum ServiceKey {
+    /// Vault id -> ed25519 public key of its attestation service.
+    ServiceSigner(u32),
+    /// Vault id -> hash of the attestation document it was validated with.
+    ServiceAttestation(u32),
+}
+
+/// Attestation service key of `vault_id`, if it is service-attested.
+pub fn signer(env: &Env, vault_id: u32) -> Option<BytesN<32>> {
+    env.storage()
+        .persistent()
+        .get(&ServiceKey::ServiceSigner(vault_id))
+}
+
+/// Hash of the attestation document `vault_id` was validated with, if any.
+pub fn attestation(env: &Env, vault_id: u32) -> Option<BytesN<32>> {
+    env.storage()
+        .persistent()
+        .get(&ServiceKey::ServiceAttestation(vault_id))
+}
+
+/// Message the service signs to validate `vault_id` on the document hashing to
+/// `attestation_hash`:
+/// `SERVICE_DOMAIN || contract_xdr || u32_be(vault_id) || milestone_hash || attestation_hash`.
+pub fn service_message(
+    env: &Env,
+    contract: &Address,
+    vault_id: u32,
+    milestone_hash: &BytesN<32>,
+    attestation_hash: &BytesN<32>,
+) -> Bytes {
+    let mut message = Bytes::from_slice(env, SERVICE_DOMAIN);
+    message.append(&contract.clone().to_xdr(env));
+    message.extend_from_array(&vault_id.to_be_bytes());
+    message.append(&Bytes::from(milestone_hash.clone()));
+    message.append(&Bytes::from(attestation_hash.clone()));
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use crate::{Error, VerificationMode};
+    use ed25519_dalek::{Signer, SigningKey};
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    fn sign(env: &Env, key: &SigningKey, message: &Bytes) -> BytesN<64> {
+        let mut buffer = [0u8; 256];
+        let len = message.len() as usize;
+        message.copy_into_slice(&mut buffer[..len]);
+        BytesN::from_array(env, &key.sign(&buffer[..len]).to_bytes())
+    }
+
+    #[test]
+    fn test_service_attestation_validates_and_is_recorded() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let service = SigningKey::from_bytes(&[5; 32]);
+        let service_key = BytesN::from_array(&env, &service.verifying_key().to_bytes());
+        crate::testutils::mint(&env, &fixture.token, &fixture.creator, fixture.amount);
+        let vault_id = client.create_vault_with_service(&fixture.params(), &service_key);
+        assert_eq!(
+            client.get_verification_mode(&vault_id),
+            VerificationMode::Service
+        );
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        assert_eq!(
+            client.try_validate_milestone(&vault_id, &None),
+            Err(Ok(Error::NotAuthorized))
+        );
+
+        let document = env
+            .crypto()
+            .sha256(&Bytes::from_slice(&env, b"{\"repo\":\"x\",\"commits\":50}"))
+            .to_bytes();
+        let signature = sign(
+            &env,
+            &service,
+            &client.service_message(&vault_id, &document),
+        );
+        client.validate_with_service(&vault_id, &document, &signature);
+        assert_eq!(client.get_service_attestation(&vault_id), Some(document));
+        client.release_funds(&vault_id, &fixture.token, &None);
+    }
+
+    #[test]
+    fn test_service_signature_must_match_vault_and_key() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let service = SigningKey::from_bytes(&[5; 32]);
+        let service_key = BytesN::from_array(&env, &service.verifying_key().to_bytes());
+        let regular = fixture.create_vault();
+        assert_eq!(
+            client.get_verification_mode(&regular),
+            VerificationMode::Verifier
+        );
+        crate::testutils::mint(&env, &fixture.token, &fixture.creator, fixture.amount);
+        let vault_id = client.create_vault_with_service(&fixture.params(), &service_key);
+        env.ledger().set_timestamp(fixture.start_timestamp);
+
+        let document = BytesN::from_array(&env, &[1; 32]);
+        let signature = sign(
+            &env,
+            &service,
+            &client.service_message(&vault_id, &document),
+        );
+        assert_eq!(
+            client.try_validate_with_service(&regular, &document, &signature),
+            Err(Ok(Error::NotAuthorized))
+        );
+        let other_document = BytesN::from_array(&env, &[2; 32]);
+        assert!(client
+            .try_validate_with_service(&vault_id, &other_document, &signature)
+            .is_err());
+        assert_eq!(client.get_service_attestation(&vault_id), None);
+    }
+    #[test]
+    fn test_second_service_validation_fails_and_keeps_the_first() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let service = SigningKey::from_bytes(&[5; 32]);
+        let service_key = BytesN::from_array(&env, &service.verifying_key().to_bytes());
+        crate::testutils::mint(&env, &fixture.token, &fixture.creator, fixture.amount);
+        let vault_id = client.create_vault_with_service(&fixture.params(), &service_key);
+        let funder = Address::generate(&env);
+        crate::testutils::mint(&env, &fixture.token, &funder, 100);
+        client.fund_bonus(&vault_id, &funder, &100, &10);
+        env.ledger().set_timestamp(fixture.start_timestamp);
+
+        let document = BytesN::from_array(&env, &[1; 32]);
+        let signature = sign(
+            &env,
+            &service,
+            &client.service_message(&vault_id, &document),
+        );
+        client.validate_with_service(&vault_id, &document, &signature);
+        let validated_at = client.get_bonus(&vault_id).unwrap().validated_at;
+        assert_eq!(validated_at, Some(fixture.start_timestamp));
+
+        env.ledger().set_timestamp(fixture.start_timestamp + 10);
+        let later = BytesN::from_array(&env, &[2; 32]);
+        let later_signature = sign(&env, &service, &client.service_message(&vault_id, &later));
+        assert_eq!(
+            client.try_validate_with_service(&vault_id, &later, &later_signature),
+            Err(Ok(Error::VaultNotActive))
+        );
+        assert_eq!(
+            client.get_bonus(&vault_id).unwrap().validated_at,
+            validated_at
+        );
+        assert_eq!(client.get_service_attestation(&vault_id), Some(document));
+    }
+}
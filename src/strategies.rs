@@ -0,0 +1,344 @@
+//! Registry of approved yield strategy adapters.
+//!
+//! Escrow yield is never hardcoded to one lending protocol. Each venue is wrapped in an adapter
+//! contract implementing [`YieldStrategy`], and the admin approves adapters per token with
+//! `approve_strategy`, so a new venue is added by deploying and approving its adapter rather
+//! than upgrading the vault contract.
+//!
+//! Escrow is moved into an adapter only by the admin, with `deploy_to_strategy`, and never past
+//! the adapter's risk cap: the most principal it may hold, set per token with
+//! `set_strategy_cap` (zero until set). Deployed principal still counts as escrow, so every
+//! stake is still owed in full. When a payout or claim needs more than the contract holds,
+//! strategies are recalled until it is covered, and the admin can recall one at any time with
+//! `recall_from_strategy`. A recall withdraws everything the adapter holds: yield above the
+//! principal joins the token's yield buffer (`EscrowBreakdown::yield_buffer`, also topped up
+//! with `fund_yield_buffer`), and a loss is drawn from that buffer, so settlements keep paying
+//! whole stakes as long as the buffer covers what strategies lose.
+
+use soroban_sdk::{contractclient, contracttype, token, Address, Env, Map, Vec};
+
+/// Most strategies approved for one token at a time.
+pub const MAX_STRATEGIES_PER_TOKEN: u32 = 8;
+
+/// Storage key of the strategy registry.
+#[contracttype]
+#[derive(Clone)]
+pub enum StrategyKey {
+    /// Token -> its approved strategy adapters, in approval order.
+    Strategies(Address),
+    /// Token -> strategy -> its risk cap.
+    Caps(Address),
+    /// Token -> strategy -> principal deployed to it and not yet recalled.
+    Deployed(Address),
+}
+
+/// Entry points a yield strategy adapter implements, for one venue.
+#[contractclient(name = "YieldStrategyClient")]
+pub trait YieldStrategy {
+    /// Take `amount` of `token` from `from` and put it to work.
+    fn deposit(env: Env, token: Address, from: Address, amount: i128);
+
+    /// Return `amount` of `token` to `to`, and report the amount actually sent.
+    fn withdraw(env: Env, token: Address, to: Address, amount: i128) -> i128;
+
+    /// Current value, in `token`, of what `owner` has deposited.
+    fn balance_of(env: Env, token: Address, owner: Address) -> i128;
+}
+
+/// Strategies approved for `token`.
+pub fn strategies(env: &Env, token: &Address) -> Vec<Address> {
+    env.storage()
+        .persistent()
+        .get(&StrategyKey::Strategies(token.clone()))
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+/// Whether `strategy` is approved for `token`.
+pub fn is_approved(env: &Env, token: &Address, strategy: &Address) -> bool {
+    strategies(env, token).contains(strategy)
+}
+
+/// Store the strategies approved for `token`.
+pub fn save(env: &Env, token: &Address, strategies: &Vec<Address>) {
+    let key = StrategyKey::Strategies(token.clone());
+    if strategies.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, strategies);
+    }
+}
+
+/// Risk caps of `token`'s strategies.
+pub fn caps(env: &Env, token: &Address) -> Map<Address, i128> {
+    env.storage()
+        .persistent()
+        .get(&StrategyKey::Caps(token.clone()))
+        .unwrap_or_else(|| Map::new(env))
+}
+
+/// Most principal of `token` that `strategy` may hold; zero until set.
+pub fn cap(env: &Env, token: &Address, strategy: &Address) -> i128 {
+    caps(env, token).get(strategy.clone()).unwrap_or(0)
+}
+
+/// Store the risk caps of `token`'s strategies.
+pub fn save_caps(env: &Env, token: &Address, caps: &Map<Address, i128>) {
+    let key = StrategyKey::Caps(token.clone());
+    if caps.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, caps);
+    }
+}
+
+/// Principal of `token` deployed to each strategy.
+pub fn deployed(env: &Env, token: &Address) -> Map<Address, i128> {
+    env.storage()
+        .persistent()
+        .get(&StrategyKey::Deployed(token.clone()))
+        .unwrap_or_else(|| Map::new(env))
+}
+
+/// Store the principal of `token` deployed to each strategy.
+pub fn save_deployed(env: &Env, token: &Address, deployed: &Map<Address, i128>) {
+    let key = StrategyKey::Deployed(token.clone());
+    if deployed.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, deployed);
+    }
+}
+
+/// Principal of `token` deployed across all strategies.
+pub fn deployed_total(env: &Env, token: &Address) -> i128 {
+    deployed(env, token)
+        .values()
+        .iter()
+        .fold(0i128, |total, principal| total.saturating_add(principal))
+}
+
+/// Move `amount` of `token` into `strategy`. Returns whether exactly `amount` left the
+/// contract; the adapter is approved for `amount` only during the call.
+pub fn deposit(env: &Env, token: &Address, strategy: &Address, amount: i128) -> bool {
+    let this = env.current_contract_address();
+    let client = token::Client::new(env, token);
+    let before = client.balance(&this);
+    client.approve(&this, strategy, &amount, &env.ledger().sequence());
+    let deposited = YieldStrategyClient::new(env, strategy).try_deposit(token, &this, &amount);
+    client.approve(&this, strategy, &0, &env.ledger().sequence());
+    matches!(deposited, Ok(Ok(()))) && before - client.balance(&this) == amount
+}
+
+/// Withdraw everything `strategy` holds of `token` for this contract. Returns the amount that
+/// arrived, or `None` if the adapter failed.
+pub fn withdraw_all(env: &Env, token: &Address, strategy: &Address) -> Option<i128> {
+    let this = env.current_contract_address();
+    let adapter = YieldStrategyClient::new(env, strategy);
+    let value = match adapter.try_balance_of(token, &this) {
+        Ok(Ok(value)) => value,
+        _ => return None,
+    };
+    let client = token::Client::new(env, token);
+    let before = client.balance(&this);
+    if value > 0 && !matches!(adapter.try_withdraw(token, &this, &value), Ok(Ok(_))) {
+        return None;
+    }
+    Some((client.balance(&this) - before).max(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::{mint, VaultFixture};
+    use crate::Error;
+    use soroban_sdk::{
+        contract, contractimpl,
+        testutils::{Address as _, Ledger},
+        vec,
+    };
+
+    /// Holds deposits and reports them grown (or shrunk) by a settable rate, paying yield out
+    /// of its own balance.
+    #[contract]
+    struct RateStrategy;
+
+    #[contractimpl]
+    impl RateStrategy {
+        pub fn deposit(env: Env, token: Address, from: Address, amount: i128) {
+            let this = env.current_contract_address();
+            token::Client::new(&env, &token).transfer_from(&this, &from, &this, &amount);
+            let held: i128 = env.storage().instance().get(&from).unwrap_or(0);
+            env.storage().instance().set(&from, &(held + amount));
+        }
+
+        pub fn withdraw(env: Env, token: Address, to: Address, amount: i128) -> i128 {
+            let this = env.current_contract_address();
+            token::Client::new(&env, &token).transfer(&this, &to, &amount);
+            env.storage().instance().remove(&to);
+            amount
+        }
+
+        pub fn balance_of(env: Env, _token: Address, owner: Address) -> i128 {
+            let held: i128 = env.storage().instance().get(&owner).unwrap_or(0);
+            let (num, den): (i128, i128) = env.storage().instance().get(&()).unwrap_or((1, 1));
+            held * num / den
+        }
+
+        pub fn set_rate(env: Env, num: i128, den: i128) {
+            env.storage().instance().set(&(), &(num, den));
+        }
+    }
+
+    /// A funded vault, and an approved strategy for its token capped at the vault's stake.
+    fn deployable(env: &Env) -> (VaultFixture, u32, RateStrategyClient<'_>) {
+        let fixture = VaultFixture::new(env);
+        let client = fixture.client();
+        client.initialize(&Address::generate(env));
+        let vault_id = fixture.create_vault();
+        let strategy = env.register(RateStrategy, ());
+        client.approve_strategy(&fixture.token, &strategy);
+        client.set_strategy_cap(&fixture.token, &strategy, &fixture.amount);
+        (fixture, vault_id, RateStrategyClient::new(env, &strategy))
+    }
+
+    #[test]
+    fn test_strategies_are_approved_per_token() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        client.initialize(&Address::generate(&env));
+        let token = &fixture.token;
+        let other_token = Address::generate(&env);
+        let first = Address::generate(&env);
+        let second = Address::generate(&env);
+
+        client.approve_strategy(token, &first);
+        client.approve_strategy(token, &second);
+        // Approving twice keeps one entry.
+        client.approve_strategy(token, &first);
+        assert_eq!(
+            client.get_strategies(token),
+            vec![&env, first.clone(), second.clone()]
+        );
+        assert!(client.is_strategy_approved(token, &first));
+        assert!(!client.is_strategy_approved(&other_token, &first));
+
+        client.revoke_strategy(token, &first);
+        assert_eq!(client.get_strategies(token), vec![&env, second]);
+        assert!(!client.is_strategy_approved(token, &first));
+    }
+
+    #[test]
+    fn test_strategy_registry_is_bounded() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        client.initialize(&Address::generate(&env));
+        for _ in 0..MAX_STRATEGIES_PER_TOKEN {
+            client.approve_strategy(&fixture.token, &Address::generate(&env));
+        }
+        assert_eq!(
+            client.try_approve_strategy(&fixture.token, &Address::generate(&env)),
+            Err(Ok(Error::InvalidLimits))
+        );
+    }
+    #[test]
+    fn test_deployment_is_capped_per_strategy() {
+        let env = Env::default();
+        let (fixture, _, strategy) = deployable(&env);
+        let client = fixture.client();
+        let token = &fixture.token;
+        let unapproved = env.register(RateStrategy, ());
+        assert_eq!(client.get_strategy_cap(token, &unapproved), 0);
+        assert_eq!(
+            client.try_deploy_to_strategy(token, &unapproved, &1),
+            Err(Ok(Error::NotAuthorized))
+        );
+        assert_eq!(
+            client.try_set_strategy_cap(token, &strategy.address, &-1),
+            Err(Ok(Error::InvalidAmount))
+        );
+
+        client.deploy_to_strategy(token, &strategy.address, &(fixture.amount / 2));
+        assert_eq!(
+            client.try_deploy_to_strategy(token, &strategy.address, &(fixture.amount / 2 + 1)),
+            Err(Ok(Error::InvalidLimits))
+        );
+        assert_eq!(
+            client.get_strategy_principal(token, &strategy.address),
+            fixture.amount / 2
+        );
+        assert_eq!(
+            token::Client::new(&env, token).allowance(&fixture.contract_id, &strategy.address),
+            0
+        );
+        assert_eq!(
+            client.try_revoke_strategy(token, &strategy.address),
+            Err(Ok(Error::InvalidStatus))
+        );
+
+        client.recall_from_strategy(token, &strategy.address);
+        assert_eq!(client.get_strategy_principal(token, &strategy.address), 0);
+        assert_eq!(
+            client.try_recall_from_strategy(token, &strategy.address),
+            Err(Ok(Error::NothingToClaim))
+        );
+        client.revoke_strategy(token, &strategy.address);
+    }
+
+    #[test]
+    fn test_recalled_yield_joins_the_buffer() {
+        let env = Env::default();
+        let (fixture, vault_id, strategy) = deployable(&env);
+        let client = fixture.client();
+        let token = &fixture.token;
+        client.deploy_to_strategy(token, &strategy.address, &fixture.amount);
+        assert!(client.audit_vault(&vault_id).violations.is_empty());
+
+        // 10% yield, paid out of the strategy's own funds.
+        mint(&env, token, &strategy.address, fixture.amount / 10);
+        strategy.set_rate(&11, &10);
+        client.recall_from_strategy(token, &strategy.address);
+        let escrow = client.get_escrow_breakdown(token);
+        assert_eq!(escrow.yield_buffer, fixture.amount / 10);
+        assert_eq!(escrow.active_escrow, fixture.amount);
+        assert_eq!(
+            token::Client::new(&env, token).balance(&fixture.contract_id),
+            escrow.total().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_settlement_pays_the_whole_stake_after_a_strategy_loss() {
+        let env = Env::default();
+        let (fixture, vault_id, strategy) = deployable(&env);
+        let client = fixture.client();
+        let token = &fixture.token;
+        let funder = Address::generate(&env);
+        mint(&env, token, &funder, fixture.amount / 10);
+        assert_eq!(
+            client.try_fund_yield_buffer(&funder, token, &0),
+            Err(Ok(Error::InvalidAmount))
+        );
+        client.fund_yield_buffer(&funder, token, &(fixture.amount / 10));
+        client.deploy_to_strategy(token, &strategy.address, &fixture.amount);
+
+        // The strategy loses 5%; the release recalls it and the buffer covers the loss.
+        strategy.set_rate(&95, &100);
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        client.validate_milestone(&vault_id, &None);
+        client.release_funds(&vault_id, token, &None);
+        assert_eq!(
+            token::Client::new(&env, token).balance(&fixture.success_destination),
+            fixture.amount
+        );
+        assert_eq!(client.get_strategy_principal(token, &strategy.address), 0);
+        let escrow = client.get_escrow_breakdown(token);
+        assert_eq!(
+            escrow.yield_buffer,
+            fixture.amount / 10 - fixture.amount / 20
+        );
+        assert_eq!(escrow.active_escrow, 0);
+        assert!(client.audit_vault(&vault_id).violations.is_empty());
+    }
+}
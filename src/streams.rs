@@ -0,0 +1,221 @@
+//! Payouts streamed to their recipient over time instead of paid in one transfer.
+//!
+//! Some anti-charity setups want the sting of a failure without an immediate windfall for the
+//! failure destination. A vault created with `create_vault_with_vested_forfeit` forfeits into
+//! a [`PayoutStream`] instead: its redirect payout (or cancellation penalty) vests linearly to
+//! the failure destination over the chosen period, starting at settlement.
+//!
+//! The success side works the same way. A sponsor funding a vault as a reward for ongoing
+//! behavior can create it with `create_vault_with_success_stream`: once validated, its release
+//! payout streams to the success destination over the chosen period instead of landing as a
+//! single payday.
+//!
+//! A stream is paid through the pull-payment path. `release_vested` (permissionless) credits
+//! what has vested since the last release to the recipient's claimable balance, which the
+//! recipient withdraws with `claim`. Until then the whole stream counts as `pending_claims` in
+//! the escrow breakdown: it is owed, and no longer part of any live stake. Forfeits to the burn
+//! address are never streamed.
+//!
+//! A stream outlives its vault's archival and is removed once fully released.
+
+use soroban_sdk::{contracttype, Address, Env};
+
+use crate::{
+    math::{self, Rounding},
+    PayoutKind,
+};
+
+/// Longest period a payout may stream over (one year).
+pub const MAX_STREAM_SECS: u64 = 365 * 24 * 60 * 60;
+
+/// Storage keys of payout streams.
+#[contracttype]
+#[derive(Clone)]
+pub enum StreamKey {
+    /// Vault id -> seconds its forfeit vests over.
+    ForfeitVesting(u32),
+    /// Vault id -> seconds its success payout streams over.
+    SuccessStream(u32),
+    /// Vault id -> its [`PayoutStream`], once settled.
+    PayoutStream(u32),
+}
+
+/// A settlement payout vesting linearly to `recipient`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PayoutStream {
+    pub recipient: Address,
+    pub token: Address,
+    pub kind: PayoutKind,
+    pub total: i128,
+    /// Part of `total` already credited to the recipient's claimable balance.
+    pub released: i128,
+    pub start: u64,
+    pub duration: u64,
+}
+
+impl PayoutStream {
+    /// Part of `total` vested at `now` (rounded down).
+    pub fn vested(&self, now: u64) -> Option<i128> {
+        let elapsed = now.saturating_sub(self.start).min(self.duration);
+        math::mul_div(
+            self.total,
+            i128::from(elapsed),
+            i128::from(self.duration),
+            Rounding::Down,
+        )
+    }
+}
+
+/// Whether `duration` is a valid streaming period.
+pub fn is_valid_duration(duration: u64) -> bool {
+    duration > 0 && duration <= MAX_STREAM_SECS
+}
+
+/// Seconds the forfeit of `vault_id` vests over, if it vests.
+pub fn forfeit_vesting(env: &Env, vault_id: u32) -> Option<u64> {
+    env.storage()
+        .persistent()
+        .get(&StreamKey::ForfeitVesting(vault_id))
+}
+
+/// Make the forfeit of `vault_id` vest over `duration` seconds.
+pub fn set_forfeit_vesting(env: &Env, vault_id: u32, duration: u64) {
+    env.storage()
+        .persistent()
+        .set(&StreamKey::ForfeitVesting(vault_id), &duration);
+}
+
+/// Seconds the success payout of `vault_id` streams over, if it streams.
+pub fn success_stream(env: &Env, vault_id: u32) -> Option<u64> {
+    env.storage()
+        .persistent()
+        .get(&StreamKey::SuccessStream(vault_id))
+}
+
+/// Make the success payout of `vault_id` stream over `duration` seconds.
+pub fn set_success_stream(env: &Env, vault_id: u32, duration: u64) {
+    env.storage()
+        .persistent()
+        .set(&StreamKey::SuccessStream(vault_id), &duration);
+}
+
+/// Stream of `vault_id`, if its payout is streaming.
+pub fn get(env: &Env, vault_id: u32) -> Option<PayoutStream> {
+    env.storage()
+        .persistent()
+        .get(&StreamKey::PayoutStream(vault_id))
+}
+
+/// Store the stream of `vault_id`, removing it once fully released.
+pub fn save(env: &Env, vault_id: u32, stream: &PayoutStream) {
+    let key = StreamKey::PayoutStream(vault_id);
+    if stream.released >= stream.total {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, stream);
+    }
+}
+
+/// Drop the streaming terms of an archived vault; a stream in progress is kept.
+pub fn remove_terms(env: &Env, vault_id: u32) {
+    env.storage()
+        .persistent()
+        .remove(&StreamKey::ForfeitVesting(vault_id));
+    env.storage()
+        .persistent()
+        .remove(&StreamKey::SuccessStream(vault_id));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::{mint, VaultFixture};
+    use crate::Error;
+    use soroban_sdk::testutils::Ledger;
+
+    const VESTING: u64 = 1_000;
+
+    #[test]
+    fn test_forfeit_vests_to_failure_destination() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let failure = &fixture.failure_destination;
+        mint(&env, &fixture.token, &fixture.creator, fixture.amount);
+        let vault_id = client.create_vault_with_vested_forfeit(&fixture.params(), &VESTING);
+
+        env.ledger().set_timestamp(fixture.end_timestamp);
+        client.redirect_funds(&vault_id, &fixture.token, &None);
+        let token = soroban_sdk::token::TokenClient::new(&env, &fixture.token);
+        assert_eq!(token.balance(failure), 0);
+        let escrow = client.get_escrow_breakdown(&fixture.token);
+        assert_eq!(escrow.active_escrow, 0);
+        assert_eq!(escrow.pending_claims, fixture.amount);
+        assert_eq!(
+            client.try_release_vested(&vault_id),
+            Err(Ok(Error::NothingToClaim))
+        );
+
+        // A quarter of the way through, a quarter is claimable.
+        env.ledger()
+            .set_timestamp(fixture.end_timestamp + VESTING / 4);
+        assert_eq!(client.release_vested(&vault_id), fixture.amount / 4);
+        assert_eq!(client.claim(failure, &fixture.token), fixture.amount / 4);
+
+        env.ledger()
+            .set_timestamp(fixture.end_timestamp + 2 * VESTING);
+        assert_eq!(client.release_vested(&vault_id), fixture.amount * 3 / 4);
+        assert_eq!(client.get_payout_stream(&vault_id), None);
+        client.claim(failure, &fixture.token);
+        assert_eq!(token.balance(failure), fixture.amount);
+        assert_eq!(
+            client.get_escrow_breakdown(&fixture.token).pending_claims,
+            0
+        );
+    }
+
+    #[test]
+    fn test_success_payout_streams_after_validation() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let success = &fixture.success_destination;
+        mint(&env, &fixture.token, &fixture.creator, fixture.amount);
+        let vault_id = client.create_vault_with_success_stream(&fixture.params(), &VESTING);
+        assert_eq!(client.get_success_stream(&vault_id), Some(VESTING));
+
+        let validated_at = fixture.start_timestamp + 10;
+        env.ledger().set_timestamp(validated_at);
+        client.validate_milestone(&vault_id, &None);
+        client.release_funds(&vault_id, &fixture.token, &None);
+        let stream = client.get_payout_stream(&vault_id).unwrap();
+        assert_eq!(stream.recipient, *success);
+        assert_eq!(stream.kind, PayoutKind::Principal);
+        assert_eq!(stream.start, validated_at);
+
+        // Claimable continuously, as it vests.
+        env.ledger().set_timestamp(validated_at + VESTING / 10);
+        assert_eq!(client.release_vested(&vault_id), fixture.amount / 10);
+        env.ledger().set_timestamp(validated_at + VESTING / 2);
+        assert_eq!(client.release_vested(&vault_id), fixture.amount * 4 / 10);
+        assert_eq!(client.claim(success, &fixture.token), fixture.amount / 2);
+    }
+
+    #[test]
+    fn test_vesting_period_is_bounded() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        for duration in [0, MAX_STREAM_SECS + 1] {
+            assert_eq!(
+                client.try_create_vault_with_vested_forfeit(&fixture.params(), &duration),
+                Err(Ok(Error::InvalidSchedule))
+            );
+            assert_eq!(
+                client.try_create_vault_with_success_stream(&fixture.params(), &duration),
+                Err(Ok(Error::InvalidSchedule))
+            );
+        }
+    }
+}
@@ -0,0 +1,387 @@
+//! Failure legs settled in an allowlisted stable token.
+//!
+//! Charities and treasuries receiving forfeits often cannot handle a volatile asset. The admin
+//! allowlists stable pairs with `set_stable_pair`: a vault token, a stable token, and the
+//! router contract (implementing [`SwapRouter`]) that swaps one into the other. A vault created
+//! with `create_vault_with_stable_failure` names one of these stables and a slippage bound; its
+//! failure leg (redirect payout or cancellation penalty) is swapped into the stable at
+//! settlement and paid to the failure destination through the usual payout path.
+//!
+//! The minimum accepted output comes from the price oracle (see [`crate::valuation`]): the
+//! leg's USD value, converted to the stable at the oracle's price, less `max_slippage_bps`. The
+//! router is allowed to pull exactly the leg with `transfer_from` and must send the stable back
+//! to the vault contract. A settlement never waits on the swap: if the pair has been delisted,
+//! there is no usable price, or the router call fails, the leg is paid in the vault's token
+//! and `("swap_skipped", vault_id)` is emitted with data `(stable, reason)`. A router that
+//! reports success but under-delivers makes the settlement fail with `InvalidPair` until the
+//! admin delists it. Forfeits to the burn address and vested forfeits are never swapped.
+
+use soroban_sdk::{contractclient, contracttype, token, Address, Env, Map};
+
+use crate::{
+    math::{self, Rounding},
+    valuation::{self, PriceOracleClient},
+    PayoutKind, BPS_DENOMINATOR,
+};
+
+/// Largest slippage a vault may accept on its stable swap (10%).
+pub const MAX_SLIPPAGE_BPS: u32 = 1_000;
+
+/// Storage keys of stable settlement.
+#[contracttype]
+#[derive(Clone)]
+pub enum SwapKey {
+    /// Vault token -> stable token -> router swapping one into the other.
+    StablePairs(Address),
+    /// Vault id -> its [`StableSettlement`].
+    StableSettlement(u32),
+}
+
+/// Stable token a vault's failure leg settles in.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StableSettlement {
+    pub stable: Address,
+    pub max_slippage_bps: u32,
+}
+
+/// A settlement leg swapped into a stable: the stake it took (`amount_in`, in the vault's
+/// token) and what `recipient` was paid (`amount` of `token`).
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SwappedPayout {
+    pub recipient: Address,
+    pub kind: PayoutKind,
+    pub amount_in: i128,
+    pub token: Address,
+    pub amount: i128,
+}
+
+/// Entry points a swap router implements.
+#[contractclient(name = "SwapRouterClient")]
+pub trait SwapRouter {
+    /// Pull `amount_in` of `token_in` from `from` with `transfer_from`, swap it, and send at
+    /// least `min_out` of `token_out` to `from`. Returns the amount sent.
+    fn swap_exact_in(
+        env: Env,
+        from: Address,
+        token_in: Address,
+        token_out: Address,
+        amount_in: i128,
+        min_out: i128,
+    ) -> i128;
+}
+
+/// Result of trying to swap a failure leg.
+pub enum SwapOutcome {
+    /// The leg was swapped into this amount of the stable.
+    Swapped(i128),
+    /// The leg was not swapped, for this reason; it is paid in the vault's token.
+    Skipped(&'static str),
+    /// The router reported success but took or delivered the wrong amount.
+    UnderDelivered,
+}
+
+/// Stable pairs allowlisted for `token`, with their routers.
+pub fn pairs(env: &Env, token: &Address) -> Map<Address, Address> {
+    env.storage()
+        .persistent()
+        .get(&SwapKey::StablePairs(token.clone()))
+        .unwrap_or_else(|| Map::new(env))
+}
+
+/// Router allowlisted to swap `token` into `stable`, if any.
+pub fn router(env: &Env, token: &Address, stable: &Address) -> Option<Address> {
+    pairs(env, token).get(stable.clone())
+}
+
+/// Store the stable pairs allowlisted for `token`.
+pub fn save_pairs(env: &Env, token: &Address, pairs: &Map<Address, Address>) {
+    let key = SwapKey::StablePairs(token.clone());
+    if pairs.is_empty() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, pairs);
+    }
+}
+
+/// Stable settlement terms of `vault_id`, if its failure leg settles in a stable.
+pub fn settlement(env: &Env, vault_id: u32) -> Option<StableSettlement> {
+    env.storage()
+        .persistent()
+        .get(&SwapKey::StableSettlement(vault_id))
+}
+
+/// Make the failure leg of `vault_id` settle in a stable.
+pub fn set_settlement(env: &Env, vault_id: u32, terms: &StableSettlement) {
+    env.storage()
+        .persistent()
+        .set(&SwapKey::StableSettlement(vault_id), terms);
+}
+
+/// Drop the stable settlement terms of an archived vault.
+pub fn remove(env: &Env, vault_id: u32) {
+    env.storage()
+        .persistent()
+        .remove(&SwapKey::StableSettlement(vault_id));
+}
+
+/// Smallest output accepted for `amount` of `token` swapped into `stable`: its oracle value in
+/// the stable, less `max_slippage_bps`. `None` without an oracle or a usable price.
+pub fn min_out(env: &Env, token: &Address, amount: i128, terms: &StableSettlement) -> Option<i128> {
+    let oracle = PriceOracleClient::new(env, &valuation::oracle(env)?);
+    let usd_value = match oracle.try_usd_value(token, &amount) {
+        Ok(Ok(value)) if value > 0 => value,
+        _ => return None,
+    };
+    let decimals = token::Client::new(env, &terms.stable)
+        .try_decimals()
+        .ok()?
+        .ok()?;
+    let unit = 10i128.checked_pow(decimals)?;
+    let unit_usd = match oracle.try_usd_value(&terms.stable, &unit) {
+        Ok(Ok(value)) if value > 0 => value,
+        _ => return None,
+    };
+    let expected = math::mul_div(usd_value, unit, unit_usd, Rounding::Down)?;
+    math::apply_bps(
+        expected,
+        BPS_DENOMINATOR - terms.max_slippage_bps,
+        Rounding::Up,
+    )
+}
+
+/// Swap `amount` of `token` held by the contract into the stable of `terms`, keeping the
+/// proceeds in the contract.
+pub fn swap(env: &Env, token: &Address, amount: i128, terms: &StableSettlement) -> SwapOutcome {
+    let Some(router) = router(env, token, &terms.stable) else {
+        return SwapOutcome::Skipped("delisted");
+    };
+    let Some(min_out) = min_out(env, token, amount, terms) else {
+        return SwapOutcome::Skipped("no_price");
+    };
+    let this = env.current_contract_address();
+    let token_in = token::Client::new(env, token);
+    let token_out = token::Client::new(env, &terms.stable);
+    let (in_before, out_before) = (token_in.balance(&this), token_out.balance(&this));
+    token_in.approve(&this, &router, &amount, &env.ledger().sequence());
+    let swapped = SwapRouterClient::new(env, &router).try_swap_exact_in(
+        &this,
+        token,
+        &terms.stable,
+        &amount,
+        &min_out,
+    );
+    let swapped = matches!(swapped, Ok(Ok(_)));
+    // Whatever the router left unspent must not stay spendable by it.
+    token_in.approve(&this, &router, &0, &env.ledger().sequence());
+    if !swapped {
+        return SwapOutcome::Skipped("swap_failed");
+    }
+    let spent = in_before - token_in.balance(&this);
+    let received = token_out.balance(&this) - out_before;
+    if spent != amount || received < min_out {
+        return SwapOutcome::UnderDelivered;
+    }
+    SwapOutcome::Swapped(received)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::{deploy_token, mint, VaultFixture};
+    use crate::Error;
+    use soroban_sdk::{
+        contract, contractimpl,
+        testutils::{Address as _, Ledger},
+    };
+
+    /// Prices each token at a fixed number of USD units per token unit.
+    #[contract]
+    struct TokenPrices;
+
+    #[contractimpl]
+    impl TokenPrices {
+        pub fn usd_value(env: Env, token: Address, amount: i128) -> i128 {
+            let price: i128 = env.storage().instance().get(&token).unwrap_or(0);
+            amount * price
+        }
+
+        pub fn set_price(env: Env, token: Address, price: i128) {
+            env.storage().instance().set(&token, &price);
+        }
+    }
+
+    /// Swaps at a fixed rate, paying out of its own stable balance; a rate of 0 fails.
+    #[contract]
+    struct FixedRateRouter;
+
+    #[contractimpl]
+    impl FixedRateRouter {
+        pub fn swap_exact_in(
+            env: Env,
+            from: Address,
+            token_in: Address,
+            token_out: Address,
+            amount_in: i128,
+            min_out: i128,
+        ) -> i128 {
+            let (num, den): (i128, i128) = env.storage().instance().get(&()).unwrap();
+            let out = amount_in * num / den;
+            assert!(out >= min_out, "slippage");
+            let this = env.current_contract_address();
+            token::Client::new(&env, &token_in).transfer_from(&this, &from, &this, &amount_in);
+            token::Client::new(&env, &token_out).transfer(&this, &from, &out);
+            out
+        }
+
+        pub fn set_rate(env: Env, num: i128, den: i128) {
+            env.storage().instance().set(&(), &(num, den));
+        }
+    }
+
+    struct Setup<'a> {
+        fixture: VaultFixture,
+        stable: Address,
+        router: FixedRateRouterClient<'a>,
+    }
+
+    /// Vault token priced at 3 USD, stable at 1 USD, and a router filled with stable.
+    fn setup(env: &Env) -> Setup<'_> {
+        let fixture = VaultFixture::new(env);
+        let client = fixture.client();
+        client.initialize(&Address::generate(env));
+        let stable = deploy_token(env);
+        let oracle = env.register(TokenPrices, ());
+        let prices = TokenPricesClient::new(env, &oracle);
+        prices.set_price(&fixture.token, &3);
+        prices.set_price(&stable, &1);
+        client.set_price_oracle(&Some(oracle));
+
+        let router = env.register(FixedRateRouter, ());
+        mint(env, &stable, &router, 10 * fixture.amount);
+        client.set_stable_pair(&fixture.token, &stable, &Some(router.clone()));
+        Setup {
+            fixture,
+            stable,
+            router: FixedRateRouterClient::new(env, &router),
+        }
+    }
+
+    fn stable_vault(setup: &Setup) -> u32 {
+        let fixture = &setup.fixture;
+        mint(
+            &fixture.env,
+            &fixture.token,
+            &fixture.creator,
+            fixture.amount,
+        );
+        fixture
+            .client()
+            .create_vault_with_stable_failure(&fixture.params(), &setup.stable, &100)
+    }
+
+    #[test]
+    fn test_failure_leg_is_paid_in_stable() {
+        let env = Env::default();
+        let setup = setup(&env);
+        let fixture = &setup.fixture;
+        let client = fixture.client();
+        // 0.5% below the oracle price, within the 1% bound.
+        setup.router.set_rate(&2985, &1000);
+        let vault_id = stable_vault(&setup);
+        assert_eq!(
+            client.get_stable_settlement(&vault_id).unwrap().stable,
+            setup.stable
+        );
+
+        env.ledger().set_timestamp(fixture.end_timestamp);
+        client.redirect_funds(&vault_id, &fixture.token, &None);
+        let failure = &fixture.failure_destination;
+        let received = fixture.amount * 2985 / 1000;
+        assert_eq!(
+            token::Client::new(&env, &setup.stable).balance(failure),
+            received
+        );
+        assert_eq!(token::Client::new(&env, &fixture.token).balance(failure), 0);
+        let receipt = client.get_settlement(&vault_id).unwrap();
+        assert_eq!(
+            receipt.net_by_destination.get(0).unwrap().amount,
+            fixture.amount
+        );
+        assert_eq!(
+            receipt.swapped,
+            soroban_sdk::vec![
+                &env,
+                SwappedPayout {
+                    recipient: failure.clone(),
+                    kind: PayoutKind::Principal,
+                    amount_in: fixture.amount,
+                    token: setup.stable.clone(),
+                    amount: received,
+                }
+            ]
+        );
+        for token in [&fixture.token, &setup.stable] {
+            let escrow = client.get_escrow_breakdown(token);
+            assert_eq!((escrow.active_escrow, escrow.pending_claims), (0, 0));
+        }
+    }
+
+    #[test]
+    fn test_failed_swap_pays_in_vault_token() {
+        let env = Env::default();
+        let setup = setup(&env);
+        let fixture = &setup.fixture;
+        let client = fixture.client();
+        // 2% below the oracle price: the router refuses the minimum.
+        setup.router.set_rate(&2940, &1000);
+        let vault_id = stable_vault(&setup);
+
+        env.ledger().set_timestamp(fixture.end_timestamp);
+        client.redirect_funds(&vault_id, &fixture.token, &None);
+        let failure = &fixture.failure_destination;
+        assert_eq!(
+            token::Client::new(&env, &fixture.token).balance(failure),
+            fixture.amount
+        );
+        assert_eq!(token::Client::new(&env, &setup.stable).balance(failure), 0);
+        assert_eq!(
+            token::Client::new(&env, &fixture.token)
+                .allowance(&fixture.contract_id, &setup.router.address),
+            0
+        );
+        assert!(client.get_settlement(&vault_id).unwrap().swapped.is_empty());
+    }
+
+    #[test]
+    fn test_stable_settlement_needs_an_allowlisted_pair() {
+        let env = Env::default();
+        let setup = setup(&env);
+        let fixture = &setup.fixture;
+        let client = fixture.client();
+        let other = Address::generate(&env);
+        assert_eq!(
+            client.try_create_vault_with_stable_failure(&fixture.params(), &other, &100),
+            Err(Ok(Error::InvalidPair))
+        );
+        assert_eq!(
+            client.try_create_vault_with_stable_failure(
+                &fixture.params(),
+                &setup.stable,
+                &(MAX_SLIPPAGE_BPS + 1)
+            ),
+            Err(Ok(Error::InvalidLimits))
+        );
+        assert_eq!(
+            client.try_set_stable_pair(&fixture.token, &fixture.token, &None),
+            Err(Ok(Error::InvalidPair))
+        );
+
+        client.set_stable_pair(&fixture.token, &setup.stable, &None);
+        assert_eq!(
+            client.get_stable_router(&fixture.token, &setup.stable),
+            None
+        );
+    }
+}
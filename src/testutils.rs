@@ -0,0 +1,170 @@
+//! Reusable fixtures for integration tests against the vault contract.
+//!
+//! Enabled with the `testutils` cargo feature (which also enables `soroban-sdk/testutils`).
+//! Integrators can register the contract, deploy a SEP-41 token, mint balances and fabricate
+//! vaults in any status without reaching into contract storage themselves.
+
+use soroban_sdk::{testutils::Address as _, token::StellarAssetClient, Address, BytesN, Env};
+
+use crate::{
+    on_terminal,
+    packed::{load_vault, save_vault},
+    DisciplrVault, DisciplrVaultClient, VaultParams, VaultStatus,
+};
+
+/// Register a fresh `DisciplrVault` instance and return its address.
+pub fn register_vault_contract(env: &Env) -> Address {
+    env.register(DisciplrVault, ())
+}
+
+/// Deploy a Stellar asset contract (SEP-41 token) with a generated admin.
+pub fn deploy_token(env: &Env) -> Address {
+    let admin = Address::generate(env);
+    env.register_stellar_asset_contract_v2(admin).address()
+}
+
+/// Mint `amount` of `token` to `to`. `token` must come from [`deploy_token`].
+pub fn mint(env: &Env, token: &Address, to: &Address, amount: i128) {
+    StellarAssetClient::new(env, token).mint(to, &amount);
+}
+
+/// Overwrite the status of a stored vault, bypassing the lifecycle checks.
+///
+/// Moving to a terminal status also removes the vault from the expiry index, as a real
+/// settlement would. No tokens are moved: the stake stays in the contract.
+pub fn set_vault_status(env: &Env, contract_id: &Address, vault_id: u32, status: VaultStatus) {
+    env.as_contract(contract_id, || {
+        let mut vault = load_vault(env, vault_id).expect("vault not found");
+        vault.status = status;
+        save_vault(env, vault_id, &vault);
+        if !matches!(vault.status, VaultStatus::Active | VaultStatus::Disputed) {
+            on_terminal(env, vault_id, &vault).expect("terminal bookkeeping failed");
+        }
+    });
+}
+
+/// A registered vault contract, a token and a default set of actors.
+///
+/// `new` calls `env.mock_all_auths()`.
+pub struct VaultFixture {
+    pub env: Env,
+    pub contract_id: Address,
+    pub token: Address,
+    pub creator: Address,
+    pub verifier: Address,
+    pub success_destination: Address,
+    pub failure_destination: Address,
+    pub amount: i128,
+    pub start_timestamp: u64,
+    pub end_timestamp: u64,
+}
+
+impl VaultFixture {
+    pub fn new(env: &Env) -> Self {
+        env.mock_all_auths();
+        VaultFixture {
+            env: env.clone(),
+            contract_id: register_vault_contract(env),
+            token: deploy_token(env),
+            creator: Address::generate(env),
+            verifier: Address::generate(env),
+            success_destination: Address::generate(env),
+            failure_destination: Address::generate(env),
+            amount: 1_000_000,
+            start_timestamp: 100,
+            end_timestamp: 1_000,
+        }
+    }
+
+    pub fn client(&self) -> DisciplrVaultClient<'_> {
+        DisciplrVaultClient::new(&self.env, &self.contract_id)
+    }
+
+    /// Parameters for a private vault with the fixture's actors, timestamps and amount.
+    pub fn params(&self) -> VaultParams {
+        VaultParams {
+            usdc_token: self.token.clone(),
+            creator: self.creator.clone(),
+            amount: self.amount,
+            start_timestamp: self.start_timestamp,
+            end_timestamp: self.end_timestamp,
+            milestone_hash: BytesN::from_array(&self.env, &[1u8; 32]),
+            verifier: Some(self.verifier.clone()),
+            success_destination: self.success_destination.clone(),
+            failure_destination: self.failure_destination.clone(),
+            is_public: false,
+            arbiter: None,
+        }
+    }
+
+    /// Mint the stake to the creator and create a vault from `params`.
+    pub fn create_vault_with(&self, params: &VaultParams) -> u32 {
+        mint(
+            &self.env,
+            &params.usdc_token,
+            &params.creator,
+            params.amount,
+        );
+        self.client().create_vault_with_params(params)
+    }
+
+    /// Mint the stake and create a vault from [`VaultFixture::params`].
+    pub fn create_vault(&self) -> u32 {
+        self.create_vault_with(&self.params())
+    }
+
+    /// Create a default vault and force it into `status` (see [`set_vault_status`]).
+    pub fn create_vault_in_state(&self, status: VaultStatus) -> u32 {
+        let vault_id = self.create_vault();
+        set_vault_status(&self.env, &self.contract_id, vault_id, status);
+        vault_id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use soroban_sdk::{testutils::Ledger, token::TokenClient};
+
+    #[test]
+    fn test_fixture_creates_funded_vault() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+
+        let vault_id = fixture.create_vault();
+
+        let vault = fixture.client().get_vault_state(&vault_id).unwrap();
+        assert_eq!(vault.status, VaultStatus::Active);
+        assert_eq!(
+            TokenClient::new(&env, &fixture.token).balance(&fixture.contract_id),
+            fixture.amount
+        );
+    }
+
+    #[test]
+    fn test_create_vault_in_state_is_terminal_for_lifecycle_calls() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        env.ledger().set_timestamp(fixture.start_timestamp);
+
+        let vault_id = fixture.create_vault_in_state(VaultStatus::Failed);
+
+        let client = fixture.client();
+        assert_eq!(
+            client.get_vault_state(&vault_id).unwrap().status,
+            VaultStatus::Failed
+        );
+        assert_eq!(
+            client.try_cancel_vault(&vault_id, &fixture.token, &None),
+            Err(Ok(Error::VaultNotActive))
+        );
+        assert_eq!(
+            client
+                .get_vaults_expiring_between(&0, &fixture.end_timestamp, &0, &10)
+                .ids
+                .len(),
+            0
+        );
+    }
+}
@@ -0,0 +1,472 @@
+//! Timelock queue for privileged admin actions.
+//!
+//! Once the admin sets a delay with `set_admin_delay`, the [`AdminAction`]s that change what
+//! happens to escrowed funds (fee overrides, arbiter fees, the blocklist and destination
+//! allowlist, the contracts settlements consult or notify, settlement controls, upgrades,
+//! rescues and admin handovers; see [`AdminAction::is_timelocked`]) can no longer be called
+//! directly; they fail with `TimelockPending`. The admin queues them instead:
+//!
+//! 1. `propose_admin_action(action)` records it and returns its id. It becomes executable
+//!    `delay` seconds later (rescues also wait at least `RESCUE_TIMELOCK_SECS`).
+//! 2. `execute_admin_action(id)` applies it once executable, through the same code as the
+//!    direct call, so every check and event is the same.
+//! 3. Until then, `cancel_admin_action(id)` drops it.
+//!
+//! Every queued action is public (`get_pending_admin_actions`) and announced with
+//! `admin_action_queued`, so users get at least `delay` seconds' notice of any governance
+//! change affecting their escrow and can exit first. Changing the delay is itself a queued
+//! action, so it cannot be lowered without notice either. With no delay (the default) the
+//! direct calls work as before.
+
+use soroban_sdk::{contracttype, Address, BytesN, Env, Vec};
+
+use crate::{
+    config::Limits,
+    cosign::CosignPolicy,
+    events,
+    fees::{FeeOverride, FeeSubject},
+    holding,
+    keepers::{self, BountyPolicy},
+    Error, BPS_DENOMINATOR, RESCUE_TIMELOCK_SECS,
+};
+
+/// Longest delay the admin can set.
+pub const MAX_ADMIN_DELAY_SECS: u64 = 30 * 24 * 60 * 60;
+
+/// Storage keys of the admin action queue.
+#[contracttype]
+#[derive(Clone)]
+pub enum TimelockKey {
+    /// Notice required for privileged actions, in seconds (instance storage).
+    AdminDelay,
+    /// Ids handed out so far (instance storage).
+    ActionCount,
+    /// Ids of the queued actions, oldest first.
+    PendingActions,
+    /// Action id -> [`QueuedAction`].
+    Action(u32),
+}
+
+/// A fee override to set, as `set_fee_override` with `Some`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FeeOverrideChange {
+    pub subject: FeeSubject,
+    pub fee_override: FeeOverride,
+}
+
+/// A sweep of surplus, as `rescue_tokens` then `execute_rescue`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RescueAction {
+    pub token: Address,
+    pub amount: i128,
+    pub to: Address,
+}
+
+/// A stable pair's router to allowlist, or with `None` delist, as `set_stable_pair`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StablePairChange {
+    pub token: Address,
+    pub stable: Address,
+    pub router: Option<Address>,
+}
+
+/// A yield adapter for `token`, as `approve_strategy` and `revoke_strategy`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategyChange {
+    pub token: Address,
+    pub strategy: Address,
+}
+
+/// A yield adapter's risk cap for `token`, as `set_strategy_cap`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategyCap {
+    pub token: Address,
+    pub strategy: Address,
+    pub cap: i128,
+}
+
+/// Principal of `token` moved into a yield adapter, as `deploy_to_strategy`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct StrategyDeployment {
+    pub token: Address,
+    pub strategy: Address,
+    pub amount: i128,
+}
+
+/// An arbiter and its fee, as `register_arbiter`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ArbiterRegistration {
+    pub arbiter: Address,
+    pub fee_bps: u32,
+}
+
+/// A token's outflow cap, or with `None` no cap, as `set_outflow_cap`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OutflowCapChange {
+    pub token: Address,
+    pub cap: Option<i128>,
+}
+
+/// A privileged action, with the arguments of its direct entry point.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AdminAction {
+    SetFeeOverride(FeeOverrideChange),
+    /// As `set_fee_override` with `None`.
+    RemoveFeeOverride(FeeSubject),
+    BlockAddress(Address),
+    UnblockAddress(Address),
+    SetApprovedOnly(bool),
+    ApproveDestination(Address),
+    RevokeDestination(Address),
+    Upgrade(BytesN<32>),
+    Rescue(RescueAction),
+    SetAdminDelay(u64),
+    /// Hand the admin role to an address that must accept it (see `governance`).
+    TransferAdmin(Address),
+    /// Hand the fee manager role to an address (see `treasury`).
+    SetFeeManager(Address),
+    SetTreasury(Address),
+    SetLimits(Limits),
+    SetMaxFreezeSecs(u64),
+    SetTombstoneMode(bool),
+    SetDuplicateGuard(bool),
+    SetCancelHold(u32),
+    SetBountyPolicy(BountyPolicy),
+    RegisterArbiter(ArbiterRegistration),
+    SetSettleHook(Option<Address>),
+    SetScreener(Option<Address>),
+    SetPriceOracle(Option<Address>),
+    SetResultsRegistry(Option<Address>),
+    SetCosignPolicy(CosignPolicy),
+    /// As `set_cosign_policy` with `None`.
+    RemoveCosignPolicy,
+    SetOutflowCap(OutflowCapChange),
+    SetStablePair(StablePairChange),
+    ApproveStrategy(StrategyChange),
+    RevokeStrategy(StrategyChange),
+    SetStrategyCap(StrategyCap),
+    DeployToStrategy(StrategyDeployment),
+    RecallFromStrategy(StrategyChange),
+}
+
+/// An action waiting in the queue.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QueuedAction {
+    pub id: u32,
+    pub action: AdminAction,
+    pub proposed_at: u64,
+    pub executable_at: u64,
+}
+
+impl AdminAction {
+    /// Whether the action must be queued while an admin delay is set. The others may also be
+    /// queued, but can always be called directly.
+    pub fn is_timelocked(&self) -> bool {
+        matches!(
+            self,
+            AdminAction::SetFeeOverride(_)
+                | AdminAction::RemoveFeeOverride(_)
+                | AdminAction::BlockAddress(_)
+                | AdminAction::UnblockAddress(_)
+                | AdminAction::SetApprovedOnly(_)
+                | AdminAction::ApproveDestination(_)
+                | AdminAction::RevokeDestination(_)
+                | AdminAction::Upgrade(_)
+                | AdminAction::Rescue(_)
+                | AdminAction::SetAdminDelay(_)
+                | AdminAction::TransferAdmin(_)
+                | AdminAction::SetFeeManager(_)
+                | AdminAction::RegisterArbiter(_)
+                | AdminAction::SetSettleHook(_)
+                | AdminAction::SetScreener(_)
+                | AdminAction::SetPriceOracle(_)
+                | AdminAction::SetResultsRegistry(_)
+                | AdminAction::SetCosignPolicy(_)
+                | AdminAction::RemoveCosignPolicy
+                | AdminAction::SetOutflowCap(_)
+                | AdminAction::SetStablePair(_)
+                | AdminAction::ApproveStrategy(_)
+                | AdminAction::RevokeStrategy(_)
+                | AdminAction::SetStrategyCap(_)
+                | AdminAction::DeployToStrategy(_)
+        )
+    }
+
+    /// Checks that do not depend on state, run when the action is proposed or applied, so a
+    /// queued action cannot be rejected for its arguments alone.
+    pub fn check(&self) -> Result<(), Error> {
+        match self {
+            AdminAction::SetFeeOverride(change)
+                if change.fee_override.fee_bps > BPS_DENOMINATOR =>
+            {
+                Err(Error::InvalidFee)
+            }
+            AdminAction::Rescue(rescue) if rescue.amount <= 0 => Err(Error::InvalidAmount),
+            AdminAction::SetAdminDelay(secs) if *secs > MAX_ADMIN_DELAY_SECS => {
+                Err(Error::InvalidLimits)
+            }
+            AdminAction::SetLimits(limits) if !limits.is_valid() => Err(Error::InvalidLimits),
+            AdminAction::SetMaxFreezeSecs(0) => Err(Error::InvalidTimestamp),
+            AdminAction::SetCancelHold(ledgers) if *ledgers > holding::MAX_CANCEL_HOLD_LEDGERS => {
+                Err(Error::InvalidLimits)
+            }
+            AdminAction::SetBountyPolicy(policy)
+                if policy.bounty_bps > keepers::MAX_KEEPER_BOUNTY_BPS =>
+            {
+                Err(Error::InvalidFee)
+            }
+            AdminAction::SetBountyPolicy(policy) if policy.unregistered_cap < 0 => {
+                Err(Error::InvalidAmount)
+            }
+            AdminAction::RegisterArbiter(registration)
+                if registration.fee_bps > BPS_DENOMINATOR =>
+            {
+                Err(Error::InvalidFee)
+            }
+            AdminAction::SetCosignPolicy(policy) if policy.threshold <= 0 => {
+                Err(Error::InvalidAmount)
+            }
+            AdminAction::SetOutflowCap(change) if change.cap.is_some_and(|cap| cap < 0) => {
+                Err(Error::InvalidAmount)
+            }
+            AdminAction::SetStablePair(change) if change.token == change.stable => {
+                Err(Error::InvalidPair)
+            }
+            AdminAction::SetStrategyCap(change) if change.cap < 0 => Err(Error::InvalidAmount),
+            AdminAction::DeployToStrategy(deployment) if deployment.amount <= 0 => {
+                Err(Error::InvalidAmount)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Notice required for privileged actions; zero while they may be called directly.
+pub fn delay(env: &Env) -> u64 {
+    env.storage()
+        .instance()
+        .get(&TimelockKey::AdminDelay)
+        .unwrap_or(0)
+}
+
+/// Fail with `TimelockPending` while an admin delay is set.
+pub fn ensure_no_delay(env: &Env) -> Result<(), Error> {
+    if delay(env) > 0 {
+        return Err(Error::TimelockPending);
+    }
+    Ok(())
+}
+
+/// Fail with `TimelockPending` if `action` must be queued rather than called directly.
+pub fn ensure_direct(env: &Env, action: &AdminAction) -> Result<(), Error> {
+    if action.is_timelocked() {
+        ensure_no_delay(env)?;
+    }
+    Ok(())
+}
+
+/// Ids of the queued actions, oldest first.
+pub fn pending_ids(env: &Env) -> Vec<u32> {
+    env.storage()
+        .persistent()
+        .get(&TimelockKey::PendingActions)
+        .unwrap_or_else(|| Vec::new(env))
+}
+
+/// The queued action `id`, if any.
+pub fn get(env: &Env, id: u32) -> Option<QueuedAction> {
+    env.storage().persistent().get(&TimelockKey::Action(id))
+}
+
+/// Queue `action`; returns its id.
+pub fn propose(env: &Env, action: AdminAction) -> Result<u32, Error> {
+    action.check()?;
+    let id: u32 = env
+        .storage()
+        .instance()
+        .get(&TimelockKey::ActionCount)
+        .unwrap_or(0);
+    let proposed_at = env.ledger().timestamp();
+    let wait = match action {
+        AdminAction::Rescue(..) => delay(env).max(RESCUE_TIMELOCK_SECS),
+        _ => delay(env),
+    };
+    let queued = QueuedAction {
+        id,
+        action: action.clone(),
+        proposed_at,
+        executable_at: proposed_at.saturating_add(wait),
+    };
+    env.storage()
+        .instance()
+        .set(&TimelockKey::ActionCount, &(id + 1));
+    env.storage()
+        .persistent()
+        .set(&TimelockKey::Action(id), &queued);
+    let mut ids = pending_ids(env);
+    ids.push_back(id);
+    env.storage()
+        .persistent()
+        .set(&TimelockKey::PendingActions, &ids);
+    events::publish(
+        env,
+        "admin_action_queued",
+        id,
+        (action, queued.executable_at),
+    );
+    Ok(id)
+}
+
+/// Remove `id` from the queue. With `ready`, fails with `TimelockPending` until it is
+/// executable. Fails with `NoPendingRescue` if it is not queued.
+pub fn take(env: &Env, id: u32, ready: bool) -> Result<AdminAction, Error> {
+    let queued = get(env, id).ok_or(Error::NoPendingRescue)?;
+    if ready && env.ledger().timestamp() < queued.executable_at {
+        return Err(Error::TimelockPending);
+    }
+    env.storage().persistent().remove(&TimelockKey::Action(id));
+    let mut ids = pending_ids(env);
+    if let Some(index) = ids.first_index_of(id) {
+        ids.remove(index);
+    }
+    env.storage()
+        .persistent()
+        .set(&TimelockKey::PendingActions, &ids);
+    Ok(queued.action)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use crate::DisciplrVaultClient;
+    use soroban_sdk::testutils::{Address as _, Ledger};
+
+    const DELAY: u64 = 3_600;
+
+    fn with_delay(fixture: &VaultFixture) -> DisciplrVaultClient<'_> {
+        let client = fixture.client();
+        client.initialize(&Address::generate(&fixture.env));
+        client.set_admin_delay(&DELAY);
+        client
+    }
+
+    #[test]
+    fn test_queued_action_waits_for_delay() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = with_delay(&fixture);
+        let target = Address::generate(&env);
+        assert_eq!(
+            client.try_block_address(&target),
+            Err(Ok(Error::TimelockPending))
+        );
+
+        let id = client.propose_admin_action(&AdminAction::BlockAddress(target.clone()));
+        let queued = client.get_admin_action(&id).unwrap();
+        assert_eq!(queued.executable_at, queued.proposed_at + DELAY);
+        assert_eq!(client.get_pending_admin_actions().len(), 1);
+        assert_eq!(
+            client.try_execute_admin_action(&id),
+            Err(Ok(Error::TimelockPending))
+        );
+
+        env.ledger().set_timestamp(queued.executable_at);
+        client.execute_admin_action(&id);
+        assert!(client.is_address_blocked(&target));
+        assert_eq!(client.get_admin_action(&id), None);
+        assert_eq!(client.get_pending_admin_actions().len(), 0);
+        assert_eq!(
+            client.try_execute_admin_action(&id),
+            Err(Ok(Error::NoPendingRescue))
+        );
+    }
+
+    #[test]
+    fn test_cancelled_action_never_runs_and_delay_change_is_queued() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = with_delay(&fixture);
+        assert_eq!(
+            client.try_set_admin_delay(&0),
+            Err(Ok(Error::TimelockPending))
+        );
+        assert_eq!(
+            client.try_propose_admin_action(&AdminAction::SetAdminDelay(MAX_ADMIN_DELAY_SECS + 1)),
+            Err(Ok(Error::InvalidLimits))
+        );
+
+        let approve = client.propose_admin_action(&AdminAction::SetApprovedOnly(true));
+        let lower = client.propose_admin_action(&AdminAction::SetAdminDelay(0));
+        client.cancel_admin_action(&approve);
+        assert_eq!(
+            client
+                .get_pending_admin_actions()
+                .get(0)
+                .map(|queued| queued.id),
+            Some(lower)
+        );
+
+        env.ledger().set_timestamp(env.ledger().timestamp() + DELAY);
+        assert_eq!(
+            client.try_execute_admin_action(&approve),
+            Err(Ok(Error::NoPendingRescue))
+        );
+        client.execute_admin_action(&lower);
+        assert!(!client.get_approved_only());
+        assert_eq!(client.get_admin_delay(), 0);
+        // Direct calls work again.
+        client.set_approved_only(&true);
+        assert!(client.get_approved_only());
+    }
+
+    #[test]
+    fn test_settlement_settings_are_timelocked() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = with_delay(&fixture);
+        let token = fixture.token.clone();
+        let other = Address::generate(&env);
+        let pending = Err(Ok(Error::TimelockPending));
+        assert_eq!(client.try_register_arbiter(&other, &100), pending);
+        assert_eq!(client.try_set_settle_hook(&Some(other.clone())), pending);
+        assert_eq!(client.try_set_screener(&Some(other.clone())), pending);
+        assert_eq!(client.try_set_price_oracle(&Some(other.clone())), pending);
+        assert_eq!(client.try_set_results_registry(&None), pending);
+        assert_eq!(client.try_set_cosign_policy(&None), pending);
+        assert_eq!(client.try_set_outflow_cap(&token, &Some(0)), pending);
+        assert_eq!(
+            client.try_set_stable_pair(&token, &other, &Some(other.clone())),
+            pending
+        );
+        assert_eq!(client.try_approve_strategy(&token, &other), pending);
+        assert_eq!(client.try_revoke_strategy(&token, &other), pending);
+        assert_eq!(
+            client.try_propose_admin_action(&AdminAction::SetStablePair(StablePairChange {
+                token: token.clone(),
+                stable: token.clone(),
+                router: None,
+            })),
+            Err(Ok(Error::InvalidPair))
+        );
+
+        let id = client.propose_admin_action(&AdminAction::RegisterArbiter(ArbiterRegistration {
+            arbiter: other.clone(),
+            fee_bps: 100,
+        }));
+        assert_eq!(client.get_arbiter(&other), None);
+        env.ledger().set_timestamp(env.ledger().timestamp() + DELAY);
+        client.execute_admin_action(&id);
+        assert_eq!(client.get_arbiter(&other).unwrap().fee_bps, 100);
+    }
+}
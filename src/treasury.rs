@@ -0,0 +1,108 @@
+//! Protocol fee accrual and sweeps.
+//!
+//! Protocol fees (today the snooze fee) are not pushed out when they are charged. They are
+//! collected into the contract and added to the token's `accrued_fees` bucket of
+//! [`crate::EscrowBreakdown`], so fee revenue per token is visible on chain with
+//! `get_accrued_fees` and is never mistaken for rescuable surplus.
+//!
+//! The fee manager withdraws the accrued balance with `sweep_fees(token, to)`, one transfer for
+//! any number of fees. The role defaults to the admin; the admin can hand it to a separate
+//! address (e.g. a finance multisig) with `set_fee_manager`, a timelocked action. Sweeps count
+//! against the outflow circuit breaker like any other outflow.
+
+use soroban_sdk::{contracttype, Address, Env};
+
+use crate::{config, Error};
+
+/// Storage key of the fee manager.
+#[contracttype]
+#[derive(Clone)]
+pub enum TreasuryKey {
+    /// Address allowed to sweep fees, if not the admin (instance storage).
+    FeeManager,
+}
+
+/// The fee manager: the address set with `set_fee_manager`, or else the admin.
+pub fn fee_manager(env: &Env) -> Option<Address> {
+    env.storage()
+        .instance()
+        .get(&TreasuryKey::FeeManager)
+        .or_else(|| config::load(env).admin)
+}
+
+/// The fee manager, after requiring its authorization.
+pub fn require_fee_manager(env: &Env) -> Result<Address, Error> {
+    let manager = fee_manager(env).ok_or(Error::NotInitialized)?;
+    manager.require_auth();
+    Ok(manager)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::{mint, VaultFixture};
+    use crate::timelock::AdminAction;
+    use soroban_sdk::{
+        testutils::{Address as _, Ledger},
+        token::TokenClient,
+    };
+
+    #[test]
+    fn test_snooze_fees_accrue_until_swept() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let token = TokenClient::new(&env, &fixture.token);
+        client.initialize(&Address::generate(&env));
+        let first = fixture.create_vault();
+        let second = fixture.create_vault();
+        mint(&env, &fixture.token, &fixture.creator, fixture.amount);
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        client.snooze(&first, &60);
+        client.snooze(&second, &60);
+
+        let fee = fixture.amount / 20;
+        assert_eq!(client.get_accrued_fees(&fixture.token), 2 * fee);
+        assert_eq!(client.get_rescuable_surplus(&fixture.token), 0);
+
+        let to = Address::generate(&env);
+        assert_eq!(client.sweep_fees(&fixture.token, &to), 2 * fee);
+        assert_eq!(token.balance(&to), 2 * fee);
+        assert_eq!(client.get_accrued_fees(&fixture.token), 0);
+        assert_eq!(
+            client.try_sweep_fees(&fixture.token, &to),
+            Err(Ok(Error::NothingToClaim))
+        );
+    }
+
+    #[test]
+    fn test_fee_manager_role_replaces_admin_for_sweeps() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let admin = Address::generate(&env);
+        assert_eq!(client.get_fee_manager(), None);
+        client.initialize(&admin);
+        assert_eq!(client.get_fee_manager(), Some(admin));
+
+        let manager = Address::generate(&env);
+        client.set_fee_manager(&manager);
+        assert_eq!(client.get_fee_manager(), Some(manager.clone()));
+        client.set_admin_delay(&60);
+        assert_eq!(
+            client.try_execute(&AdminAction::SetFeeManager(Address::generate(&env))),
+            Err(Ok(Error::TimelockPending))
+        );
+
+        let vault_id = fixture.create_vault();
+        mint(&env, &fixture.token, &fixture.creator, fixture.amount);
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        client.snooze(&vault_id, &60);
+        client.sweep_fees(&fixture.token, &manager);
+        assert_eq!(
+            env.auths()[0].0,
+            manager,
+            "the sweep is authorized by the fee manager"
+        );
+    }
+}
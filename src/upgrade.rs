@@ -0,0 +1,174 @@
+//! Wasm upgrades and versioned storage migrations.
+//!
+//! The instance records the schema version of its stored data under `DataKey::StorageVersion`
+//! (instances from before the entry existed are schema 1). A build whose stored types differ
+//! from the previous one bumps [`STORAGE_VERSION`] and adds a step below that rewrites the
+//! affected entries.
+//!
+//! An upgrade is two admin calls, submitted together (e.g. from one multi-call or
+//! upgrade-helper transaction) so nothing runs against half-migrated storage:
+//!
+//! 1. `upgrade(new_wasm_hash)` swaps the code; it takes effect for later invocations.
+//! 2. `migrate(from_version)` on the new code runs every step from the stored version up to
+//!    `STORAGE_VERSION` in order. `from_version` must equal the stored version, so a migration
+//!    cannot be skipped, replayed or run against the wrong starting schema, and all steps
+//!    commit together or not at all.
+
+use soroban_sdk::{contracttype, Address, Env, Map, Symbol, TryFromVal, Val};
+
+use crate::{
+    config::{self, Config, Limits},
+    DataKey, Error,
+};
+
+/// Storage schema version written by this build.
+pub const STORAGE_VERSION: u32 = 2;
+
+/// Schema version of the instance's stored data.
+pub fn stored_version(env: &Env) -> u32 {
+    env.storage()
+        .instance()
+        .get(&DataKey::StorageVersion)
+        .unwrap_or(1)
+}
+
+/// Mark storage as written by this build (fresh instances need no migration).
+pub fn mark_current(env: &Env) {
+    env.storage()
+        .instance()
+        .set(&DataKey::StorageVersion, &STORAGE_VERSION);
+}
+
+/// The admin, after requiring its authorization. Reads `Config` in any stored layout, since
+/// `migrate` runs before the current layout is in place.
+pub fn require_admin(env: &Env) -> Result<Address, Error> {
+    let admin = match stored_config_v1(env) {
+        Some(old) => old.admin,
+        None => config::load(env).admin,
+    };
+    let admin = admin.ok_or(Error::NotInitialized)?;
+    admin.require_auth();
+    Ok(admin)
+}
+
+/// Run every migration step from `from_version` to `STORAGE_VERSION`. Fails with
+/// `InvalidStatus` unless `from_version` is the stored version and older than this build.
+pub fn migrate(env: &Env, from_version: u32) -> Result<u32, Error> {
+    if from_version != stored_version(env) || from_version >= STORAGE_VERSION {
+        return Err(Error::InvalidStatus);
+    }
+    for version in from_version..STORAGE_VERSION {
+        step(env, version)?;
+    }
+    mark_current(env);
+    Ok(STORAGE_VERSION)
+}
+
+/// Rewrite storage from schema `version` to `version + 1`.
+fn step(env: &Env, version: u32) -> Result<(), Error> {
+    match version {
+        1 => add_settle_hook_to_config(env),
+        _ => Err(Error::InvalidStatus),
+    }
+}
+
+/// `Config` as stored by schema 1, before `settle_hook`.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ConfigV1 {
+    pub admin: Option<Address>,
+    pub treasury: Option<Address>,
+    pub default_arbiter: Option<Address>,
+    pub max_freeze_secs: u64,
+    pub tombstone_mode: bool,
+    pub limits: Limits,
+}
+
+/// The stored `Config` if it still has the schema 1 layout. Checked by field name, since
+/// decoding a struct from a map with different fields traps instead of failing.
+fn stored_config_v1(env: &Env) -> Option<ConfigV1> {
+    let stored: Val = env.storage().instance().get(&DataKey::Config)?;
+    let fields = Map::<Symbol, Val>::try_from_val(env, &stored).ok()?;
+    if fields.contains_key(Symbol::new(env, "settle_hook")) {
+        return None;
+    }
+    ConfigV1::try_from_val(env, &stored).ok()
+}
+
+/// Schema 1 -> 2: `Config` gained `settle_hook`.
+fn add_settle_hook_to_config(env: &Env) -> Result<(), Error> {
+    if let Some(old) = stored_config_v1(env) {
+        config::save(
+            env,
+            &Config {
+                admin: old.admin,
+                treasury: old.treasury,
+                default_arbiter: old.default_arbiter,
+                max_freeze_secs: old.max_freeze_secs,
+                tombstone_mode: old.tombstone_mode,
+                limits: old.limits,
+                settle_hook: None,
+            },
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use soroban_sdk::{testutils::Address as _, BytesN};
+
+    #[test]
+    fn test_migrate_rewrites_schema_1_config_once() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let admin = Address::generate(&env);
+        // State as left by a schema 1 build.
+        env.as_contract(&fixture.contract_id, || {
+            env.storage().instance().set(
+                &DataKey::Config,
+                &ConfigV1 {
+                    admin: Some(admin.clone()),
+                    treasury: None,
+                    default_arbiter: None,
+                    max_freeze_secs: 60,
+                    tombstone_mode: true,
+                    limits: Limits::default(),
+                },
+            );
+        });
+        assert_eq!(client.get_storage_version(), 1);
+
+        assert_eq!(client.try_migrate(&2), Err(Ok(Error::InvalidStatus)));
+        assert_eq!(client.migrate(&1), STORAGE_VERSION);
+        let config = client.get_config();
+        assert_eq!(config.admin, Some(admin));
+        assert_eq!(config.max_freeze_secs, 60);
+        assert!(config.tombstone_mode);
+        assert_eq!(config.settle_hook, None);
+
+        // Already current: nothing to replay.
+        assert_eq!(client.try_migrate(&1), Err(Ok(Error::InvalidStatus)));
+        assert_eq!(client.get_storage_version(), STORAGE_VERSION);
+    }
+
+    #[test]
+    fn test_fresh_instances_start_current_and_upgrade_is_admin_only() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let hash = BytesN::from_array(&env, &[0; 32]);
+        assert_eq!(client.try_upgrade(&hash), Err(Ok(Error::NotInitialized)));
+        assert_eq!(client.try_migrate(&1), Err(Ok(Error::NotInitialized)));
+
+        client.initialize(&Address::generate(&env));
+        assert_eq!(client.get_storage_version(), STORAGE_VERSION);
+        assert_eq!(
+            client.try_migrate(&STORAGE_VERSION),
+            Err(Ok(Error::InvalidStatus))
+        );
+    }
+}
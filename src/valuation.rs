@@ -0,0 +1,181 @@
+//! USD value snapshots of stakes, for reporting in a stable unit.
+//!
+//! The admin can register one price oracle contract with `set_price_oracle`. When a vault is
+//! created the contract asks it, through the [`PriceOracle`] interface, what the stake is worth
+//! in USD (with `USD_DECIMALS` decimals) and stores the answer next to the vault as a
+//! [`UsdSnapshot`]. The snapshot never changes afterwards, so reports built on it do not move
+//! with the staked token's price during the vault's life.
+//!
+//! The snapshot is announced with `("vault_valued", vault_id)` and data
+//! `(token, amount, usd_value)`, and feeds the USD columns of creator and category stats.
+//!
+//! Valuation is informational and fails open, unlike the screener: without an oracle, or if it
+//! traps or answers with a negative value, the vault is created without a snapshot and counts
+//! as zero in the USD stats.
+
+use soroban_sdk::{contractclient, contracttype, Address, Env};
+
+use crate::{
+    events,
+    math::{self, Rounding},
+};
+
+/// Decimals of every USD value (`10_000_000` is one dollar).
+pub const USD_DECIMALS: u32 = 7;
+
+/// Storage keys of stake valuation.
+#[contracttype]
+#[derive(Clone)]
+pub enum ValuationKey {
+    /// The price oracle contract (instance storage).
+    PriceOracle,
+    /// Vault id -> its [`UsdSnapshot`].
+    UsdSnapshot(u32),
+}
+
+/// Entry points a price oracle contract implements.
+#[contractclient(name = "PriceOracleClient")]
+pub trait PriceOracle {
+    /// USD value, with `USD_DECIMALS` decimals, of `amount` of `token` at the current price.
+    fn usd_value(env: Env, token: Address, amount: i128) -> i128;
+}
+
+/// USD value of a vault's stake when it was created.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UsdSnapshot {
+    /// Stake in the vault's token, as at creation.
+    pub amount: i128,
+    /// Its USD value, with `USD_DECIMALS` decimals.
+    pub usd_value: i128,
+    pub priced_at: u64,
+}
+
+/// The configured price oracle, if any.
+pub fn oracle(env: &Env) -> Option<Address> {
+    env.storage().instance().get(&ValuationKey::PriceOracle)
+}
+
+/// USD snapshot of `vault_id`, if it was valued.
+pub fn snapshot(env: &Env, vault_id: u32) -> Option<UsdSnapshot> {
+    env.storage()
+        .persistent()
+        .get(&ValuationKey::UsdSnapshot(vault_id))
+}
+
+/// Value the new vault `vault_id` holding `amount` of `token` and store the snapshot. Returns
+/// the USD value, or `None` if there is no oracle or it gave no usable answer.
+pub fn record(env: &Env, vault_id: u32, token: &Address, amount: i128) -> Option<i128> {
+    let oracle = oracle(env)?;
+    let usd_value = match PriceOracleClient::new(env, &oracle).try_usd_value(token, &amount) {
+        Ok(Ok(value)) if value >= 0 => value,
+        _ => return None,
+    };
+    env.storage().persistent().set(
+        &ValuationKey::UsdSnapshot(vault_id),
+        &UsdSnapshot {
+            amount,
+            usd_value,
+            priced_at: env.ledger().timestamp(),
+        },
+    );
+    events::publish(
+        env,
+        "vault_valued",
+        vault_id,
+        (token.clone(), amount, usd_value),
+    );
+    Some(usd_value)
+}
+
+/// USD value, at the creation snapshot, of `part` of the stake of `vault_id` (rounded down);
+/// zero without a snapshot.
+pub fn share(env: &Env, vault_id: u32, part: i128) -> i128 {
+    snapshot(env, vault_id)
+        .and_then(|snapshot| {
+            math::mul_div(snapshot.usd_value, part, snapshot.amount, Rounding::Down)
+        })
+        .unwrap_or(0)
+}
+
+/// Drop the snapshot of an archived vault.
+pub fn remove(env: &Env, vault_id: u32) {
+    env.storage()
+        .persistent()
+        .remove(&ValuationKey::UsdSnapshot(vault_id));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::categories::Category;
+    use crate::testutils::{mint, VaultFixture};
+    use soroban_sdk::{
+        contract, contractimpl,
+        testutils::{Address as _, Ledger},
+    };
+
+    /// Prices every token at a fixed number of USD units per token unit.
+    #[contract]
+    struct FixedPrice;
+
+    #[contractimpl]
+    impl FixedPrice {
+        pub fn usd_value(env: Env, _token: Address, amount: i128) -> i128 {
+            let price: i128 = env.storage().instance().get(&()).unwrap_or(0);
+            amount * price
+        }
+
+        pub fn set_price(env: Env, price: i128) {
+            env.storage().instance().set(&(), &price);
+        }
+    }
+
+    #[test]
+    fn test_snapshot_is_fixed_at_creation_and_feeds_stats() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        client.initialize(&Address::generate(&env));
+        let oracle = env.register(FixedPrice, ());
+        let prices = FixedPriceClient::new(&env, &oracle);
+        prices.set_price(&2);
+        client.set_price_oracle(&Some(oracle.clone()));
+        assert_eq!(client.get_price_oracle(), Some(oracle));
+
+        mint(&env, &fixture.token, &fixture.creator, fixture.amount);
+        let vault_id = client.create_vault_with_category(&fixture.params(), &Category::Fitness);
+        let snapshot = client.get_usd_snapshot(&vault_id).unwrap();
+        assert_eq!(snapshot.amount, fixture.amount);
+        assert_eq!(snapshot.usd_value, 2 * fixture.amount);
+
+        // A later price move does not change the snapshot or the stats built on it.
+        prices.set_price(&5);
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        client.validate_milestone(&vault_id, &None);
+        client.release_funds(&vault_id, &fixture.token, &None);
+        assert_eq!(client.get_usd_snapshot(&vault_id), Some(snapshot));
+        let stats = client.get_creator_stats(&fixture.creator);
+        assert_eq!(stats.defended_usd, 2 * fixture.amount);
+        let category = client.get_category_stats(&Category::Fitness);
+        assert_eq!(category.staked_usd, 2 * fixture.amount);
+    }
+
+    #[test]
+    fn test_vaults_are_created_without_a_usable_price() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        client.initialize(&Address::generate(&env));
+        assert_eq!(client.get_usd_snapshot(&fixture.create_vault()), None);
+
+        let oracle = env.register(FixedPrice, ());
+        FixedPriceClient::new(&env, &oracle).set_price(&-1);
+        client.set_price_oracle(&Some(oracle));
+        let vault_id = fixture.create_vault();
+        assert_eq!(client.get_usd_snapshot(&vault_id), None);
+        env.as_contract(&fixture.contract_id, || {
+            assert_eq!(share(&env, vault_id, fixture.amount), 0);
+        });
+    }
+}
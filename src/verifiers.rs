@@ -0,0 +1,235 @@
+//! Open verifier directory: standing authorization across many vaults.
+//!
+//! A vault can name any address as its verifier without that address taking part, so a
+//! professional verifier never has to accept vaults one by one. What it needs is a way to say
+//! on which terms it takes them, and to stop taking new ones. With
+//! `register_as_open_verifier(verifier, terms_hash, fee_bps)` it publishes a hash of its
+//! off-chain terms and a fee, and joins the directory creators browse with
+//! `get_open_verifiers`. Re-registering updates the terms. Registering is open to any
+//! address, so the directory is stored one verifier per entry (like the position-keyed vault
+//! lists in [`lists`](crate::lists)) and a crowded directory costs a withdrawal nothing extra.
+//!
+//! A creator naming a registered verifier pays it `fee_bps` of the stake (rounded down) at
+//! creation, on top of the stake. `withdraw_open_verifier` takes the verifier out of the
+//! directory and stops new assignments: creating a vault that names it then fails with
+//! `NotAuthorized`. Vaults it already verifies are unaffected. Addresses that never registered
+//! can still be named, as before, without a fee.
+
+use soroban_sdk::{contracttype, Address, BytesN, Env, Vec};
+
+use crate::Error;
+
+/// Highest fee an open verifier can charge, in bps of the stake.
+pub const MAX_VERIFIER_FEE_BPS: u32 = 1_000;
+
+/// Storage keys of the open verifier directory.
+#[contracttype]
+#[derive(Clone)]
+pub enum VerifierKey {
+    /// Number of open verifiers in the directory.
+    DirectoryLen,
+    /// Directory position -> open verifier.
+    DirectoryEntry(u32),
+    /// Open verifier -> its directory position.
+    DirectorySlot(Address),
+    /// Verifier -> its [`OpenVerifier`] entry, kept after withdrawal.
+    OpenVerifier(Address),
+}
+
+/// Standing terms of a verifier that registered as open.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OpenVerifier {
+    pub verifier: Address,
+    /// Hash of the verifier's off-chain terms of service.
+    pub terms_hash: BytesN<32>,
+    /// Fee charged to the creator at creation, in bps of the stake.
+    pub fee_bps: u32,
+    pub registered_at: u64,
+    /// `false` once withdrawn: no new vault may name the verifier.
+    pub open: bool,
+}
+
+/// Entry of `verifier`, open or withdrawn, if it ever registered.
+pub fn get(env: &Env, verifier: &Address) -> Option<OpenVerifier> {
+    env.storage()
+        .persistent()
+        .get(&VerifierKey::OpenVerifier(verifier.clone()))
+}
+
+/// Number of open verifiers in the directory.
+fn directory_len(env: &Env) -> u32 {
+    env.storage()
+        .persistent()
+        .get(&VerifierKey::DirectoryLen)
+        .unwrap_or(0)
+}
+
+/// Entries of the open verifiers at directory positions `start..start + limit`.
+pub fn page(env: &Env, start: u32, limit: u32) -> Vec<OpenVerifier> {
+    let storage = env.storage().persistent();
+    let end = start.saturating_add(limit).min(directory_len(env));
+    let mut entries = Vec::new(env);
+    for position in start..end {
+        if let Some(entry) = storage
+            .get::<_, Address>(&VerifierKey::DirectoryEntry(position))
+            .and_then(|verifier| get(env, &verifier))
+        {
+            entries.push_back(entry);
+        }
+    }
+    entries
+}
+
+/// Append `verifier` to the directory.
+fn list(env: &Env, verifier: &Address) {
+    let storage = env.storage().persistent();
+    let position = directory_len(env);
+    storage.set(&VerifierKey::DirectoryEntry(position), verifier);
+    storage.set(&VerifierKey::DirectorySlot(verifier.clone()), &position);
+    storage.set(&VerifierKey::DirectoryLen, &(position + 1));
+}
+
+/// Drop `verifier` from the directory, moving the last verifier into its position.
+fn unlist(env: &Env, verifier: &Address) {
+    let storage = env.storage().persistent();
+    let slot_key = VerifierKey::DirectorySlot(verifier.clone());
+    let Some(position) = storage.get::<_, u32>(&slot_key) else {
+        return;
+    };
+    storage.remove(&slot_key);
+    let last = directory_len(env).saturating_sub(1);
+    let last_key = VerifierKey::DirectoryEntry(last);
+    if position != last {
+        if let Some(moved) = storage.get::<_, Address>(&last_key) {
+            storage.set(&VerifierKey::DirectoryEntry(position), &moved);
+            storage.set(&VerifierKey::DirectorySlot(moved), &position);
+        }
+    }
+    storage.remove(&last_key);
+    storage.set(&VerifierKey::DirectoryLen, &last);
+}
+
+/// Register (or update the terms of) `verifier` as open.
+pub fn register(
+    env: &Env,
+    verifier: &Address,
+    terms_hash: BytesN<32>,
+    fee_bps: u32,
+) -> Result<OpenVerifier, Error> {
+    if fee_bps > MAX_VERIFIER_FEE_BPS {
+        return Err(Error::InvalidFee);
+    }
+    let was_open = get(env, verifier).is_some_and(|entry| entry.open);
+    if !was_open {
+        list(env, verifier);
+    }
+    let entry = OpenVerifier {
+        verifier: verifier.clone(),
+        terms_hash,
+        fee_bps,
+        registered_at: env.ledger().timestamp(),
+        open: true,
+    };
+    env.storage()
+        .persistent()
+        .set(&VerifierKey::OpenVerifier(verifier.clone()), &entry);
+    Ok(entry)
+}
+
+/// Take `verifier` out of the directory. Fails with `NotAuthorized` if it is not open.
+pub fn withdraw(env: &Env, verifier: &Address) -> Result<(), Error> {
+    let mut entry = get(env, verifier)
+        .filter(|entry| entry.open)
+        .ok_or(Error::NotAuthorized)?;
+    entry.open = false;
+    env.storage()
+        .persistent()
+        .set(&VerifierKey::OpenVerifier(verifier.clone()), &entry);
+    unlist(env, verifier);
+    Ok(())
+}
+
+/// Fee rate a new vault naming `verifier` pays it: the registered rate, or zero for an address
+/// that never registered. Fails with `NotAuthorized` if the verifier withdrew.
+pub fn assignment_fee_bps(env: &Env, verifier: &Address) -> Result<u32, Error> {
+    match get(env, verifier) {
+        Some(entry) if !entry.open => Err(Error::NotAuthorized),
+        Some(entry) => Ok(entry.fee_bps),
+        None => Ok(0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::{mint, VaultFixture};
+    use soroban_sdk::{testutils::Address as _, token::TokenClient};
+
+    #[test]
+    fn test_open_verifier_is_listed_and_paid_at_creation() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        let terms = BytesN::from_array(&env, &[7; 32]);
+        assert_eq!(
+            client.try_register_as_open_verifier(
+                &fixture.verifier,
+                &terms,
+                &(MAX_VERIFIER_FEE_BPS + 1)
+            ),
+            Err(Ok(Error::InvalidFee))
+        );
+        client.register_as_open_verifier(&fixture.verifier, &terms, &100);
+        client.register_as_open_verifier(&Address::generate(&env), &terms, &0);
+        let listed = client.get_open_verifiers(&0, &10);
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed.get(0).unwrap().verifier, fixture.verifier);
+        assert_eq!(client.get_open_verifiers(&1, &10).len(), 1);
+
+        let fee = fixture.amount / 100;
+        mint(&env, &fixture.token, &fixture.creator, fee);
+        fixture.create_vault();
+        assert_eq!(
+            TokenClient::new(&env, &fixture.token).balance(&fixture.verifier),
+            fee
+        );
+        // Terms can be updated in place.
+        client.register_as_open_verifier(&fixture.verifier, &terms, &0);
+        assert_eq!(client.get_open_verifiers(&0, &10).len(), 2);
+        fixture.create_vault();
+    }
+
+    #[test]
+    fn test_withdrawn_verifier_takes_no_new_vaults() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        assert_eq!(
+            client.try_withdraw_open_verifier(&fixture.verifier),
+            Err(Ok(Error::NotAuthorized))
+        );
+        client.register_as_open_verifier(
+            &fixture.verifier,
+            &BytesN::from_array(&env, &[1; 32]),
+            &0,
+        );
+        let existing = fixture.create_vault();
+        let other = Address::generate(&env);
+        client.register_as_open_verifier(&other, &BytesN::from_array(&env, &[2; 32]), &0);
+        client.withdraw_open_verifier(&fixture.verifier);
+        // The last verifier moves into the freed position.
+        let listed = client.get_open_verifiers(&0, &10);
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed.get(0).unwrap().verifier, other);
+        client.withdraw_open_verifier(&other);
+        assert_eq!(client.get_open_verifiers(&0, &10).len(), 0);
+        assert!(!client.get_open_verifier(&fixture.verifier).unwrap().open);
+        mint(&env, &fixture.token, &fixture.creator, fixture.amount);
+        assert_eq!(
+            client.try_create_vault_with_params(&fixture.params()),
+            Err(Ok(Error::NotAuthorized))
+        );
+        assert!(client.get_vault_state(&existing).is_some());
+    }
+}
@@ -0,0 +1,140 @@
+//! Contract version, build identifier and supported feature modules.
+//!
+//! Deployed contracts on testnet and mainnet drift apart, so frontends and composing contracts
+//! feature-detect instead of assuming one build:
+//!
+//! - `version()` is the semantic version of the crate the wasm was built from. The major version
+//!   changes with any breaking change to entry points, stored types or event schemas.
+//! - `build_id()` identifies the exact wasm build. Release builds set `DISCIPLR_BUILD_ID`
+//!   (e.g. to the git commit) at compile time; local builds report `dev`.
+//! - `supported_features()` lists the feature modules this build implements, like an interface
+//!   id. Names are stable once published and never reused for a different behaviour; new
+//!   modules append to the list.
+
+use soroban_sdk::{Env, String, Symbol, Vec};
+
+/// Semantic version of this contract build, `(major, minor, patch)`.
+pub const CONTRACT_VERSION: (u32, u32, u32) = (0, 1, 0);
+
+/// Build identifier, from `DISCIPLR_BUILD_ID` at compile time.
+pub const BUILD_ID: &str = match option_env!("DISCIPLR_BUILD_ID") {
+    Some(build_id) => build_id,
+    None => "dev",
+};
+
+/// Feature modules implemented by this build, in the order they were added.
+pub const FEATURES: [&str; 70] = [
+    "vaults",
+    "verifier",
+    "deterministic_ids",
+    "pair_vaults",
+    "hidden_failure",
+    "zk_proofs",
+    "attestation",
+    "public_index",
+    "leaderboard",
+    "tombstones",
+    "compliance_freeze",
+    "blocklist",
+    "rescue",
+    "disputes",
+    "teams",
+    "refund_curves",
+    "snooze",
+    "pause",
+    "bonus",
+    "retries",
+    "abandonment",
+    "beneficiary",
+    "recovery",
+    "fee_overrides",
+    "settlement_receipts",
+    "keeper_batch",
+    "creator_caps",
+    "amendments",
+    "clone_vault",
+    "status_index",
+    "review_queue",
+    "exposure",
+    "settle_hook",
+    "cohort_factory",
+    "migration",
+    "upgrades",
+    "keeper_bounties",
+    "vault_bounty",
+    "quote_fees",
+    "audit_vault",
+    "cancel_hold",
+    "reduce_stake",
+    "approved_destinations",
+    "burn_destination",
+    "settlement_cosign",
+    "outflow_breaker",
+    "screener",
+    "admin_timelock",
+    "governance",
+    "fee_treasury",
+    "verifier_delegation",
+    "open_verifiers",
+    "categories",
+    "hash_lookup",
+    "activity_log",
+    "goal_oracle",
+    "service_attestation",
+    "check_ins",
+    "usd_snapshots",
+    "yield_strategies",
+    "forfeit_vesting",
+    "success_streams",
+    "verifier_deposits",
+    "schedule_events",
+    "results_registry",
+    "address_topics",
+    "stable_settlement",
+    "bulk_cancel",
+    "duplicate_guard",
+    "principal_protection",
+];
+
+/// `FEATURES` as symbols.
+pub fn features(env: &Env) -> Vec<Symbol> {
+    let mut features = Vec::new(env);
+    for feature in FEATURES {
+        features.push_back(Symbol::new(env, feature));
+    }
+    features
+}
+
+/// `BUILD_ID` as a contract string.
+pub fn build_id(env: &Env) -> String {
+    String::from_str(env, BUILD_ID)
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use std::string::ToString;
+
+    #[test]
+    fn test_version_matches_crate_version() {
+        let (major, minor, patch) = CONTRACT_VERSION;
+        assert_eq!(major.to_string(), env!("CARGO_PKG_VERSION_MAJOR"));
+        assert_eq!(minor.to_string(), env!("CARGO_PKG_VERSION_MINOR"));
+        assert_eq!(patch.to_string(), env!("CARGO_PKG_VERSION_PATCH"));
+    }
+
+    #[test]
+    fn test_feature_detection() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        assert_eq!(client.version(), CONTRACT_VERSION);
+        assert_eq!(client.build_id(), String::from_str(&env, BUILD_ID));
+        assert_eq!(client.supported_features().len(), FEATURES.len() as u32);
+        assert!(client.supports_feature(&Symbol::new(&env, "disputes")));
+        assert!(!client.supports_feature(&Symbol::new(&env, "multi_milestone")));
+    }
+}
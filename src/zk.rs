@@ -0,0 +1,248 @@
+//! Groth16 proof verification over BLS12-381 for private milestones.
+//!
+//! A vault created with a verifying key settles its milestone by proof instead of by a
+//! verifier signature, so the milestone content never appears on-chain. The circuit's first
+//! public input is always the vault's `milestone_hash` (reduced into the scalar field); the
+//! contract prepends it, so a proof generated for one vault cannot be replayed on another.
+
+use soroban_sdk::{
+    contracttype,
+    crypto::bls12_381::{Fr, G1Affine, G2Affine},
+    vec, BytesN, Env, Vec,
+};
+
+/// Groth16 verifying key, points in uncompressed SDK encoding.
+///
+/// `ic` has one entry per public input plus one (the constant term).
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Groth16VerifyingKey {
+    pub alpha_g1: BytesN<96>,
+    pub beta_g2: BytesN<192>,
+    pub gamma_g2: BytesN<192>,
+    pub delta_g2: BytesN<192>,
+    pub ic: Vec<BytesN<96>>,
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Groth16Proof {
+    pub a: BytesN<96>,
+    pub b: BytesN<192>,
+    pub c: BytesN<96>,
+}
+
+/// Public inputs the contract checks a proof against: `milestone_hash` followed by
+/// `extra_inputs`.
+pub fn public_inputs(milestone_hash: &BytesN<32>, extra_inputs: &Vec<BytesN<32>>) -> Vec<Fr> {
+    let env = milestone_hash.env();
+    let mut inputs = vec![env, Fr::from_bytes(milestone_hash.clone())];
+    for input in extra_inputs.iter() {
+        inputs.push_back(Fr::from_bytes(input));
+    }
+    inputs
+}
+
+/// Check `e(-A, B) · e(alpha, beta) · e(vk_x, gamma) · e(C, delta) == 1` where
+/// `vk_x = ic[0] + Σ inputs[i] · ic[i + 1]`.
+///
+/// Returns `false` (never panics on shape) if `inputs` does not match `vk.ic`.
+pub fn verify_groth16(
+    env: &Env,
+    vk: &Groth16VerifyingKey,
+    proof: &Groth16Proof,
+    inputs: &Vec<Fr>,
+) -> bool {
+    if vk.ic.len() != inputs.len() + 1 {
+        return false;
+    }
+    let bls = env.crypto().bls12_381();
+
+    let mut vk_x = G1Affine::from_bytes(vk.ic.get_unchecked(0));
+    if !inputs.is_empty() {
+        let mut points = Vec::new(env);
+        for i in 1..vk.ic.len() {
+            points.push_back(G1Affine::from_bytes(vk.ic.get_unchecked(i)));
+        }
+        vk_x = bls.g1_add(&vk_x, &bls.g1_msm(points, inputs.clone()));
+    }
+
+    let g1 = vec![
+        env,
+        -G1Affine::from_bytes(proof.a.clone()),
+        G1Affine::from_bytes(vk.alpha_g1.clone()),
+        vk_x,
+        G1Affine::from_bytes(proof.c.clone()),
+    ];
+    let g2 = vec![
+        env,
+        G2Affine::from_bytes(proof.b.clone()),
+        G2Affine::from_bytes(vk.beta_g2.clone()),
+        G2Affine::from_bytes(vk.gamma_g2.clone()),
+        G2Affine::from_bytes(vk.delta_g2.clone()),
+    ];
+    bls.pairing_check(g1, g2)
+}
+
+/// Construct a (verifying key, proof) pair that satisfies the Groth16 equation for `inputs`,
+/// using known discrete logs instead of a real circuit. Test-only.
+#[cfg(test)]
+pub(crate) fn synthetic_proof(env: &Env, inputs: &Vec<Fr>) -> (Groth16VerifyingKey, Groth16Proof) {
+    use soroban_sdk::{Bytes, U256};
+
+    let bls = env.crypto().bls12_381();
+    let dst = Bytes::from_slice(
+        env,
+        b"DISCIPLR-TEST-V01-CS01-with-BLS12381G1_XMD:SHA-256_SSWU_RO_",
+    );
+    let g = bls.hash_to_g1(&Bytes::from_slice(env, b"g1"), &dst);
+    let h = bls.hash_to_g2(&Bytes::from_slice(env, b"g2"), &dst);
+    let fr = |n: u32| Fr::from_u256(U256::from_u32(env, n));
+
+    let (a, b, c, d, k) = (fr(3), fr(5), fr(7), fr(11), fr(13));
+    let mut ic = Vec::new(env);
+    let mut ic_scalars = Vec::new(env);
+    for i in 0..=inputs.len() {
+        let s = fr(17 + i);
+        ic.push_back((g.clone() * s.clone()).to_bytes());
+        ic_scalars.push_back(s);
+    }
+    // log(vk_x) = ic_0 + Σ x_i · ic_{i+1}
+    let mut vk_x = ic_scalars.get_unchecked(0);
+    for (i, x) in inputs.iter().enumerate() {
+        vk_x = vk_x + x * ic_scalars.get_unchecked(i as u32 + 1);
+    }
+    // With B = h: log(A) = a·b + vk_x·c + k·d.
+    let a_scalar = a.clone() * b.clone() + vk_x * c.clone() + k.clone() * d.clone();
+
+    let vk = Groth16VerifyingKey {
+        alpha_g1: (g.clone() * a).to_bytes(),
+        beta_g2: (h.clone() * b).to_bytes(),
+        gamma_g2: (h.clone() * c).to_bytes(),
+        delta_g2: (h.clone() * d).to_bytes(),
+        ic,
+    };
+    let proof = Groth16Proof {
+        a: (g.clone() * a_scalar).to_bytes(),
+        b: h.to_bytes(),
+        c: (g * k).to_bytes(),
+    };
+    (vk, proof)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutils::VaultFixture;
+    use crate::{ActivityAction, Error, VaultStatus};
+    use soroban_sdk::testutils::Ledger;
+
+    #[test]
+    fn test_verify_groth16_accepts_valid_and_rejects_tampered_inputs() {
+        let env = Env::default();
+        env.cost_estimate().budget().reset_unlimited();
+        let hash = BytesN::from_array(&env, &[4u8; 32]);
+        let inputs = public_inputs(&hash, &vec![&env, BytesN::from_array(&env, &[9u8; 32])]);
+        let (vk, proof) = synthetic_proof(&env, &inputs);
+
+        assert!(verify_groth16(&env, &vk, &proof, &inputs));
+
+        let other = public_inputs(&hash, &vec![&env, BytesN::from_array(&env, &[8u8; 32])]);
+        assert!(!verify_groth16(&env, &vk, &proof, &other));
+        assert!(!verify_groth16(
+            &env,
+            &vk,
+            &proof,
+            &public_inputs(&hash, &Vec::new(&env))
+        ));
+    }
+
+    #[test]
+    fn test_zk_vault_validates_only_with_proof() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        env.cost_estimate().budget().reset_unlimited();
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        let params = fixture.params();
+        let inputs = public_inputs(&params.milestone_hash, &Vec::new(&env));
+        let (vk, proof) = synthetic_proof(&env, &inputs);
+
+        crate::testutils::mint(&env, &fixture.token, &params.creator, params.amount);
+        let vault_id = client.create_vault_with_zk(&params, &vk);
+        assert_eq!(client.get_zk_verifying_key(&vault_id), Some(vk.clone()));
+
+        // The verifier signature path is closed for proof-settled vaults.
+        assert_eq!(
+            client.try_validate_milestone(&vault_id, &None),
+            Err(Ok(Error::NotAuthorized))
+        );
+
+        let bad = Groth16Proof {
+            c: proof.a.clone(),
+            ..proof.clone()
+        };
+        assert_eq!(
+            client.try_validate_with_proof(&vault_id, &bad, &Vec::new(&env)),
+            Err(Ok(Error::InvalidProof))
+        );
+
+        assert!(client.validate_with_proof(&vault_id, &proof, &Vec::new(&env)));
+        let vault = client.get_vault_state(&vault_id).unwrap();
+        assert!(vault.milestone_validated);
+        assert_eq!(vault.status, VaultStatus::Active);
+        let last = client.get_vault_history(&vault_id).last().unwrap();
+        assert_eq!((last.action, last.actor), (ActivityAction::Validated, None));
+
+        client.release_funds(&vault_id, &fixture.token, &None);
+    }
+
+    #[test]
+    fn test_proof_cannot_be_resubmitted_once_validated() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let client = fixture.client();
+        env.cost_estimate().budget().reset_unlimited();
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        let params = fixture.params();
+        let inputs = public_inputs(&params.milestone_hash, &Vec::new(&env));
+        let (vk, proof) = synthetic_proof(&env, &inputs);
+        crate::testutils::mint(&env, &fixture.token, &params.creator, params.amount);
+        let vault_id = client.create_vault_with_zk(&params, &vk);
+        crate::testutils::mint(&env, &fixture.token, &params.creator, 100);
+        client.fund_bonus(&vault_id, &params.creator, &100, &10);
+
+        assert!(client.validate_with_proof(&vault_id, &proof, &Vec::new(&env)));
+        let validated_at = client.get_bonus(&vault_id).unwrap().validated_at;
+        let history = client.get_vault_history(&vault_id).len();
+        env.ledger().set_timestamp(fixture.start_timestamp + 10);
+        assert_eq!(
+            client.try_validate_with_proof(&vault_id, &proof, &Vec::new(&env)),
+            Err(Ok(Error::VaultNotActive))
+        );
+        assert_eq!(client.get_vault_history(&vault_id).len(), history);
+        assert_eq!(
+            client.get_bonus(&vault_id).unwrap().validated_at,
+            validated_at
+        );
+    }
+
+    #[test]
+    fn test_validate_with_proof_requires_zk_vault() {
+        let env = Env::default();
+        let fixture = VaultFixture::new(&env);
+        let vault_id = fixture.create_vault();
+        env.ledger().set_timestamp(fixture.start_timestamp);
+        let proof = Groth16Proof {
+            a: BytesN::from_array(&env, &[0u8; 96]),
+            b: BytesN::from_array(&env, &[0u8; 192]),
+            c: BytesN::from_array(&env, &[0u8; 96]),
+        };
+        assert_eq!(
+            fixture
+                .client()
+                .try_validate_with_proof(&vault_id, &proof, &Vec::new(&env)),
+            Err(Ok(Error::NotAuthorized))
+        );
+    }
+}
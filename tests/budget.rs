@@ -0,0 +1,125 @@
+//! Cost regression tests for hot paths.
+//!
+//! Each test runs one entry point with every index it touches populated (public list, expiry
+//! buckets, leaderboard) and fails if CPU instructions or memory exceed the recorded ceiling.
+//! Ceilings sit roughly 20% above the measured cost; when a change legitimately moves a number,
+//! update the constant from the measured cost the failing assert reports.
+
+use disciplr_vault::{DisciplrVault, DisciplrVaultClient, VaultParams};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    token::StellarAssetClient,
+    Address, BytesN, Env,
+};
+
+struct Ceiling {
+    cpu: u64,
+    mem: u64,
+}
+
+const CREATE_VAULT: Ceiling = Ceiling {
+    cpu: 4_570_000,
+    mem: 1_490_000,
+};
+const VALIDATE_MILESTONE: Ceiling = Ceiling {
+    cpu: 2_140_000,
+    mem: 670_000,
+};
+const RELEASE_FUNDS: Ceiling = Ceiling {
+    cpu: 3_910_000,
+    mem: 1_250_000,
+};
+const REDIRECT_FUNDS: Ceiling = Ceiling {
+    cpu: 3_850_000,
+    mem: 1_230_000,
+};
+
+/// Vaults created before the measured call so the indexes are not empty.
+const PRELOADED_VAULTS: u32 = 20;
+
+struct Bench<'a> {
+    env: Env,
+    client: DisciplrVaultClient<'a>,
+    token: Address,
+}
+
+impl Bench<'_> {
+    fn new() -> Self {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().set_timestamp(100);
+        let client = DisciplrVaultClient::new(&env, &env.register(DisciplrVault, ()));
+        let token = env
+            .register_stellar_asset_contract_v2(Address::generate(&env))
+            .address();
+        let bench = Bench { env, client, token };
+        for _ in 0..PRELOADED_VAULTS {
+            bench.create();
+        }
+        bench
+    }
+
+    fn params(&self) -> VaultParams {
+        let creator = Address::generate(&self.env);
+        StellarAssetClient::new(&self.env, &self.token).mint(&creator, &1_000_000);
+        VaultParams {
+            usdc_token: self.token.clone(),
+            creator,
+            amount: 1_000_000,
+            start_timestamp: 100,
+            end_timestamp: 1_000,
+            milestone_hash: BytesN::from_array(&self.env, &[1u8; 32]),
+            verifier: Some(Address::generate(&self.env)),
+            success_destination: Address::generate(&self.env),
+            failure_destination: Address::generate(&self.env),
+            is_public: true,
+            arbiter: None,
+        }
+    }
+
+    fn create(&self) -> u32 {
+        self.client.create_vault_with_params(&self.params())
+    }
+
+    /// Assert the cost of the most recent top-level invocation is within `ceiling`.
+    fn assert_within(&self, name: &str, ceiling: &Ceiling) {
+        let budget = self.env.cost_estimate().budget();
+        let (cpu, mem) = (budget.cpu_instruction_cost(), budget.memory_bytes_cost());
+        assert!(cpu <= ceiling.cpu, "{name}: cpu {cpu} > {}", ceiling.cpu);
+        assert!(mem <= ceiling.mem, "{name}: mem {mem} > {}", ceiling.mem);
+    }
+}
+
+#[test]
+fn create_vault_cost() {
+    let bench = Bench::new();
+    let params = bench.params();
+    bench.client.create_vault_with_params(&params);
+    bench.assert_within("create_vault", &CREATE_VAULT);
+}
+
+#[test]
+fn validate_milestone_cost() {
+    let bench = Bench::new();
+    let vault_id = bench.create();
+    bench.client.validate_milestone(&vault_id, &None);
+    bench.assert_within("validate_milestone", &VALIDATE_MILESTONE);
+}
+
+#[test]
+fn release_funds_cost() {
+    let bench = Bench::new();
+    let vault_id = bench.create();
+    bench.client.validate_milestone(&vault_id, &None);
+    bench.client.release_funds(&vault_id, &bench.token, &None);
+    bench.assert_within("release_funds", &RELEASE_FUNDS);
+}
+
+#[test]
+fn redirect_funds_cost() {
+    let bench = Bench::new();
+    let vault_id = bench.create();
+    bench.env.ledger().set_timestamp(1_000);
+    bench.client.redirect_funds(&vault_id, &bench.token, &None);
+    bench.assert_within("redirect_funds", &REDIRECT_FUNDS);
+}
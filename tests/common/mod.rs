@@ -0,0 +1,171 @@
+//! Shared mock contracts for the integration tests.
+
+#![allow(dead_code)]
+
+use soroban_sdk::{
+    auth::Context,
+    contract, contracterror, contractimpl, contracttype,
+    crypto::Hash,
+    xdr::{
+        InvokeContractArgs, ScSymbol, ScVal, SorobanAddressCredentials, SorobanAuthorizationEntry,
+        SorobanAuthorizedFunction, SorobanAuthorizedInvocation, SorobanCredentials, VecM,
+    },
+    Address, Env, Symbol, Vec,
+};
+
+#[contracttype]
+enum MockTokenKey {
+    Balance(Address),
+}
+
+/// Minimal SEP-41-style token: just enough (`mint`, `balance`, `transfer`) for escrow flows.
+///
+/// Kept independent of the Stellar asset contract so the vault is exercised against a token
+/// implementation it knows nothing about.
+#[contract]
+pub struct MockToken;
+
+#[contractimpl]
+impl MockToken {
+    pub fn mint(env: Env, to: Address, amount: i128) {
+        let balance = Self::balance(env.clone(), to.clone());
+        env.storage()
+            .persistent()
+            .set(&MockTokenKey::Balance(to), &(balance + amount));
+    }
+
+    pub fn balance(env: Env, id: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&MockTokenKey::Balance(id))
+            .unwrap_or(0)
+    }
+
+    pub fn transfer(env: Env, from: Address, to: Address, amount: i128) {
+        from.require_auth();
+        assert!(amount >= 0, "negative amount");
+        let from_balance = Self::balance(env.clone(), from.clone());
+        assert!(from_balance >= amount, "insufficient balance");
+        env.storage()
+            .persistent()
+            .set(&MockTokenKey::Balance(from), &(from_balance - amount));
+        let to_balance = Self::balance(env.clone(), to.clone());
+        env.storage()
+            .persistent()
+            .set(&MockTokenKey::Balance(to), &(to_balance + amount));
+    }
+}
+
+#[contracttype]
+enum MockAccountKey {
+    Approve,
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(u32)]
+pub enum MockAccountError {
+    Declined = 1,
+}
+
+/// Custom account contract whose `__check_auth` approves or declines based on a stored flag.
+///
+/// Stands in for multisig or smart-wallet verifiers: tests flip the flag to simulate a quorum
+/// that signs or refuses.
+#[contract]
+pub struct MockAccount;
+
+#[contractimpl]
+impl MockAccount {
+    pub fn set_approve(env: Env, approve: bool) {
+        env.storage()
+            .instance()
+            .set(&MockAccountKey::Approve, &approve);
+    }
+
+    #[allow(non_snake_case)]
+    pub fn __check_auth(
+        env: Env,
+        _signature_payload: Hash<32>,
+        _signature: (),
+        _auth_contexts: Vec<Context>,
+    ) -> Result<(), MockAccountError> {
+        let approve: bool = env
+            .storage()
+            .instance()
+            .get(&MockAccountKey::Approve)
+            .unwrap_or(false);
+        if approve {
+            Ok(())
+        } else {
+            Err(MockAccountError::Declined)
+        }
+    }
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct OracleReport {
+    pub value: i128,
+    pub timestamp: u64,
+}
+
+#[contracttype]
+enum MockOracleKey {
+    Report(Symbol),
+}
+
+/// Price/metric oracle returning the last report per feed, with its observation time so callers
+/// can apply their own staleness policy.
+#[contract]
+pub struct MockOracle;
+
+#[contractimpl]
+impl MockOracle {
+    pub fn report(env: Env, feed: Symbol, value: i128, timestamp: u64) {
+        env.storage().persistent().set(
+            &MockOracleKey::Report(feed),
+            &OracleReport { value, timestamp },
+        );
+    }
+
+    pub fn latest(env: Env, feed: Symbol) -> Option<OracleReport> {
+        env.storage().persistent().get(&MockOracleKey::Report(feed))
+    }
+
+    /// Last report for `feed` if it is no older than `max_age` seconds at the current ledger time.
+    pub fn latest_fresh(env: Env, feed: Symbol, max_age: u64) -> Option<OracleReport> {
+        let report = Self::latest(env.clone(), feed)?;
+        let age = env.ledger().timestamp().saturating_sub(report.timestamp);
+        (age <= max_age).then_some(report)
+    }
+}
+
+/// Authorize exactly one `function(args)` call on `contract` as custom account `account`.
+///
+/// Unlike `mock_auths`, this routes through the account's real `__check_auth`.
+pub fn authorize_as_account(
+    env: &Env,
+    account: &Address,
+    contract: &Address,
+    function: &str,
+    args: std::vec::Vec<ScVal>,
+) {
+    let entry = SorobanAuthorizationEntry {
+        credentials: SorobanCredentials::Address(SorobanAddressCredentials {
+            address: account.into(),
+            nonce: 0,
+            signature_expiration_ledger: env.ledger().sequence() + 100,
+            signature: ScVal::Void,
+        }),
+        root_invocation: SorobanAuthorizedInvocation {
+            function: SorobanAuthorizedFunction::ContractFn(InvokeContractArgs {
+                contract_address: contract.into(),
+                function_name: ScSymbol(function.try_into().unwrap()),
+                args: args.try_into().unwrap(),
+            }),
+            sub_invocations: VecM::default(),
+        },
+    };
+    env.set_auths(&[entry]);
+}
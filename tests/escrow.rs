@@ -0,0 +1,173 @@
+//! End-to-end escrow flows against a standalone mock token, checking real balances on every leg.
+
+mod common;
+
+use common::{MockToken, MockTokenClient};
+use disciplr_vault::{
+    DisciplrVault, DisciplrVaultClient, DisputeRuling, Error, VaultParams, VaultStatus,
+};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    Address, BytesN, Env,
+};
+
+const STAKE: i128 = 1_000_000;
+
+struct Escrow<'a> {
+    env: Env,
+    vault: DisciplrVaultClient<'a>,
+    token: MockTokenClient<'a>,
+    params: VaultParams,
+}
+
+impl Escrow<'_> {
+    fn new() -> Self {
+        let env = Env::default();
+        env.mock_all_auths();
+        env.ledger().set_timestamp(100);
+
+        let vault = DisciplrVaultClient::new(&env, &env.register(DisciplrVault, ()));
+        let token = MockTokenClient::new(&env, &env.register(MockToken, ()));
+        let params = VaultParams {
+            usdc_token: token.address.clone(),
+            creator: Address::generate(&env),
+            amount: STAKE,
+            start_timestamp: 100,
+            end_timestamp: 1_000,
+            milestone_hash: BytesN::from_array(&env, &[1u8; 32]),
+            verifier: Some(Address::generate(&env)),
+            success_destination: Address::generate(&env),
+            failure_destination: Address::generate(&env),
+            is_public: false,
+            arbiter: None,
+        };
+        token.mint(&params.creator, &STAKE);
+
+        Escrow {
+            env,
+            vault,
+            token,
+            params,
+        }
+    }
+
+    fn create(&self) -> u32 {
+        let vault_id = self.vault.create_vault_with_params(&self.params);
+        assert_eq!(self.token.balance(&self.params.creator), 0);
+        assert_eq!(self.token.balance(&self.vault.address), STAKE);
+        vault_id
+    }
+}
+
+#[test]
+fn release_moves_stake_to_success_destination() {
+    let escrow = Escrow::new();
+    let vault_id = escrow.create();
+
+    escrow.vault.validate_milestone(&vault_id, &None);
+    escrow
+        .vault
+        .release_funds(&vault_id, &escrow.token.address, &None);
+
+    assert_eq!(escrow.token.balance(&escrow.vault.address), 0);
+    assert_eq!(
+        escrow.token.balance(&escrow.params.success_destination),
+        STAKE
+    );
+}
+
+#[test]
+fn redirect_moves_stake_to_failure_destination() {
+    let escrow = Escrow::new();
+    let vault_id = escrow.create();
+
+    escrow
+        .env
+        .ledger()
+        .set_timestamp(escrow.params.end_timestamp);
+    escrow
+        .vault
+        .redirect_funds(&vault_id, &escrow.token.address, &None);
+
+    assert_eq!(escrow.token.balance(&escrow.vault.address), 0);
+    assert_eq!(
+        escrow.token.balance(&escrow.params.failure_destination),
+        STAKE
+    );
+}
+
+#[test]
+fn cancel_refunds_creator() {
+    let escrow = Escrow::new();
+    let vault_id = escrow.create();
+
+    escrow
+        .vault
+        .cancel_vault(&vault_id, &escrow.token.address, &None);
+
+    assert_eq!(escrow.token.balance(&escrow.vault.address), 0);
+    assert_eq!(escrow.token.balance(&escrow.params.creator), STAKE);
+    assert_eq!(
+        escrow.vault.get_vault_state(&vault_id).unwrap().status,
+        VaultStatus::Cancelled
+    );
+}
+
+#[test]
+fn dispute_pays_arbiter_fee_and_remainder() {
+    let mut escrow = Escrow::new();
+    let admin = Address::generate(&escrow.env);
+    let arbiter = Address::generate(&escrow.env);
+    escrow.vault.initialize(&admin);
+    escrow.vault.register_arbiter(&arbiter, &250);
+    escrow.params.arbiter = Some(arbiter.clone());
+    let vault_id = escrow.create();
+
+    escrow.vault.open_dispute(&vault_id, &escrow.params.creator);
+    escrow
+        .vault
+        .resolve_dispute(&vault_id, &DisputeRuling::Release, &escrow.token.address);
+
+    let fee = STAKE * 250 / 10_000;
+    assert_eq!(escrow.token.balance(&arbiter), fee);
+    assert_eq!(
+        escrow.token.balance(&escrow.params.success_destination),
+        STAKE - fee
+    );
+    assert_eq!(escrow.token.balance(&escrow.vault.address), 0);
+}
+
+#[test]
+fn settling_with_another_token_is_rejected_and_moves_nothing() {
+    let escrow = Escrow::new();
+    let vault_id = escrow.create();
+    let other = MockTokenClient::new(&escrow.env, &escrow.env.register(MockToken, ()));
+
+    assert_eq!(
+        escrow
+            .vault
+            .try_cancel_vault(&vault_id, &other.address, &None),
+        Err(Ok(Error::InvalidToken))
+    );
+    assert_eq!(escrow.token.balance(&escrow.vault.address), STAKE);
+}
+
+#[test]
+fn burn_destination_locks_stake_when_token_cannot_burn() {
+    let mut escrow = Escrow::new();
+    let burn = escrow.vault.get_burn_address();
+    escrow.params.failure_destination = burn.clone();
+    let vault_id = escrow.create();
+
+    escrow
+        .env
+        .ledger()
+        .set_timestamp(escrow.params.end_timestamp);
+    escrow
+        .vault
+        .redirect_funds(&vault_id, &escrow.token.address, &None);
+
+    // The mock token has no `burn`: the stake is parked where nothing can move it.
+    assert_eq!(escrow.token.balance(&escrow.vault.address), 0);
+    assert_eq!(escrow.token.balance(&burn), STAKE);
+}
@@ -0,0 +1,113 @@
+//! Verification modes exercised end-to-end against mock verifier contracts.
+
+mod common;
+
+use common::{
+    authorize_as_account, MockAccount, MockAccountClient, MockOracle, MockOracleClient, MockToken,
+    MockTokenClient, OracleReport,
+};
+use disciplr_vault::{DisciplrVault, DisciplrVaultClient, VaultParams, VaultStatus};
+use soroban_sdk::{
+    testutils::{Address as _, Ledger},
+    xdr::ScVal,
+    Address, BytesN, Env, Symbol,
+};
+
+struct Verification<'a> {
+    env: Env,
+    vault: DisciplrVaultClient<'a>,
+    account: MockAccountClient<'a>,
+    vault_id: u32,
+}
+
+/// Create a vault whose verifier is a `MockAccount` custom account contract.
+fn vault_with_account_verifier<'a>() -> Verification<'a> {
+    let env = Env::default();
+    env.mock_all_auths();
+    env.ledger().set_timestamp(100);
+
+    let vault = DisciplrVaultClient::new(&env, &env.register(DisciplrVault, ()));
+    let token = MockTokenClient::new(&env, &env.register(MockToken, ()));
+    let account = MockAccountClient::new(&env, &env.register(MockAccount, ()));
+    let creator = Address::generate(&env);
+    token.mint(&creator, &1_000);
+
+    let vault_id = vault.create_vault_with_params(&VaultParams {
+        usdc_token: token.address.clone(),
+        creator,
+        amount: 1_000,
+        start_timestamp: 100,
+        end_timestamp: 1_000,
+        milestone_hash: BytesN::from_array(&env, &[1u8; 32]),
+        verifier: Some(account.address.clone()),
+        success_destination: Address::generate(&env),
+        failure_destination: Address::generate(&env),
+        is_public: false,
+        arbiter: None,
+    });
+
+    Verification {
+        env,
+        vault,
+        account,
+        vault_id,
+    }
+}
+
+fn authorize_validate(v: &Verification) {
+    authorize_as_account(
+        &v.env,
+        &v.account.address,
+        &v.vault.address,
+        "validate_milestone",
+        std::vec![ScVal::U32(v.vault_id), ScVal::Void],
+    );
+}
+
+#[test]
+fn custom_account_verifier_that_approves_validates_milestone() {
+    let v = vault_with_account_verifier();
+    v.account.set_approve(&true);
+
+    authorize_validate(&v);
+    v.vault.validate_milestone(&v.vault_id, &None);
+
+    let state = v.vault.get_vault_state(&v.vault_id).unwrap();
+    assert!(state.milestone_validated);
+    assert_eq!(state.status, VaultStatus::Active);
+}
+
+#[test]
+fn custom_account_verifier_that_declines_cannot_validate() {
+    let v = vault_with_account_verifier();
+    v.account.set_approve(&false);
+
+    authorize_validate(&v);
+    assert!(v.vault.try_validate_milestone(&v.vault_id, &None).is_err());
+    assert!(
+        !v.vault
+            .get_vault_state(&v.vault_id)
+            .unwrap()
+            .milestone_validated
+    );
+}
+
+#[test]
+fn mock_oracle_reports_go_stale() {
+    let env = Env::default();
+    let oracle = MockOracleClient::new(&env, &env.register(MockOracle, ()));
+    let feed = Symbol::new(&env, "steps");
+    env.ledger().set_timestamp(1_000);
+
+    assert_eq!(oracle.latest(&feed), None);
+    oracle.report(&feed, &12_000, &900);
+    let report = OracleReport {
+        value: 12_000,
+        timestamp: 900,
+    };
+    assert_eq!(oracle.latest_fresh(&feed, &100), Some(report.clone()));
+
+    env.ledger().set_timestamp(1_001);
+    assert_eq!(oracle.latest_fresh(&feed, &100), None);
+    assert_eq!(oracle.latest(&feed), Some(report));
+}
@@ -0,0 +1,187 @@
+//! Create-and-fund with a single signature.
+//!
+//! Wallets sign one authorization tree per transaction. These tests authorize exactly what a
+//! creator's wallet would sign, with no `mock_all_auths`, and check that one entry rooted at
+//! the create call, with the stake transfer nested under it, is enough: no separate `approve`
+//! step, and no allowance left behind.
+
+use disciplr_vault::{DisciplrVault, DisciplrVaultClient, VaultParams, VaultStatus};
+use soroban_sdk::{
+    testutils::{Address as _, AuthorizedFunction, MockAuth, MockAuthInvoke},
+    token::{StellarAssetClient, TokenClient},
+    Address, BytesN, Env, IntoVal, Symbol,
+};
+
+const STAKE: i128 = 1_000_000;
+
+struct Wallet<'a> {
+    env: Env,
+    vault: DisciplrVaultClient<'a>,
+    token: TokenClient<'a>,
+    params: VaultParams,
+}
+
+impl Wallet<'_> {
+    fn new() -> Self {
+        let env = Env::default();
+        let vault = DisciplrVaultClient::new(&env, &env.register(DisciplrVault, ()));
+        let asset = env.register_stellar_asset_contract_v2(Address::generate(&env));
+        let params = VaultParams {
+            usdc_token: asset.address(),
+            creator: Address::generate(&env),
+            amount: STAKE,
+            start_timestamp: 100,
+            end_timestamp: 1_000,
+            milestone_hash: BytesN::from_array(&env, &[1u8; 32]),
+            verifier: Some(Address::generate(&env)),
+            success_destination: Address::generate(&env),
+            failure_destination: Address::generate(&env),
+            is_public: false,
+            arbiter: None,
+        };
+        StellarAssetClient::new(&env, &asset.address())
+            .mock_all_auths()
+            .mint(&params.creator, &STAKE);
+
+        Wallet {
+            token: TokenClient::new(&env, &asset.address()),
+            env,
+            vault,
+            params,
+        }
+    }
+
+    /// The creator's stake transfer, as nested under the create call.
+    fn stake_transfer(&self) -> MockAuthInvoke<'_> {
+        MockAuthInvoke {
+            contract: &self.params.usdc_token,
+            fn_name: "transfer",
+            args: (
+                self.params.creator.clone(),
+                self.vault.address.clone(),
+                STAKE,
+            )
+                .into_val(&self.env),
+            sub_invokes: &[],
+        }
+    }
+}
+
+#[test]
+fn test_one_signature_creates_and_funds_a_vault() {
+    let wallet = Wallet::new();
+    let env = &wallet.env;
+    let transfer = [wallet.stake_transfer()];
+    let vault_id = wallet
+        .vault
+        .mock_auths(&[MockAuth {
+            address: &wallet.params.creator,
+            invoke: &MockAuthInvoke {
+                contract: &wallet.vault.address,
+                fn_name: "create_vault_with_params",
+                args: (wallet.params.clone(),).into_val(env),
+                sub_invokes: &transfer,
+            },
+        }])
+        .create_vault_with_params(&wallet.params);
+
+    // One signer, one tree: the create call with the transfer nested under it.
+    let auths = env.auths();
+    assert_eq!(auths.len(), 1);
+    let (signer, root) = &auths[0];
+    assert_eq!(*signer, wallet.params.creator);
+    match &root.function {
+        AuthorizedFunction::Contract((contract, function, _)) => {
+            assert_eq!(*contract, wallet.vault.address);
+            assert_eq!(*function, Symbol::new(env, "create_vault_with_params"));
+        }
+        _ => panic!("expected a contract invocation"),
+    }
+    assert_eq!(root.sub_invocations.len(), 1);
+    match &root.sub_invocations[0].function {
+        AuthorizedFunction::Contract((contract, function, _)) => {
+            assert_eq!(*contract, wallet.params.usdc_token);
+            assert_eq!(*function, Symbol::new(env, "transfer"));
+        }
+        _ => panic!("expected a contract invocation"),
+    }
+
+    assert_eq!(wallet.token.balance(&wallet.params.creator), 0);
+    assert_eq!(wallet.token.balance(&wallet.vault.address), STAKE);
+    assert_eq!(
+        wallet
+            .token
+            .allowance(&wallet.params.creator, &wallet.vault.address),
+        0
+    );
+    assert_eq!(
+        wallet.vault.get_vault_state(&vault_id).unwrap().status,
+        VaultStatus::Active
+    );
+}
+
+#[test]
+fn test_positional_create_vault_is_funded_by_the_same_signature() {
+    let wallet = Wallet::new();
+    let env = &wallet.env;
+    let p = &wallet.params;
+    let transfer = [wallet.stake_transfer()];
+    wallet
+        .vault
+        .mock_auths(&[MockAuth {
+            address: &p.creator,
+            invoke: &MockAuthInvoke {
+                contract: &wallet.vault.address,
+                fn_name: "create_vault",
+                args: (
+                    p.usdc_token.clone(),
+                    p.creator.clone(),
+                    p.amount,
+                    p.start_timestamp,
+                    p.end_timestamp,
+                    p.milestone_hash.clone(),
+                    p.verifier.clone(),
+                    p.success_destination.clone(),
+                    p.failure_destination.clone(),
+                    p.is_public,
+                )
+                    .into_val(env),
+                sub_invokes: &transfer,
+            },
+        }])
+        .create_vault(
+            &p.usdc_token,
+            &p.creator,
+            &p.amount,
+            &p.start_timestamp,
+            &p.end_timestamp,
+            &p.milestone_hash,
+            &p.verifier,
+            &p.success_destination,
+            &p.failure_destination,
+            &p.is_public,
+        );
+    assert_eq!(env.auths().len(), 1);
+    assert_eq!(wallet.token.balance(&wallet.vault.address), STAKE);
+}
+
+#[test]
+fn test_signature_without_the_nested_transfer_is_rejected() {
+    let wallet = Wallet::new();
+    let env = &wallet.env;
+    // The transfer is not covered by the creator's tree, so nothing else can authorize it.
+    let result = wallet
+        .vault
+        .mock_auths(&[MockAuth {
+            address: &wallet.params.creator,
+            invoke: &MockAuthInvoke {
+                contract: &wallet.vault.address,
+                fn_name: "create_vault_with_params",
+                args: (wallet.params.clone(),).into_val(env),
+                sub_invokes: &[],
+            },
+        }])
+        .try_create_vault_with_params(&wallet.params);
+    assert!(result.is_err());
+    assert_eq!(wallet.token.balance(&wallet.params.creator), STAKE);
+}